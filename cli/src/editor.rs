@@ -0,0 +1,214 @@
+use std::path::PathBuf;
+
+use rustyline::{
+    completion::{Completer, Pair},
+    highlight::Highlighter,
+    hint::Hinter,
+    validate::Validator,
+    Context, Helper,
+};
+
+use crate::app;
+
+/// The instructions known to the parser, completed at the start of every word.
+const OPCODES: &[&str] = &[
+    "add",
+    "adv.push_mapval",
+    "adv.push_mapvaln",
+    "adv_loadw",
+    "adv_pipe",
+    "adv_push",
+    "and",
+    "assert",
+    "assert_eq",
+    "assert_eqw",
+    "assertz",
+    "begin",
+    "cdrop",
+    "cdropw",
+    "cswap",
+    "cswapw",
+    "debug",
+    "div",
+    "drop",
+    "dropw",
+    "dup",
+    "dupw",
+    "else",
+    "end",
+    "eq",
+    "eqw",
+    "exec",
+    "exp",
+    "ext2add",
+    "ext2div",
+    "ext2inv",
+    "ext2mul",
+    "ext2neg",
+    "ext2sub",
+    "gt",
+    "gte",
+    "if.true",
+    "inv",
+    "is_odd",
+    "loc_load",
+    "loc_loadw",
+    "loc_store",
+    "loc_storew",
+    "lt",
+    "lte",
+    "mem_load",
+    "mem_loadw",
+    "mem_store",
+    "mem_storew",
+    "movdn",
+    "movdnw",
+    "movup",
+    "movupw",
+    "mul",
+    "neg",
+    "neq",
+    "not",
+    "or",
+    "padw",
+    "pow2",
+    "print",
+    "proc",
+    "push",
+    "repeat",
+    "sdepth",
+    "sub",
+    "swap",
+    "swapdw",
+    "swapw",
+    "syscall",
+    "u32checked_add",
+    "u32checked_and",
+    "u32checked_div",
+    "u32checked_divmod",
+    "u32checked_eq",
+    "u32checked_gt",
+    "u32checked_gte",
+    "u32checked_lt",
+    "u32checked_lte",
+    "u32checked_max",
+    "u32checked_min",
+    "u32checked_mod",
+    "u32checked_mul",
+    "u32checked_neq",
+    "u32checked_not",
+    "u32checked_or",
+    "u32checked_popcnt",
+    "u32checked_rotl",
+    "u32checked_rotr",
+    "u32checked_shl",
+    "u32checked_shr",
+    "u32checked_sub",
+    "u32checked_xor",
+    "u32overflowing_add",
+    "u32overflowing_add3",
+    "u32overflowing_madd",
+    "u32overflowing_mul",
+    "u32overflowing_sub",
+    "u32unchecked_div",
+    "u32unchecked_divmod",
+    "u32unchecked_gt",
+    "u32unchecked_gte",
+    "u32unchecked_lt",
+    "u32unchecked_lte",
+    "u32unchecked_max",
+    "u32unchecked_min",
+    "u32unchecked_mod",
+    "u32unchecked_popcnt",
+    "u32unchecked_rotl",
+    "u32unchecked_rotr",
+    "u32unchecked_shl",
+    "u32unchecked_shr",
+    "u32wrapping_add",
+    "u32wrapping_add3",
+    "u32wrapping_madd",
+    "u32wrapping_mul",
+    "u32wrapping_sub",
+    "use",
+    "while.true",
+    "xor",
+];
+
+/// The instructions that take the name of a procedure after a dot.
+const PROC_CALLS: &[&str] = &["exec.", "call.", "syscall."];
+
+/// Completes REPL commands and instructions, and procedure names after `exec.`, `call.` and
+/// `syscall.`.
+pub struct MasmHelper {
+    /// The procedures of the program, updated after every command.
+    pub procs: Vec<String>,
+    commands: Vec<String>,
+}
+
+impl Default for MasmHelper {
+    fn default() -> Self {
+        MasmHelper {
+            procs: Vec::new(),
+            commands: app()
+                .get_subcommands()
+                .map(|command| command.get_name().to_string())
+                .collect(),
+        }
+    }
+}
+
+impl Completer for MasmHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let start = line[..pos].rfind(char::is_whitespace).map_or(0, |i| i + 1);
+        let word = &line[start..pos];
+
+        let pair = |candidate: String| Pair {
+            display: candidate.clone(),
+            replacement: candidate,
+        };
+
+        if let Some(call) = PROC_CALLS.iter().find(|call| word.starts_with(*call)) {
+            let name = &word[call.len()..];
+            let procs = self
+                .procs
+                .iter()
+                .filter(|proc| proc.starts_with(name))
+                .map(|proc| pair(format!("{call}{proc}")))
+                .collect();
+            return Ok((start, procs));
+        }
+
+        let mut candidates: Vec<Pair> = OPCODES
+            .iter()
+            .map(|opcode| opcode.to_string())
+            .chain(self.commands.iter().cloned())
+            .filter(|candidate| candidate.starts_with(word))
+            .map(pair)
+            .collect();
+        candidates.sort_by(|a, b| a.replacement.cmp(&b.replacement));
+        candidates.dedup_by(|a, b| a.replacement == b.replacement);
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for MasmHelper {
+    type Hint = String;
+}
+
+impl Highlighter for MasmHelper {}
+
+impl Validator for MasmHelper {}
+
+impl Helper for MasmHelper {}
+
+/// Returns the file the REPL history is kept in, in the home directory.
+pub fn history_file() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".rust-masm-history"))
+}
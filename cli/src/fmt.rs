@@ -0,0 +1,113 @@
+use std::process::ExitCode;
+
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use rust_masm::{format_masm, FormatOptions, ImmediateStyle, Indent, Layout};
+
+pub fn command() -> Command {
+    Command::new("fmt")
+        .about("Formats Miden assembly files")
+        .long_about("Formats Miden assembly files in place, or checks that they are formatted")
+        .arg(
+            Arg::new("files")
+                .required(true)
+                .value_parser(value_parser!(String))
+                .num_args(1..)
+                .help("The .masm files to format"),
+        )
+        .arg(
+            Arg::new("check")
+                .long("check")
+                .action(ArgAction::SetTrue)
+                .help("Exits with an error if any file is not formatted, without writing it"),
+        )
+        .arg(
+            Arg::new("spaces")
+                .long("spaces")
+                .value_parser(value_parser!(usize))
+                .num_args(1)
+                .help("Indents with the given number of spaces instead of tabs"),
+        )
+        .arg(
+            Arg::new("grouped")
+                .long("grouped")
+                .action(ArgAction::SetTrue)
+                .help("Keeps instructions written on the same line together"),
+        )
+        .arg(
+            Arg::new("immediates")
+                .long("immediates")
+                .value_parser(["preserve", "decimal", "hex"])
+                .default_value("preserve")
+                .help("How push immediates are written"),
+        )
+        .arg(
+            Arg::new("no-sort-imports")
+                .long("no-sort-imports")
+                .action(ArgAction::SetTrue)
+                .help("Keeps use statements in their original order"),
+        )
+}
+
+fn options(matches: &ArgMatches) -> FormatOptions {
+    FormatOptions {
+        indent: match matches.get_one::<usize>("spaces") {
+            Some(n) => Indent::Spaces(*n),
+            None => Indent::Tabs,
+        },
+        layout: if matches.get_flag("grouped") {
+            Layout::Grouped
+        } else {
+            Layout::OnePerLine
+        },
+        immediates: match matches.get_one::<String>("immediates").map(|s| s.as_str()) {
+            Some("decimal") => ImmediateStyle::Decimal,
+            Some("hex") => ImmediateStyle::Hex,
+            _ => ImmediateStyle::Preserve,
+        },
+        sort_imports: !matches.get_flag("no-sort-imports"),
+    }
+}
+
+pub fn run(matches: &ArgMatches) -> ExitCode {
+    let options = options(matches);
+    let check = matches.get_flag("check");
+    let mut failed = false;
+
+    for file in matches.get_many::<String>("files").unwrap() {
+        let source = match std::fs::read_to_string(file) {
+            Ok(source) => source,
+            Err(e) => {
+                eprintln!("{}: {}", file, e);
+                failed = true;
+                continue;
+            }
+        };
+
+        let formatted = match format_masm(&source, &options) {
+            Ok(formatted) => formatted,
+            Err(e) => {
+                eprintln!("{}: {}", file, e);
+                failed = true;
+                continue;
+            }
+        };
+
+        if formatted == source {
+            continue;
+        }
+
+        if check {
+            println!("{} is not formatted", file);
+            failed = true;
+        } else if let Err(e) = std::fs::write(file, formatted) {
+            eprintln!("{}: {}", file, e);
+            failed = true;
+        }
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -0,0 +1,289 @@
+use clap::{value_parser, Arg, ArgAction, Command};
+use rust_masm::MidenProgram;
+
+fn hex() -> Arg {
+    Arg::new("hex")
+        .long("hex")
+        .short('x')
+        .action(ArgAction::SetTrue)
+        .help("Prints values in hexadecimal")
+}
+
+fn mem() -> Command {
+    Command::new("mem")
+        .about("Prints a region of memory")
+        .long_flag("mem")
+        .long_about("Prints the words written to memory between two addresses, one word per row")
+        .args([
+            Arg::new("start")
+                .required(true)
+                .value_parser(value_parser!(u32))
+                .index(1)
+                .num_args(1)
+                .help("The first address to print"),
+            Arg::new("end")
+                .required(false)
+                .value_parser(value_parser!(u32))
+                .index(2)
+                .num_args(1)
+                .help("The last address to print, defaults to the first address"),
+            hex(),
+        ])
+}
+
+fn loc() -> Command {
+    Command::new("loc")
+        .about("Prints the locals of the last procedure")
+        .long_flag("loc")
+        .long_about("Prints the locals of the procedure that returned last, one word per row")
+        .arg(hex())
+}
+
+fn advice() -> Command {
+    Command::new("advice")
+        .about("Prints the advice provider")
+        .long_flag("advice")
+        .long_about("Prints the advice stack, the advice map and the size of the Merkle store")
+        .arg(hex())
+}
+
+fn stack() -> Command {
+    Command::new("stack")
+        .about("Prints the stack")
+        .long_flag("stack")
+        .long_about("Prints the top of the stack, one word per row")
+        .args([
+            Arg::new("n")
+                .required(false)
+                .value_parser(value_parser!(usize))
+                .index(1)
+                .num_args(1)
+                .help("The number of values to print, defaults to the whole stack"),
+            hex(),
+        ])
+}
+
+fn dump_state() -> Command {
+    Command::new("dump-state")
+        .about("Saves the state of the simulator")
+        .long_flag("dump-state")
+        .long_about(
+            "Saves the stack, memory, advice stack, advice map and Merkle store to a JSON file",
+        )
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_parser(value_parser!(String))
+                .index(1)
+                .num_args(1)
+                .help("The JSON file to write"),
+        )
+}
+
+fn load_state() -> Command {
+    Command::new("load-state")
+        .about("Restores the state of the simulator")
+        .long_flag("load-state")
+        .long_about("Restores the stack, memory, advice stack, advice map and Merkle store from a JSON file written by dump-state")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_parser(value_parser!(String))
+                .index(1)
+                .num_args(1)
+                .help("The JSON file to read"),
+        )
+}
+
+pub const HELP: &str = "
+Inspection Options:\n
+    --mem, mem <start> [<end>]          Prints the words in memory between two addresses
+    --loc, loc                          Prints the locals of the last procedure
+    --advice, advice                    Prints the advice stack, advice map and Merkle store
+    --stack, stack [n]                  Prints the top n values of the stack
+    --dump-state, dump-state <file>     Saves the simulator state to a JSON file
+    --load-state, load-state <file>     Restores the simulator state from a JSON file
+
+    Add --hex to mem, loc, advice or stack to print values in hexadecimal
+";
+
+pub fn inspect() -> Command {
+    Command::new("inspect")
+        .about("Inspection Options")
+        .long_flag("inspect")
+        .long_about("Inspection Options")
+}
+
+pub fn commands() -> Vec<Command> {
+    vec![
+        inspect(),
+        mem(),
+        loc(),
+        advice(),
+        stack(),
+        dump_state(),
+        load_state(),
+    ]
+}
+
+fn format_value(value: u64, hex: bool) -> String {
+    if hex {
+        format!("{value:#018x}")
+    } else {
+        value.to_string()
+    }
+}
+
+/// Renders rows of up to four values as a table with a label column, right aligning every column.
+fn word_table(label: &str, rows: Vec<(String, Vec<u64>)>, hex: bool) -> String {
+    let header: Vec<String> = std::iter::once(label.to_string())
+        .chain((0..4).map(|i| i.to_string()))
+        .collect();
+    let cells: Vec<Vec<String>> = rows
+        .into_iter()
+        .map(|(label, values)| {
+            std::iter::once(label)
+                .chain(values.iter().map(|value| format_value(*value, hex)))
+                .collect()
+        })
+        .collect();
+
+    let widths: Vec<usize> = (0..header.len())
+        .map(|column| {
+            std::iter::once(&header)
+                .chain(cells.iter())
+                .filter_map(|row| row.get(column))
+                .map(|cell| cell.len())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let render = |row: &Vec<String>| {
+        row.iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!("{cell:>width$}"))
+            .collect::<Vec<String>>()
+            .join(" | ")
+    };
+
+    let mut table = render(&header);
+    table.push('\n');
+    table.push_str(
+        &widths
+            .iter()
+            .map(|width| "-".repeat(*width))
+            .collect::<Vec<String>>()
+            .join("-+-"),
+    );
+    for row in cells.iter() {
+        table.push('\n');
+        table.push_str(&render(row));
+    }
+    table
+}
+
+/// Splits a list of values into word-aligned rows labelled by the position of their first value.
+fn word_rows(values: Vec<u64>) -> Vec<(String, Vec<u64>)> {
+    values
+        .chunks(4)
+        .enumerate()
+        .map(|(i, word)| ((i * 4).to_string(), word.to_vec()))
+        .collect()
+}
+
+/// Renders the top `n` values of the stack, or the whole stack, four values per row.
+pub fn render_stack(program: &MidenProgram, n: Option<usize>, hex: bool) -> String {
+    let values: Vec<u64> = program
+        .get_stack()
+        .iter()
+        .take(n.unwrap_or(usize::MAX))
+        .map(|value| (*value).into())
+        .collect();
+
+    format!(
+        "depth : {}\n\n{}",
+        program.get_stack_depth(),
+        word_table("position", word_rows(values), hex)
+    )
+}
+
+/// Renders the words written to memory between `start` and `end`, one word per row.
+pub fn render_memory(program: &MidenProgram, start: u32, end: Option<u32>, hex: bool) -> String {
+    let end = end.unwrap_or(start);
+    let mut addresses: Vec<&u32> = program
+        .get_ram_memory()
+        .keys()
+        .filter(|address| (start..=end).contains(*address))
+        .collect();
+    addresses.sort();
+
+    if addresses.is_empty() {
+        return format!(
+            "No memory written between {start} and {end}, unwritten words read as zeros"
+        );
+    }
+
+    let rows = addresses
+        .into_iter()
+        .map(|address| {
+            let word = program.get_ram_memory()[address];
+            (
+                address.to_string(),
+                word.iter().map(|value| (*value).into()).collect(),
+            )
+        })
+        .collect();
+    word_table("address", rows, hex)
+}
+
+/// Renders the locals of the procedure that returned last, one word per row.
+pub fn render_locals(program: &MidenProgram, hex: bool) -> String {
+    let Some((name, locals)) = program.get_last_loc_frame() else {
+        return "No procedure was executed".to_string();
+    };
+    if locals.is_empty() {
+        return format!("{name} has no locals");
+    }
+
+    let rows = locals
+        .iter()
+        .enumerate()
+        .map(|(index, word)| {
+            (
+                index.to_string(),
+                word.iter().map(|value| (*value).into()).collect(),
+            )
+        })
+        .collect();
+    format!("{name}\n{}", word_table("local", rows, hex))
+}
+
+/// Renders the advice stack four values per row, the advice map and the size of the Merkle store.
+pub fn render_advice(program: &MidenProgram, hex: bool) -> String {
+    let mut advice = format!("advice stack : {} values\n", program.advice_stack.len());
+    if !program.advice_stack.is_empty() {
+        let rows = word_rows(program.advice_stack.iter().copied().collect());
+        advice.push_str(&format!("\n{}\n", word_table("position", rows, hex)));
+    }
+    advice.push('\n');
+
+    advice.push_str(&format!(
+        "advice map : {} entries\n",
+        program.advice_map.len()
+    ));
+    for (key, values) in program.advice_map.iter() {
+        let values: Vec<String> = values
+            .iter()
+            .map(|value| format_value(*value, hex))
+            .collect();
+        advice.push_str(&format!("\n{key} : [{}]", values.join(", ")));
+    }
+
+    let nodes = program
+        .merkle_store
+        .as_ref()
+        .map_or(0, |store| store.num_internal_nodes());
+    advice.push_str(&format!("\n\nmerkle store : {nodes} nodes"));
+    advice
+}
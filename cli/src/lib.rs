@@ -1,16 +1,30 @@
+mod editor;
 mod field;
+mod fmt;
+mod inspect;
 mod io;
 mod manipulation;
-mod u32;
+mod masm;
+mod prove;
+mod run;
+mod session;
 mod utils;
+mod verify;
 
+pub use editor::{history_file, MasmHelper};
 pub use field::HELP as FIELD_HELP;
+pub use inspect::{
+    render_advice, render_locals, render_memory, render_stack, HELP as INSPECT_HELP,
+};
 pub use io::HELP as IO_HELP;
 pub use manipulation::HELP as MANIPULATION_HELP;
+pub use masm::{block_depth, is_masm};
+pub use session::{Session, HELP as EDIT_HELP};
 
-use clap::Command;
+use std::process::ExitCode;
+
+use clap::{ArgMatches, Command};
 
-// --u32, u32                          Description for u32 commands
 pub const APP_HELP: &'static str = "
 USAGE:
     rust-masm [FLAGS] [OPTIONS] [SUBCOMMAND]
@@ -23,11 +37,17 @@ SUBCOMMANDS:
     --io, io                            Description for io commands
     --field, field                      Description for field commands
     --manipulation, manipulation        Description for manipulation commands
+    --inspect, inspect                  Description for inspection commands
+    --edit, edit                        Description for undo, history and editing commands
 
     --masm, masm                        Prints the current masm Implementation
     --save, save <filename>             Saves the current state of the stack
     --end, end                          Ends the program
 
+Any other input is read as Miden assembly, for example `push.1 push.2 u32checked_add`.
+Blocks such as `if.true ... end`, `repeat.4 ... end` and `proc.name ... end` can span
+several lines and are simulated once they are closed.
+
 For more information about a specific command, use `help <command>`
 ";
 
@@ -43,6 +63,31 @@ pub fn app() -> Command {
         .subcommands(io::commands())
         .subcommands(field::commands())
         .subcommands(manipulation::commands())
+        .subcommands(inspect::commands())
+        .subcommands(session::commands())
         .subcommands(utils::commands())
         .override_help(APP_HELP)
 }
+
+/// The non-interactive commands, used when the CLI is started with arguments.
+pub fn cli() -> Command {
+    Command::new("rust-masm")
+        .about("A CLI for Rust MASM")
+        .version("0.1.0")
+        .author("KorieDrakeChaney")
+        .subcommand_required(true)
+        .subcommand(fmt::command())
+        .subcommand(prove::command())
+        .subcommand(run::command())
+        .subcommand(verify::command())
+}
+
+pub fn run(matches: &ArgMatches) -> ExitCode {
+    match matches.subcommand() {
+        Some(("fmt", fmt_matches)) => fmt::run(fmt_matches),
+        Some(("prove", prove_matches)) => prove::run(prove_matches),
+        Some(("run", run_matches)) => run::run(run_matches),
+        Some(("verify", verify_matches)) => verify::run(verify_matches),
+        _ => ExitCode::FAILURE,
+    }
+}
@@ -1,36 +1,121 @@
-use rust_masm::MidenProgram;
-use rust_masm_cli::{app, APP_HELP, APP_VERSION, FIELD_HELP, IO_HELP, MANIPULATION_HELP};
-use std::io::{self, Write};
+use rust_masm::{MidenProgram, ProgramState};
+use rust_masm_cli::{
+    app, block_depth, cli, history_file, is_masm, render_advice, render_locals, render_memory,
+    render_stack, run, MasmHelper, Session, APP_HELP, APP_VERSION, EDIT_HELP, FIELD_HELP,
+    INSPECT_HELP, IO_HELP, MANIPULATION_HELP,
+};
+use rustyline::{error::ReadlineError, history::DefaultHistory, Editor};
+use std::io::Write;
+use std::process::ExitCode;
 use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
 
 const HELP: [&'static str; 4] = ["help", "h", "--help", "-h"];
 const VERSION: [&'static str; 4] = ["version", "-v", "-V", "--version"];
 
-fn main() {
-    let mut program = MidenProgram::new();
+/// The commands that leave the program untouched, so they are not recorded for undo.
+const READ_ONLY: [&str; 19] = [
+    "end",
+    "io",
+    "field",
+    "manipulation",
+    "inspect",
+    "edit",
+    "mem",
+    "loc",
+    "advice",
+    "stack",
+    "dump-state",
+    "save",
+    "masm",
+    "undo",
+    "redo",
+    "list",
+    "delete",
+    "insert",
+    "history",
+];
+
+fn main() -> ExitCode {
+    if std::env::args().len() > 1 {
+        return run(&cli().get_matches());
+    }
+
+    let mut session = Session::new(MidenProgram::new());
     let mut stdout = StandardStream::stdout(ColorChoice::Always);
 
+    let mut editor: Editor<MasmHelper, DefaultHistory> = Editor::new().unwrap();
+    editor.set_helper(Some(MasmHelper::default()));
+    let history = history_file();
+    if let Some(history) = &history {
+        let _ = editor.load_history(history);
+    }
+
     let _ = stdout.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)));
 
     writeln!(&mut stdout, "{}", APP_HELP).unwrap();
 
-    'app_loop: loop {
-        let mut input = String::new();
+    // lines of a MASM block that is still open, and how many blocks are open
+    let mut block = String::new();
+    let mut depth: isize = 0;
 
-        let _ = stdout.set_color(ColorSpec::new().set_fg(Some(Color::Rgb(189, 252, 206))));
+    'app_loop: loop {
+        if let Some(helper) = editor.helper_mut() {
+            helper.procs = session.program.get_proc_names().to_vec();
+        }
 
-        println!("\nstack : {:?}\n", program.get_stack());
-        println!("ram : {:?}\n", program.get_ram_memory());
+        let prompt = if block.is_empty() {
+            let _ = stdout.set_color(ColorSpec::new().set_fg(Some(Color::Rgb(189, 252, 206))));
 
-        let _ = stdout.set_color(ColorSpec::new().set_fg(Some(Color::Green)));
+            let stack: Vec<u64> = session
+                .program
+                .get_stack()
+                .iter()
+                .map(|n| (*n).into())
+                .collect();
+            println!("\nstack : {:?}\n", stack);
 
-        print!("Command : ");
+            "Command : ".to_string()
+        } else {
+            "    ".repeat(depth as usize)
+        };
 
         let _ = stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)));
 
-        io::stdout().flush().unwrap();
+        let input = match editor.readline(&prompt) {
+            Ok(input) => input,
+            // ctrl-c abandons the block being typed
+            Err(ReadlineError::Interrupted) => {
+                block.clear();
+                depth = 0;
+                continue;
+            }
+            Err(_) => break 'app_loop,
+        };
+        let _ = editor.add_history_entry(input.as_str());
+
+        let first = input.split_whitespace().next().unwrap_or_default();
+        let command = HELP.contains(&first) || VERSION.contains(&first);
+
+        if !block.is_empty() || (is_masm(&input) && !command) {
+            block.push_str(&input);
+            block.push('\n');
+            depth += block_depth(&input);
+            if depth > 0 {
+                continue;
+            }
+
+            println!("____________________________________________________\n");
 
-        io::stdin().read_line(&mut input).unwrap();
+            session.checkpoint();
+            if let Err(e) = session.program.add_masm(&block) {
+                session.discard_checkpoint();
+                let _ = stdout.set_color(ColorSpec::new().set_fg(Some(Color::Red)).set_bold(true));
+                println!("{}", e);
+            }
+            block.clear();
+            depth = 0;
+            continue;
+        }
 
         println!("____________________________________________________\n");
 
@@ -61,6 +146,12 @@ fn main() {
 
         let matches = app().try_get_matches_from(args);
 
+        if let Ok(matches) = &matches {
+            if !matches!(matches.subcommand_name(), Some(name) if READ_ONLY.contains(&name)) {
+                session.checkpoint();
+            }
+        }
+
         match matches {
             Ok(matches) => match matches.subcommand() {
                 Some(("end", _)) => {
@@ -97,6 +188,116 @@ fn main() {
                         }
                     }
                 }
+                Some(("inspect", _)) => {
+                    match stdout.set_color(ColorSpec::new().set_fg(Some(Color::Cyan))) {
+                        Ok(_) => {
+                            let _ = writeln!(&mut stdout, "\n{}", INSPECT_HELP);
+                        }
+                        Err(_) => {
+                            println!("\n{}", INSPECT_HELP);
+                        }
+                    }
+                }
+                Some(("mem", mem_matches)) => {
+                    let start: u32 = *mem_matches.get_one("start").unwrap();
+                    let end: Option<u32> = mem_matches.get_one("end").copied();
+                    let hex = mem_matches.get_flag("hex");
+
+                    println!("{}", render_memory(&session.program, start, end, hex));
+                }
+                Some(("loc", loc_matches)) => {
+                    println!(
+                        "{}",
+                        render_locals(&session.program, loc_matches.get_flag("hex"))
+                    );
+                }
+                Some(("advice", advice_matches)) => {
+                    println!(
+                        "{}",
+                        render_advice(&session.program, advice_matches.get_flag("hex"))
+                    );
+                }
+                Some(("stack", stack_matches)) => {
+                    let n: Option<usize> = stack_matches.get_one("n").copied();
+                    let hex = stack_matches.get_flag("hex");
+
+                    println!("{}", render_stack(&session.program, n, hex));
+                }
+                Some(("dump-state", dump_matches)) => {
+                    let file: &String = dump_matches.get_one("file").unwrap();
+
+                    match session.program.get_state().write(file) {
+                        Ok(_) => println!("State saved to {file}"),
+                        Err(e) => println!("{e}"),
+                    }
+                }
+                Some(("load-state", load_matches)) => {
+                    let file: &String = load_matches.get_one("file").unwrap();
+
+                    match ProgramState::read(file)
+                        .and_then(|state| session.program.set_state(&state))
+                    {
+                        Ok(_) => {
+                            session.rebase();
+                            println!("State loaded from {file}");
+                        }
+                        Err(e) => {
+                            session.discard_checkpoint();
+                            println!("{e}");
+                        }
+                    }
+                }
+                Some(("edit", _)) => {
+                    match stdout.set_color(ColorSpec::new().set_fg(Some(Color::Cyan))) {
+                        Ok(_) => {
+                            let _ = writeln!(&mut stdout, "\n{}", EDIT_HELP);
+                        }
+                        Err(_) => {
+                            println!("\n{}", EDIT_HELP);
+                        }
+                    }
+                }
+                Some(("undo", _)) => {
+                    let undone = session.undo();
+                    if !undone {
+                        println!("Nothing to undo");
+                    }
+                }
+                Some(("redo", _)) => {
+                    let redone = session.redo();
+                    if !redone {
+                        println!("Nothing to redo");
+                    }
+                }
+                Some(("list", _)) => {
+                    let _ = stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)));
+                    println!("{}", session.list());
+                }
+                Some(("delete", delete_matches)) => {
+                    let index: usize = *delete_matches.get_one("index").unwrap();
+
+                    if let Err(e) = session.delete(index) {
+                        println!("{e}");
+                    }
+                }
+                Some(("insert", insert_matches)) => {
+                    let index: usize = *insert_matches.get_one("index").unwrap();
+                    let masm: Vec<String> = insert_matches
+                        .get_many::<String>("masm")
+                        .unwrap()
+                        .cloned()
+                        .collect();
+
+                    if let Err(e) = session.insert(index, &masm.join(" ")) {
+                        println!("{e}");
+                    }
+                }
+                Some(("history", _)) => {
+                    let _ = stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)));
+                    for (index, entry) in editor.history().iter().enumerate() {
+                        println!("{index:>4} | {entry}");
+                    }
+                }
                 Some(("save", save_matches)) => {
                     let mut file: String = save_matches
                         .get_one::<String>("filename")
@@ -104,15 +305,15 @@ fn main() {
                         .to_owned();
                     file.push_str(".masm");
 
-                    program.save(&file);
+                    session.program.save(&file);
                 }
                 Some(("masm", _)) => {
                     match stdout.set_color(ColorSpec::new().set_fg(Some(Color::White))) {
                         Ok(_) => {
-                            let _ = writeln!(&mut stdout, "\n{}", program.get_masm());
+                            let _ = writeln!(&mut stdout, "\n{}", session.program.get_masm());
                         }
                         Err(_) => {
-                            println!("\n{}", program.get_masm());
+                            println!("\n{}", session.program.get_masm());
                         }
                     }
                 }
@@ -120,36 +321,36 @@ fn main() {
                     if add_matches.contains_id("n") {
                         let n: u64 = *add_matches.get_one("n").unwrap();
 
-                        program.add_n(n);
+                        session.program.add_n(n);
                     } else {
-                        program.add();
+                        session.program.add();
                     }
                 }
                 Some(("sub", sub_matches)) => {
                     if sub_matches.contains_id("n") {
                         let n: u64 = *sub_matches.get_one("n").unwrap();
 
-                        program.sub_n(n);
+                        session.program.sub_n(n);
                     } else {
-                        program.sub();
+                        session.program.sub();
                     }
                 }
                 Some(("mul", mul_matches)) => {
                     if mul_matches.contains_id("n") {
                         let n: u64 = *mul_matches.get_one("n").unwrap();
 
-                        program.mul_n(n);
+                        session.program.mul_n(n);
                     } else {
-                        program.mul();
+                        session.program.mul();
                     }
                 }
                 Some(("div", div_matches)) => {
                     if div_matches.contains_id("n") {
                         let n: u64 = *div_matches.get_one("n").unwrap();
 
-                        program.div_n(n);
+                        session.program.div_n(n);
                     } else {
-                        program.div();
+                        session.program.div();
                     }
                 }
                 Some(("push", push_matches)) => {
@@ -157,96 +358,96 @@ fn main() {
                         let n: Vec<u64> = push_matches.get_many("n").unwrap().copied().collect();
 
                         for n in n {
-                            program.push(n);
+                            session.program.push(n);
                         }
                     }
                 }
 
                 Some(("inv", _)) => {
-                    program.inv();
+                    session.program.inv();
                 }
                 Some(("neg", _)) => {
-                    program.neg();
+                    session.program.neg();
                 }
                 Some(("pow2", _)) => {
-                    program.pow2();
+                    session.program.pow2();
                 }
                 Some(("exp", exp_matches)) => {
                     if exp_matches.contains_id("n") {
                         let n: u64 = *exp_matches.get_one("n").unwrap();
 
-                        program.exp_n(n);
+                        session.program.exp_n(n);
                     } else {
-                        program.exp();
+                        session.program.exp();
                     }
                 }
                 Some(("and", _)) => {
-                    program.and();
+                    session.program.and();
                 }
                 Some(("or", _)) => {
-                    program.or();
+                    session.program.or();
                 }
                 Some(("movup", movup_matches)) => {
                     let n: usize = *movup_matches.get_one("index").unwrap();
 
-                    program.movup_n(n);
+                    session.program.movup_n(n);
                 }
                 Some(("movdn", movdn_matches)) => {
                     let n: usize = *movdn_matches.get_one("index").unwrap();
 
-                    program.movdn_n(n);
+                    session.program.movdn_n(n);
                 }
                 Some(("movupw", movupw_matches)) => {
                     let n: usize = *movupw_matches.get_one("index").unwrap();
 
-                    program.movupw_n(n);
+                    session.program.movupw_n(n);
                 }
                 Some(("movdnw", movdnw_matches)) => {
                     let n: usize = *movdnw_matches.get_one("index").unwrap();
 
-                    program.movdnw_n(n);
+                    session.program.movdnw_n(n);
                 }
                 Some(("padw", _)) => {
-                    program.padw();
+                    session.program.padw();
                 }
                 Some(("swap", swap_matches)) => {
                     if swap_matches.contains_id("index") {
                         let n: usize = *swap_matches.get_one("index").unwrap();
 
-                        program.swap_n(n);
+                        session.program.swap_n(n);
                     } else {
-                        program.swap();
+                        session.program.swap();
                     }
                 }
                 Some(("swapw", swapw_matches)) => {
                     if swapw_matches.contains_id("index") {
                         let n: usize = *swapw_matches.get_one("index").unwrap();
 
-                        program.swapw_n(n);
+                        session.program.swapw_n(n);
                     } else {
-                        program.swapw();
+                        session.program.swapw();
                     }
                 }
                 Some(("dup", dup_matches)) => {
                     if dup_matches.contains_id("index") {
                         let n: usize = *dup_matches.get_one("index").unwrap();
 
-                        program.dup_n(n);
+                        session.program.dup_n(n);
                     } else {
-                        program.dup();
+                        session.program.dup();
                     }
                 }
                 Some(("drop", _)) => {
-                    program.drop();
+                    session.program.drop();
                 }
 
                 Some(("mem_store", mem_store_matches)) => {
                     if mem_store_matches.contains_id("address") {
                         let n: u32 = *mem_store_matches.get_one("address").unwrap();
 
-                        program.mem_store_n(n);
+                        session.program.mem_store_n(n);
                     } else {
-                        program.mem_store();
+                        session.program.mem_store();
                     }
                 }
 
@@ -254,9 +455,9 @@ fn main() {
                     if mem_load_matches.contains_id("address") {
                         let n: u32 = *mem_load_matches.get_one("address").unwrap();
 
-                        program.mem_load_n(n);
+                        session.program.mem_load_n(n);
                     } else {
-                        program.mem_load();
+                        session.program.mem_load();
                     }
                 }
 
@@ -264,9 +465,9 @@ fn main() {
                     if mem_loadw_matches.contains_id("address") {
                         let n: u32 = *mem_loadw_matches.get_one("address").unwrap();
 
-                        program.mem_load_w_n(n);
+                        session.program.mem_load_w_n(n);
                     } else {
-                        program.mem_load_w();
+                        session.program.mem_load_w();
                     }
                 }
 
@@ -274,27 +475,27 @@ fn main() {
                     if mem_storew_matches.contains_id("address") {
                         let n: u32 = *mem_storew_matches.get_one("address").unwrap();
 
-                        program.mem_store_w_n(n);
+                        session.program.mem_store_w_n(n);
                     } else {
-                        program.mem_store_w();
+                        session.program.mem_store_w();
                     }
                 }
 
                 Some(("xor", _)) => {
-                    program.xor();
+                    session.program.xor();
                 }
 
                 Some(("not", _)) => {
-                    program.not();
+                    session.program.not();
                 }
 
                 Some(("eq", eq_matches)) => {
                     if eq_matches.contains_id("n") {
                         let n: u64 = *eq_matches.get_one("n").unwrap();
 
-                        program.eq_n(n);
+                        session.program.eq_n(n);
                     } else {
-                        program.eq();
+                        session.program.eq();
                     }
                 }
 
@@ -302,38 +503,49 @@ fn main() {
                     if eq_matches.contains_id("n") {
                         let n: u64 = *eq_matches.get_one("n").unwrap();
 
-                        program.neq_n(n);
+                        session.program.neq_n(n);
                     } else {
-                        program.neq();
+                        session.program.neq();
                     }
                 }
 
                 Some(("lt", _)) => {
-                    program.lt();
+                    session.program.lt();
                 }
 
                 Some(("lte", _)) => {
-                    program.lte();
+                    session.program.lte();
                 }
 
                 Some(("gt", _)) => {
-                    program.gt();
+                    session.program.gt();
                 }
 
                 Some(("gte", _)) => {
-                    program.gte();
+                    session.program.gte();
                 }
 
                 Some(("is_odd", _)) => {
-                    program.is_odd();
+                    session.program.is_odd();
                 }
 
                 Some((_, _)) => {}
                 None => {}
             },
+            // a command word used with MASM arguments, as in `dup dup add`
             Err(e) => {
-                println!("{}", e);
+                session.checkpoint();
+                if session.program.add_masm(&input).is_err() {
+                    session.discard_checkpoint();
+                    println!("{}", e);
+                }
             }
         }
     }
+
+    if let Some(history) = &history {
+        let _ = editor.save_history(history);
+    }
+
+    ExitCode::SUCCESS
 }
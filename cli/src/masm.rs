@@ -0,0 +1,31 @@
+use crate::app;
+
+/// Returns the number of blocks a line of Miden assembly opens minus the number it closes, so
+/// the REPL knows when a multi-line block such as `if.true ... end` or `proc.x ... end` is
+/// complete.
+pub fn block_depth(line: &str) -> isize {
+    let code = line.split('#').next().unwrap_or_default();
+
+    code.split_whitespace()
+        .map(|word| {
+            let name = word.split('.').next().unwrap_or_default();
+            match (name, word) {
+                (_, "begin" | "if.true" | "while.true") => 1,
+                ("repeat" | "proc" | "export", _) if word.contains('.') => 1,
+                (_, "end") => -1,
+                _ => 0,
+            }
+        })
+        .sum()
+}
+
+/// Returns whether a line typed in the REPL is Miden assembly rather than a REPL command: either
+/// its first word is not a command, or it takes its arguments the MASM way, as in `push.1`.
+pub fn is_masm(line: &str) -> bool {
+    match line.split_whitespace().next() {
+        Some(word) => {
+            word.contains('.') || (!word.starts_with('-') && app().find_subcommand(word).is_none())
+        }
+        None => false,
+    }
+}
@@ -0,0 +1,157 @@
+use std::{process::ExitCode, time::Instant};
+
+use clap::{value_parser, Arg, ArgMatches, Command};
+use rust_masm::{Inputs, MidenProgram, ProofFile, ProofHash, ProveOptions, SecurityLevel};
+
+pub fn command() -> Command {
+    Command::new("prove")
+        .about("Proves a Miden assembly program")
+        .long_about("Proves a Miden assembly program on the Miden VM and verifies the proof")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_parser(value_parser!(String))
+                .help("The .masm file to prove"),
+        )
+        .arg(
+            Arg::new("inputs")
+                .long("inputs")
+                .short('i')
+                .value_parser(value_parser!(String))
+                .num_args(1)
+                .help("A JSON inputs file with the operand stack and advice inputs"),
+        )
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .short('o')
+                .value_parser(value_parser!(String))
+                .num_args(1)
+                .help("Writes the proof and its public inputs and outputs to this file"),
+        )
+        .arg(
+            Arg::new("security")
+                .long("security")
+                .value_parser(["96", "128"])
+                .default_value("96")
+                .help("The security level of the proof in bits"),
+        )
+        .arg(
+            Arg::new("hash")
+                .long("hash")
+                .value_parser(["blake3", "rpo"])
+                .default_value("blake3")
+                .help("The hash function used to build the proof"),
+        )
+        .arg(
+            Arg::new("queries")
+                .long("queries")
+                .value_parser(value_parser!(usize))
+                .num_args(1)
+                .help("The number of queries, defaults to the preset of the security level"),
+        )
+        .arg(
+            Arg::new("blowup")
+                .long("blowup")
+                .value_parser(value_parser!(usize))
+                .num_args(1)
+                .help("The blowup factor, a power of 2 between 8 and 128, defaults to the preset of the security level"),
+        )
+}
+
+pub(crate) fn options(matches: &ArgMatches) -> ProveOptions {
+    ProveOptions {
+        security: match matches.get_one::<String>("security").map(|s| s.as_str()) {
+            Some("128") => SecurityLevel::Bits128,
+            _ => SecurityLevel::Bits96,
+        },
+        hash: match matches.get_one::<String>("hash").map(|s| s.as_str()) {
+            Some("rpo") => ProofHash::Rpo,
+            _ => ProofHash::Blake3,
+        },
+        num_queries: matches.get_one::<usize>("queries").copied(),
+        blowup_factor: matches.get_one::<usize>("blowup").copied(),
+    }
+}
+
+pub fn run(matches: &ArgMatches) -> ExitCode {
+    let file = matches.get_one::<String>("file").unwrap();
+
+    let source = match std::fs::read_to_string(file) {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("{}: {}", file, e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let inputs = match matches.get_one::<String>("inputs") {
+        Some(inputs) => match Inputs::try_from_file(inputs) {
+            Ok(inputs) => Some(inputs),
+            Err(e) => {
+                eprintln!("{}", e);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => None,
+    };
+
+    let parsed = match inputs {
+        Some(inputs) => MidenProgram::parse_with_inputs(&source, inputs),
+        None => MidenProgram::parse(&source),
+    };
+
+    let mut program = match parsed {
+        Ok(program) => program,
+        Err(e) => {
+            eprintln!("{}: {}", file, e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // a rejected instruction is commented out, so the proof would not be of the source file
+    let errors = program.get_errors();
+    if !errors.is_empty() {
+        for error in errors.iter() {
+            eprintln!("{}: {}", file, error);
+        }
+        return ExitCode::FAILURE;
+    }
+
+    let result = match program.prove_with_options(&options(matches)) {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("{}: {}", file, e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let start = Instant::now();
+    let security_level = match result.verify(program.get_stack_inputs().clone()) {
+        Ok(security_level) => security_level,
+        Err(e) => {
+            eprintln!("{}: {}", file, e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let verification = start.elapsed();
+
+    println!("program hash : {}", result.program_hash);
+    println!("stack outputs : {:?}", result.stack_outputs.stack());
+    println!("security level : {} bits", security_level);
+    println!("proof size : {} bytes", result.proof.to_bytes().len());
+    println!("assembly : {} ms", result.timings.assembly.as_millis());
+    println!("proving : {} ms", result.timings.proving.as_millis());
+    println!("verification : {} ms", verification.as_millis());
+
+    if let Some(output) = matches.get_one::<String>("output") {
+        let proof_file = ProofFile::new(&result, program.get_stack_inputs().clone());
+        if let Err(e) = proof_file.write(output) {
+            eprintln!("{}: {}", output, e);
+            return ExitCode::FAILURE;
+        }
+        println!("proof written to {}", output);
+    }
+
+    ExitCode::SUCCESS
+}
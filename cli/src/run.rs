@@ -0,0 +1,113 @@
+use std::process::ExitCode;
+
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use rust_masm::{Inputs, MidenProgram, Outputs};
+
+use crate::inspect::{render_memory, render_stack};
+
+pub fn command() -> Command {
+    Command::new("run")
+        .about("Runs a Miden assembly program")
+        .long_about(
+            "Simulates a Miden assembly program, checks it on the Miden VM and prints the final stack and memory",
+        )
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_parser(value_parser!(String))
+                .help("The .masm file to run"),
+        )
+        .arg(
+            Arg::new("inputs")
+                .long("inputs")
+                .short('i')
+                .value_parser(value_parser!(String))
+                .num_args(1)
+                .help("A JSON inputs file with the operand stack and advice inputs"),
+        )
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .short('o')
+                .value_parser(value_parser!(String))
+                .num_args(1)
+                .help("Writes the stack, memory and program hash to a JSON outputs file"),
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .action(ArgAction::SetTrue)
+                .help("Prints the final state as a JSON state file"),
+        )
+}
+
+/// Simulates the program, then runs it on the Miden VM so that instructions rejected by the
+/// simulator, failed assertions and other execution errors end the command with a nonzero exit
+/// code.
+pub fn run(matches: &ArgMatches) -> ExitCode {
+    let file = matches.get_one::<String>("file").unwrap();
+
+    let inputs = match matches.get_one::<String>("inputs") {
+        Some(inputs) => match Inputs::try_from_file(inputs) {
+            Ok(inputs) => Some(inputs),
+            Err(e) => {
+                eprintln!("{}", e);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => None,
+    };
+
+    let parsed = match inputs {
+        Some(inputs) => MidenProgram::parse_from_file_with_inputs(file, inputs),
+        None => MidenProgram::parse_from_file(file),
+    };
+
+    let mut program = match parsed {
+        Ok(program) => program,
+        Err(e) => {
+            eprintln!("{}: {}", file, e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // a rejected instruction is commented out, so the VM would run a different program
+    let errors = program.get_errors();
+    if !errors.is_empty() {
+        for error in errors.iter() {
+            eprintln!("{}: {}", file, error);
+        }
+        return ExitCode::FAILURE;
+    }
+
+    let output = match program.execute_vm() {
+        Ok(output) => output,
+        Err(e) => {
+            eprintln!("{}: {}", file, e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if let Some(outputs_file) = matches.get_one::<String>("output") {
+        let outputs = Outputs {
+            memory: program.get_outputs().memory,
+            ..output.outputs()
+        };
+        if let Err(e) = outputs.write(outputs_file) {
+            eprintln!("{}: {}", outputs_file, e);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    if matches.get_flag("json") {
+        println!("{}", program.get_state().to_json());
+    } else {
+        println!("stack\n\n{}\n", render_stack(&program, None, false));
+        println!(
+            "memory\n\n{}",
+            render_memory(&program, 0, Some(u32::MAX), false)
+        );
+    }
+
+    ExitCode::SUCCESS
+}
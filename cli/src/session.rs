@@ -0,0 +1,267 @@
+use std::collections::VecDeque;
+
+use clap::{value_parser, Arg, Command};
+use rust_masm::{parse, tokenize, Instruction, MidenProgram, ProgramState};
+
+fn undo() -> Command {
+    Command::new("undo")
+        .about("Undoes the last command")
+        .long_flag("undo")
+        .long_about("Restores the program and the simulator state from before the last command")
+}
+
+fn redo() -> Command {
+    Command::new("redo")
+        .about("Redoes the last undone command")
+        .long_flag("redo")
+        .long_about("Restores the program and the simulator state undone by the last undo")
+}
+
+fn list() -> Command {
+    Command::new("list")
+        .about("Lists the instructions of the program")
+        .long_flag("list")
+        .long_about(
+            "Lists the instructions of the program with the index used by delete and insert",
+        )
+}
+
+fn delete() -> Command {
+    Command::new("delete")
+        .about("Deletes an instruction")
+        .long_flag("delete")
+        .long_about(
+            "Deletes the instruction at an index shown by list and simulates the program again",
+        )
+        .arg(
+            Arg::new("index")
+                .required(true)
+                .value_parser(value_parser!(usize))
+                .index(1)
+                .num_args(1)
+                .help("The index of the instruction to delete"),
+        )
+}
+
+fn insert() -> Command {
+    Command::new("insert")
+        .about("Inserts instructions")
+        .long_flag("insert")
+        .long_about("Inserts Miden assembly before the instruction at an index shown by list and simulates the program again")
+        .args([
+            Arg::new("index")
+                .required(true)
+                .value_parser(value_parser!(usize))
+                .index(1)
+                .num_args(1)
+                .help("The index to insert at, the number of instructions appends"),
+            Arg::new("masm")
+                .required(true)
+                .value_parser(value_parser!(String))
+                .index(2)
+                .num_args(1..)
+                .allow_hyphen_values(true)
+                .help("The Miden assembly to insert"),
+        ])
+}
+
+fn history() -> Command {
+    Command::new("history")
+        .about("Prints the command history")
+        .long_flag("history")
+        .long_about("Prints the commands typed in this and earlier sessions")
+}
+
+pub const HELP: &str = "
+Editing Options:\n
+    --undo, undo                        Undoes the last command
+    --redo, redo                        Redoes the last undone command
+    --list, list                        Lists the instructions of the program
+    --delete, delete <n>                Deletes the instruction at index n
+    --insert, insert <n> <masm>         Inserts Miden assembly before the instruction at index n
+    --history, history                  Prints the command history
+
+    delete and insert simulate the whole program again from the state the session started with,
+    or from the last state loaded with load-state
+";
+
+pub fn edit() -> Command {
+    Command::new("edit")
+        .about("Editing Options")
+        .long_flag("edit")
+        .long_about("Editing Options")
+}
+
+pub fn commands() -> Vec<Command> {
+    vec![
+        edit(),
+        undo(),
+        redo(),
+        list(),
+        delete(),
+        insert(),
+        history(),
+    ]
+}
+
+/// The program edited in the REPL, with the snapshots that make its commands undoable.
+pub struct Session {
+    pub program: MidenProgram,
+    /// The state that `delete` and `insert` simulate the instructions from.
+    initial: ProgramState,
+    undo: Vec<(MidenProgram, ProgramState)>,
+    redo: Vec<(MidenProgram, ProgramState)>,
+}
+
+impl Session {
+    pub fn new(program: MidenProgram) -> Self {
+        Session {
+            initial: program.get_state(),
+            program,
+            undo: Vec::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    /// Saves a snapshot of the program before a command changes it. Any undone command can no
+    /// longer be redone.
+    pub fn checkpoint(&mut self) {
+        self.undo.push((self.program.clone(), self.initial.clone()));
+        self.redo.clear();
+    }
+
+    /// Drops the snapshot saved by the last [`Session::checkpoint`], for a command that ended up
+    /// not changing the program.
+    pub fn discard_checkpoint(&mut self) {
+        self.undo.pop();
+    }
+
+    /// Makes the current state of the program the state that `delete` and `insert` simulate
+    /// from, as after `load-state`.
+    pub fn rebase(&mut self) {
+        self.initial = self.program.get_state();
+    }
+
+    /// Restores the program from before the last command. Returns `false` if there is nothing
+    /// to undo.
+    pub fn undo(&mut self) -> bool {
+        match self.undo.pop() {
+            Some((program, initial)) => {
+                let program = std::mem::replace(&mut self.program, program);
+                let initial = std::mem::replace(&mut self.initial, initial);
+                self.redo.push((program, initial));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Restores the program undone by the last [`Session::undo`]. Returns `false` if there is
+    /// nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        match self.redo.pop() {
+            Some((program, initial)) => {
+                let program = std::mem::replace(&mut self.program, program);
+                let initial = std::mem::replace(&mut self.initial, initial);
+                self.undo.push((program, initial));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Renders the instructions of the program, one per row with its index.
+    pub fn list(&self) -> String {
+        if self.program.instructions.is_empty() {
+            return "No instructions".to_string();
+        }
+
+        let width = (self.program.instructions.len() - 1).to_string().len();
+        self.program
+            .instructions
+            .iter()
+            .enumerate()
+            .map(|(index, instruction)| format!("{index:>width$} | {instruction}"))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// Deletes the instruction at `index` and simulates the program again.
+    pub fn delete(&mut self, index: usize) -> Result<(), String> {
+        if index >= self.program.instructions.len() {
+            return Err(format!(
+                "No instruction at index {index}, the program has {} instructions",
+                self.program.instructions.len()
+            ));
+        }
+
+        let mut instructions = self.program.instructions.clone();
+        instructions.remove(index);
+        self.resimulate(instructions)
+    }
+
+    /// Inserts the instructions of `masm` before the instruction at `index` and simulates the
+    /// program again.
+    pub fn insert(&mut self, index: usize, masm: &str) -> Result<(), String> {
+        if index > self.program.instructions.len() {
+            return Err(format!(
+                "Cannot insert at index {index}, the program has {} instructions",
+                self.program.instructions.len()
+            ));
+        }
+
+        let parsed = parse(tokenize(masm))?;
+        if !parsed.procedures.is_empty() || !parsed.imports.is_empty() {
+            return Err("Only instructions can be inserted".to_string());
+        }
+
+        let mut instructions = self.program.instructions.clone();
+        for (offset, instruction) in parsed.instructions.into_iter().enumerate() {
+            instructions.insert(index + offset, instruction);
+        }
+        self.resimulate(instructions)
+    }
+
+    fn resimulate(&mut self, instructions: VecDeque<Instruction>) -> Result<(), String> {
+        check_blocks(&instructions)?;
+        let snapshot = (self.program.clone(), self.initial.clone());
+        self.program
+            .replace_instructions(&self.initial, instructions)
+            .map_err(|e| e.to_string())?;
+        self.undo.push(snapshot);
+        self.redo.clear();
+        Ok(())
+    }
+}
+
+/// Checks that every `if.true`, `while.true` and `repeat` block of `instructions` is closed by an
+/// `end`, and that each `else` belongs to an `if.true`, so that an edit cannot leave a dangling
+/// `else` or `end` behind.
+fn check_blocks(instructions: &VecDeque<Instruction>) -> Result<(), String> {
+    // the opened blocks, with whether an `if.true` already had its `else`
+    let mut blocks: Vec<(&Instruction, bool)> = Vec::new();
+    for instruction in instructions {
+        match instruction {
+            Instruction::IF | Instruction::WHILE | Instruction::REPEAT(_) => {
+                blocks.push((instruction, false))
+            }
+            Instruction::ELSE => match blocks.last_mut() {
+                Some((Instruction::IF, has_else)) if !*has_else => *has_else = true,
+                _ => return Err("The edit leaves an `else` without an `if.true`".to_string()),
+            },
+            Instruction::END => {
+                blocks
+                    .pop()
+                    .ok_or("The edit leaves an `end` without a block to close")?;
+            }
+            _ => {}
+        }
+    }
+
+    match blocks.last() {
+        Some((block, _)) => Err(format!(
+            "The edit leaves a `{block}` block without an `end`"
+        )),
+        None => Ok(()),
+    }
+}
@@ -1 +0,0 @@
-use clap::{Arg, Command};
@@ -0,0 +1,42 @@
+use std::process::ExitCode;
+
+use clap::{value_parser, Arg, ArgMatches, Command};
+use rust_masm::ProofFile;
+
+pub fn command() -> Command {
+    Command::new("verify")
+        .about("Verifies a proof file")
+        .long_about("Verifies a proof file written by the prove command")
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .value_parser(value_parser!(String))
+                .help("The proof file to verify"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> ExitCode {
+    let file = matches.get_one::<String>("file").unwrap();
+
+    let proof_file = match ProofFile::read(file) {
+        Ok(proof_file) => proof_file,
+        Err(e) => {
+            eprintln!("{}: {}", file, e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match proof_file.verify() {
+        Ok(security_level) => {
+            println!("program hash : {}", proof_file.program_hash);
+            println!("stack outputs : {:?}", proof_file.stack_outputs.stack());
+            println!("security level : {} bits", security_level);
+            println!("proof verified");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}: {}", file, e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+use rust_masm::MidenProgram;
+use rust_masm_cli::Session;
+
+/// Writes `masm` to a file in a temporary directory and returns its path.
+fn masm_file(name: &str, masm: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("rust_masm_cli_tests");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    std::fs::write(&path, masm).unwrap();
+    path
+}
+
+fn cli(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rust-masm-cli"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_run_exit_code() {
+    let valid = masm_file("run_valid.masm", "begin push.1 push.2 add end");
+    let output = cli(&["run", valid.to_str().unwrap()]);
+    assert!(output.status.success());
+
+    for (name, masm) in [
+        ("run_div.masm", "begin push.1 push.0 div end"),
+        (
+            "run_overflow.masm",
+            "begin push.4294967295 push.1 u32checked_add end",
+        ),
+        ("run_assert.masm", "begin push.0 assert end"),
+    ] {
+        let file = masm_file(name, masm);
+        let output = cli(&["run", file.to_str().unwrap()]);
+        assert_eq!(output.status.code(), Some(1), "{masm}");
+        assert!(!output.stderr.is_empty(), "{masm}");
+    }
+}
+
+#[test]
+fn test_prove_rejected_instruction() {
+    let file = masm_file("prove_div.masm", "begin push.1 push.0 div end");
+    let proof = file.with_extension("proof");
+    let output = cli(&[
+        "prove",
+        file.to_str().unwrap(),
+        "--output",
+        proof.to_str().unwrap(),
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("prove_div.masm"));
+    assert!(!proof.exists());
+}
+
+#[test]
+fn test_prove_invalid_blowup() {
+    let file = masm_file("prove_blowup.masm", "begin push.1 push.2 add end");
+    for blowup in ["2", "4"] {
+        let output = cli(&["prove", file.to_str().unwrap(), "--blowup", blowup]);
+        assert_eq!(output.status.code(), Some(1), "{blowup}");
+        assert!(String::from_utf8_lossy(&output.stderr).contains("blowup factor"));
+    }
+}
+
+#[test]
+fn test_session_keeps_blocks_balanced() {
+    let program = MidenProgram::parse("begin push.1 if.true push.5 else push.6 end end").unwrap();
+    let mut session = Session::new(program);
+    let listing = session.list();
+
+    // deleting the opener or the `end` of a block would leave it unbalanced
+    for index in [1, 5] {
+        assert!(session.delete(index).is_err(), "{index}");
+        assert_eq!(session.list(), listing);
+    }
+    for masm in ["end", "else", "while.true", "repeat.2 push.1"] {
+        assert!(session.insert(2, masm).is_err(), "{masm}");
+        assert_eq!(session.list(), listing);
+    }
+
+    // edits inside a block are still allowed, as are whole blocks
+    session.delete(2).unwrap();
+    session.insert(0, "repeat.2 push.1 end").unwrap();
+    assert!(session.list().contains("repeat.2"));
+    let stack = session.program.get_stack();
+    assert_eq!((stack[0], stack[1]), (1u64.into(), 1u64.into()));
+}
+
+#[test]
+fn test_fmt_invalid() {
+    let source = "begin push.1 pushh.2 frobnicate end";
+    let file = masm_file("fmt_invalid.masm", source);
+    for args in [&["fmt"][..], &["fmt", "--check"]] {
+        let output = cli(&[args, &[file.to_str().unwrap()]].concat());
+        assert_eq!(output.status.code(), Some(1), "{args:?}");
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), source);
+    }
+}
+
+#[test]
+fn test_loc_shows_last_procedure() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rust-masm-cli"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"loc\nproc.keep.2 push.9 loc_store.1 end\nexec.keep\nloc\nend\n")
+        .unwrap();
+    let stdout = String::from_utf8(child.wait_with_output().unwrap().stdout).unwrap();
+
+    assert!(stdout.contains("No procedure was executed"));
+    assert!(stdout.contains("keep\nlocal"));
+    assert!(stdout.contains("    1 | 9 | 0 | 0 | 0"));
+}
@@ -0,0 +1,574 @@
+use std::collections::HashMap;
+
+use miden::math::Felt;
+
+use super::{BinOp, CompileError, Expr, Function, Stmt, Type, UnOp};
+use crate::{
+    program::{eqw_and_drop, MAX_REACH},
+    Instruction, Proc,
+};
+
+/// The most stack elements a function keeps its variables in. Further variables are spilled to
+/// locals, so variables stay within reach of `dup.15` while expressions are evaluated above
+/// them.
+pub(crate) const MAX_STACK_VARIABLES: usize = 8;
+
+/// The parameters and return type of a function, used to check calls.
+pub(crate) struct Signature {
+    pub params: Vec<Type>,
+    pub returns: Option<Type>,
+}
+
+/// An element of the compile-time model of the operand stack of a function.
+#[derive(Clone, Debug, PartialEq)]
+enum Slot {
+    Variable(String),
+    Temporary,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Storage {
+    Stack,
+    Local(u16),
+    /// The last use of the variable moved it off the stack.
+    Moved,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Variable {
+    ty: Type,
+    storage: Storage,
+}
+
+pub(crate) struct FunctionCompiler<'a> {
+    function: &'a Function,
+    signatures: &'a HashMap<String, Signature>,
+    proc: Proc,
+    /// The elements the function has pushed, the top of the stack last.
+    stack: Vec<Slot>,
+    /// The variables in scope, innermost scope last.
+    scopes: Vec<Vec<(String, Variable)>>,
+    /// The uses of each variable that have not been compiled yet.
+    remaining_uses: HashMap<String, usize>,
+    num_locals: u16,
+}
+
+impl<'a> FunctionCompiler<'a> {
+    pub fn new(function: &'a Function, signatures: &'a HashMap<String, Signature>) -> Self {
+        let mut remaining_uses = HashMap::new();
+        count_uses_in_block(&function.body, &mut remaining_uses);
+
+        FunctionCompiler {
+            function,
+            signatures,
+            proc: Proc::new(&function.name),
+            stack: Vec::new(),
+            scopes: vec![Vec::new()],
+            remaining_uses,
+            num_locals: 0,
+        }
+    }
+
+    pub fn compile(mut self) -> Result<Proc, CompileError> {
+        self.bind_params();
+
+        let body = &self.function.body[..];
+        for (i, stmt) in body.iter().enumerate() {
+            if let Stmt::Return(value) = stmt {
+                if i + 1 != body.len() {
+                    return Err(CompileError::MisplacedReturn(self.function.name.clone()));
+                }
+                self.compile_return(value)?;
+                return Ok(self.proc);
+            }
+            self.compile_stmt(stmt)?;
+        }
+
+        if self.function.returns.is_some() {
+            return Err(CompileError::MissingReturn(self.function.name.clone()));
+        }
+        let frame = self.stack.len();
+        self.drop_elements(frame);
+        Ok(self.proc)
+    }
+
+    fn emit(&mut self, instruction: Instruction) {
+        self.proc.add_instruction(instruction);
+    }
+
+    fn new_local(&mut self) -> u16 {
+        self.num_locals += 1;
+        self.num_locals - 1
+    }
+
+    fn stack_variables(&self) -> usize {
+        self.stack
+            .iter()
+            .filter(|slot| matches!(slot, Slot::Variable(_)))
+            .count()
+    }
+
+    fn depth_of(&self, name: &str) -> usize {
+        let index = self
+            .stack
+            .iter()
+            .rposition(|slot| *slot == Slot::Variable(name.to_string()))
+            .expect("a stack variable is on the stack model");
+        self.stack.len() - 1 - index
+    }
+
+    fn lookup(&self, name: &str) -> Result<Variable, CompileError> {
+        self.scopes
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.iter().rev())
+            .find(|(variable, _)| variable == name)
+            .map(|(_, variable)| *variable)
+            .ok_or_else(|| CompileError::UnknownVariable(name.to_string()))
+    }
+
+    fn set_storage(&mut self, name: &str, storage: Storage) {
+        if let Some((_, variable)) = self
+            .scopes
+            .iter_mut()
+            .rev()
+            .flat_map(|scope| scope.iter_mut().rev())
+            .find(|(variable, _)| variable == name)
+        {
+            variable.storage = storage;
+        }
+    }
+
+    fn declare(&mut self, name: &str, ty: Type, storage: Storage) {
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .push((name.to_string(), Variable { ty, storage }));
+    }
+
+    /// Records that a use of the variable was compiled, and returns whether it was the last one.
+    fn use_variable(&mut self, name: &str) -> bool {
+        match self.remaining_uses.get_mut(name) {
+            Some(count) => {
+                *count = count.saturating_sub(1);
+                *count == 0
+            }
+            None => true,
+        }
+    }
+
+    fn reach(&self, depth: usize) -> Result<usize, CompileError> {
+        if depth > MAX_REACH {
+            return Err(CompileError::StackTooDeep(self.function.name.clone()));
+        }
+        Ok(depth)
+    }
+
+    fn push_temporaries(&mut self, ty: Type) {
+        for _ in 0..ty.size() {
+            self.stack.push(Slot::Temporary);
+        }
+    }
+
+    fn pop_temporaries(&mut self, count: usize) {
+        for _ in 0..count {
+            self.stack.pop();
+        }
+    }
+
+    /// Drops the top `count` elements of the stack.
+    fn drop_elements(&mut self, count: usize) {
+        for _ in 0..count / 4 {
+            self.emit(Instruction::DropW);
+        }
+        for _ in 0..count % 4 {
+            self.emit(Instruction::Drop);
+        }
+        self.pop_temporaries(count);
+    }
+
+    /// Keeps as many leading felt and `u32` parameters on the stack as fit, and moves the rest
+    /// into locals, starting with the last parameter, which is on top.
+    fn bind_params(&mut self) {
+        let params = &self.function.params;
+        let on_stack = params
+            .iter()
+            .take_while(|(_, ty)| *ty != Type::Word)
+            .count()
+            .min(MAX_STACK_VARIABLES);
+
+        for (name, ty) in params.iter().take(on_stack) {
+            self.stack.push(Slot::Variable(name.clone()));
+            self.declare(name, *ty, Storage::Stack);
+        }
+
+        let mut spilled = Vec::new();
+        for (name, ty) in params.iter().skip(on_stack).rev() {
+            let local = self.new_local();
+            self.store_local(*ty, local);
+            spilled.push((name, *ty, local));
+        }
+        for (name, ty, local) in spilled.into_iter().rev() {
+            self.declare(name, ty, Storage::Local(local));
+        }
+    }
+
+    /// Stores the value on top of the stack into a local and drops it.
+    fn store_local(&mut self, ty: Type, local: u16) {
+        match ty {
+            Type::Word => {
+                self.emit(Instruction::LocStoreW(local));
+                self.emit(Instruction::DropW);
+            }
+            _ => self.emit(Instruction::LocStore(local)),
+        }
+    }
+
+    fn compile_block(&mut self, block: &[Stmt]) -> Result<(), CompileError> {
+        self.scopes.push(Vec::new());
+        for stmt in block {
+            self.compile_stmt(stmt)?;
+        }
+
+        // the variables of the block are the top of the stack
+        let scope = self.scopes.pop().unwrap();
+        let on_stack = scope
+            .iter()
+            .filter(|(_, variable)| variable.storage == Storage::Stack)
+            .count();
+        self.drop_elements(on_stack);
+        Ok(())
+    }
+
+    fn compile_stmt(&mut self, stmt: &Stmt) -> Result<(), CompileError> {
+        match stmt {
+            Stmt::Let(name, ty, value) => {
+                self.compile_typed(value, *ty, name)?;
+                if *ty != Type::Word && self.stack_variables() < MAX_STACK_VARIABLES {
+                    *self.stack.last_mut().unwrap() = Slot::Variable(name.clone());
+                    self.declare(name, *ty, Storage::Stack);
+                } else {
+                    let local = self.new_local();
+                    self.store_local(*ty, local);
+                    self.pop_temporaries(ty.size());
+                    self.declare(name, *ty, Storage::Local(local));
+                }
+            }
+            Stmt::Assign(name, value) => {
+                let variable = self.lookup(name)?;
+                self.compile_typed(value, variable.ty, name)?;
+                self.use_variable(name);
+                match variable.storage {
+                    Storage::Stack => {
+                        // the new value takes the place of the old one
+                        let depth = self.reach(self.depth_of(name))?;
+                        self.emit(Instruction::Swap(depth));
+                        self.emit(Instruction::Drop);
+                    }
+                    Storage::Local(local) => self.store_local(variable.ty, local),
+                    Storage::Moved => unreachable!("a moved variable has no later uses"),
+                }
+                self.pop_temporaries(variable.ty.size());
+            }
+            Stmt::If(condition, then, otherwise) => {
+                self.compile_typed(condition, Type::Felt, "if")?;
+                self.pop_temporaries(1);
+                self.emit(Instruction::IF);
+                self.compile_block(then)?;
+                if !otherwise.is_empty() {
+                    self.emit(Instruction::ELSE);
+                    self.compile_block(otherwise)?;
+                }
+                self.emit(Instruction::END);
+            }
+            Stmt::While(condition, body) => {
+                self.compile_typed(condition, Type::Felt, "while")?;
+                self.pop_temporaries(1);
+                self.emit(Instruction::WHILE);
+                self.compile_block(body)?;
+                // the condition is compiled again in a scope of its own, as it runs in the loop
+                self.scopes.push(Vec::new());
+                self.compile_typed(condition, Type::Felt, "while")?;
+                self.scopes.pop();
+                self.pop_temporaries(1);
+                self.emit(Instruction::END);
+            }
+            Stmt::Expr(value) => {
+                let size = match value {
+                    Expr::Call(name, args) => {
+                        self.compile_call(name, args)?.map_or(0, |ty| ty.size())
+                    }
+                    value => self.compile_expr(value)?.size(),
+                };
+                self.drop_elements(size);
+            }
+            Stmt::Return(_) => {
+                return Err(CompileError::MisplacedReturn(self.function.name.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Leaves the returned value in place of the variables of the function.
+    fn compile_return(&mut self, value: &Expr) -> Result<(), CompileError> {
+        let returns = self
+            .function
+            .returns
+            .ok_or_else(|| CompileError::UnexpectedReturn(self.function.name.clone()))?;
+        self.compile_typed(value, returns, "return")?;
+        self.pop_temporaries(returns.size());
+
+        let frame = self.stack.len();
+        match returns {
+            Type::Word => {
+                if frame > 0 {
+                    let local = self.new_local();
+                    self.emit(Instruction::LocStoreW(local));
+                    self.emit(Instruction::DropW);
+                    self.drop_elements(frame);
+                    self.emit(Instruction::PadW);
+                    self.emit(Instruction::LocLoadW(local));
+                }
+            }
+            _ => {
+                match frame {
+                    0 => {}
+                    1 => self.emit(Instruction::Swap(1)),
+                    frame => self.emit(Instruction::MovDn(self.reach(frame)?)),
+                }
+                self.drop_elements(frame);
+            }
+        }
+        Ok(())
+    }
+
+    fn compile_typed(
+        &mut self,
+        expr: &Expr,
+        expected: Type,
+        context: &str,
+    ) -> Result<(), CompileError> {
+        let found = self.compile_expr(expr)?;
+        if found != expected {
+            return Err(CompileError::TypeMismatch(
+                context.to_string(),
+                expected,
+                found,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Compiles an expression, which pushes its value on top of the stack, and returns its type.
+    fn compile_expr(&mut self, expr: &Expr) -> Result<Type, CompileError> {
+        match expr {
+            Expr::Felt(value) => {
+                self.emit(Instruction::Push(Felt::new(*value)));
+                self.push_temporaries(Type::Felt);
+                Ok(Type::Felt)
+            }
+            Expr::U32(value) => {
+                self.emit(Instruction::Push(Felt::from(*value)));
+                self.push_temporaries(Type::U32);
+                Ok(Type::U32)
+            }
+            Expr::Word(word) => {
+                for value in word {
+                    self.emit(Instruction::Push(Felt::new(*value)));
+                }
+                self.push_temporaries(Type::Word);
+                Ok(Type::Word)
+            }
+            Expr::Var(name) => self.compile_read(name),
+            Expr::Unary(op, operand) => {
+                let ty = self.compile_expr(operand)?;
+                if ty != Type::Felt {
+                    return Err(CompileError::UnsupportedOperation(op.to_string(), ty));
+                }
+                self.emit(match op {
+                    UnOp::Neg => Instruction::Neg,
+                    UnOp::Not => Instruction::Not,
+                });
+                Ok(Type::Felt)
+            }
+            Expr::Binary(op, lhs, rhs) => {
+                let ty = self.compile_expr(lhs)?;
+                let rhs_ty = self.compile_expr(rhs)?;
+                if ty != rhs_ty {
+                    return Err(CompileError::TypeMismatch(op.to_string(), ty, rhs_ty));
+                }
+                let result = self.compile_binary(*op, ty)?;
+                self.pop_temporaries(2 * ty.size());
+                self.push_temporaries(result);
+                Ok(result)
+            }
+            Expr::Call(name, args) => self
+                .compile_call(name, args)?
+                .ok_or_else(|| CompileError::NoReturnValue(name.clone())),
+        }
+    }
+
+    /// Pushes a copy of a variable. The last use of a variable of the function body moves it up
+    /// instead, as long as it is not inside a block that could leave the stack in another shape.
+    fn compile_read(&mut self, name: &str) -> Result<Type, CompileError> {
+        let variable = self.lookup(name)?;
+        let last_use = self.use_variable(name);
+
+        match variable.storage {
+            Storage::Stack => {
+                let depth = self.reach(self.depth_of(name))?;
+                if last_use && self.scopes.len() == 1 {
+                    match depth {
+                        0 => {}
+                        1 => self.emit(Instruction::Swap(1)),
+                        depth => self.emit(Instruction::MovUp(depth)),
+                    }
+                    let index = self.stack.len() - 1 - depth;
+                    self.stack.remove(index);
+                    self.set_storage(name, Storage::Moved);
+                } else {
+                    self.emit(Instruction::Dup(depth));
+                }
+            }
+            Storage::Local(local) => match variable.ty {
+                Type::Word => {
+                    self.emit(Instruction::PadW);
+                    self.emit(Instruction::LocLoadW(local));
+                }
+                _ => self.emit(Instruction::LocLoad(local)),
+            },
+            Storage::Moved => unreachable!("a moved variable has no later uses"),
+        }
+
+        self.push_temporaries(variable.ty);
+        Ok(variable.ty)
+    }
+
+    /// Emits the instructions of a binary operation on two operands of type `ty` and returns the
+    /// type of the result.
+    fn compile_binary(&mut self, op: BinOp, ty: Type) -> Result<Type, CompileError> {
+        let unsupported = || CompileError::UnsupportedOperation(op.to_string(), ty);
+
+        let (instruction, result) = match ty {
+            Type::Felt => match op {
+                BinOp::Add => (Instruction::Add, Type::Felt),
+                BinOp::Sub => (Instruction::Sub, Type::Felt),
+                BinOp::Mul => (Instruction::Mul, Type::Felt),
+                BinOp::Div => (Instruction::Div, Type::Felt),
+                BinOp::Rem => return Err(unsupported()),
+                BinOp::Eq => (Instruction::Eq, Type::Felt),
+                BinOp::Neq => (Instruction::Neq, Type::Felt),
+                BinOp::Lt => (Instruction::Lt, Type::Felt),
+                BinOp::Lte => (Instruction::Lte, Type::Felt),
+                BinOp::Gt => (Instruction::Gt, Type::Felt),
+                BinOp::Gte => (Instruction::Gte, Type::Felt),
+                BinOp::And => (Instruction::And, Type::Felt),
+                BinOp::Or => (Instruction::Or, Type::Felt),
+                BinOp::Xor => (Instruction::Xor, Type::Felt),
+            },
+            // u32 values compare like the felts they are, so the felt comparisons are used
+            Type::U32 => match op {
+                BinOp::Add => (Instruction::U32CheckedAdd, Type::U32),
+                BinOp::Sub => (Instruction::U32CheckedSub, Type::U32),
+                BinOp::Mul => (Instruction::U32CheckedMul, Type::U32),
+                BinOp::Div => (Instruction::U32CheckedDiv, Type::U32),
+                BinOp::Rem => (Instruction::U32CheckedMod, Type::U32),
+                BinOp::Eq => (Instruction::Eq, Type::Felt),
+                BinOp::Neq => (Instruction::Neq, Type::Felt),
+                BinOp::Lt => (Instruction::Lt, Type::Felt),
+                BinOp::Lte => (Instruction::Lte, Type::Felt),
+                BinOp::Gt => (Instruction::Gt, Type::Felt),
+                BinOp::Gte => (Instruction::Gte, Type::Felt),
+                BinOp::And => (Instruction::U32CheckedAnd, Type::U32),
+                BinOp::Or => (Instruction::U32CheckedOr, Type::U32),
+                BinOp::Xor => (Instruction::U32CheckedXor, Type::U32),
+            },
+            Type::Word => match op {
+                BinOp::Eq | BinOp::Neq => {
+                    for instruction in eqw_and_drop() {
+                        self.emit(instruction);
+                    }
+                    if op == BinOp::Neq {
+                        self.emit(Instruction::Not);
+                    }
+                    return Ok(Type::Felt);
+                }
+                _ => return Err(unsupported()),
+            },
+        };
+        self.emit(instruction);
+        Ok(result)
+    }
+
+    /// Pushes the arguments and calls the procedure of a function, and returns the type of its
+    /// return value, if any.
+    fn compile_call(&mut self, name: &str, args: &[Expr]) -> Result<Option<Type>, CompileError> {
+        let signature = self
+            .signatures
+            .get(name)
+            .ok_or_else(|| CompileError::UnknownFunction(name.to_string()))?;
+        if signature.params.len() != args.len() {
+            return Err(CompileError::ArgumentCount(
+                name.to_string(),
+                signature.params.len(),
+                args.len(),
+            ));
+        }
+
+        let mut size = 0;
+        for (arg, ty) in args.iter().zip(signature.params.iter()) {
+            self.compile_typed(arg, *ty, name)?;
+            size += ty.size();
+        }
+        self.emit(Instruction::Exec(name.to_string()));
+        self.pop_temporaries(size);
+
+        let returns = signature.returns;
+        if let Some(ty) = returns {
+            self.push_temporaries(ty);
+        }
+        Ok(returns)
+    }
+}
+
+fn count_uses_in_block(block: &[Stmt], uses: &mut HashMap<String, usize>) {
+    for stmt in block {
+        match stmt {
+            Stmt::Let(_, _, value) | Stmt::Expr(value) | Stmt::Return(value) => {
+                count_uses(value, uses)
+            }
+            Stmt::Assign(name, value) => {
+                count_uses(value, uses);
+                *uses.entry(name.clone()).or_default() += 1;
+            }
+            Stmt::If(condition, then, otherwise) => {
+                count_uses(condition, uses);
+                count_uses_in_block(then, uses);
+                count_uses_in_block(otherwise, uses);
+            }
+            Stmt::While(condition, body) => {
+                // the condition is compiled before the loop and again at the end of the body
+                count_uses(condition, uses);
+                count_uses_in_block(body, uses);
+                count_uses(condition, uses);
+            }
+        }
+    }
+}
+
+fn count_uses(expr: &Expr, uses: &mut HashMap<String, usize>) {
+    match expr {
+        Expr::Felt(_) | Expr::U32(_) | Expr::Word(_) => {}
+        Expr::Var(name) => *uses.entry(name.clone()).or_default() += 1,
+        Expr::Unary(_, operand) => count_uses(operand, uses),
+        Expr::Binary(_, lhs, rhs) => {
+            count_uses(lhs, uses);
+            count_uses(rhs, uses);
+        }
+        Expr::Call(_, args) => {
+            for arg in args {
+                count_uses(arg, uses);
+            }
+        }
+    }
+}
@@ -0,0 +1,312 @@
+use std::ops;
+
+/// The type of a value of the expression compiler.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Type {
+    /// A field element, which is also used for booleans, with `0` as false and `1` as true.
+    Felt,
+    /// A field element that is a valid `u32`, operated on with the checked `u32` instructions.
+    U32,
+    /// A word of four field elements.
+    Word,
+}
+
+impl Type {
+    /// Returns the number of stack elements a value of the type takes.
+    pub fn size(&self) -> usize {
+        match self {
+            Type::Felt | Type::U32 => 1,
+            Type::Word => 4,
+        }
+    }
+}
+
+impl std::fmt::Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::Felt => write!(f, "felt"),
+            Type::U32 => write!(f, "u32"),
+            Type::Word => write!(f, "word"),
+        }
+    }
+}
+
+/// A binary operation. Both operands must have the same type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    /// The remainder of a division, for `u32` values only.
+    Rem,
+    Eq,
+    Neq,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+    /// Logical and of booleans, or bitwise and of `u32` values.
+    And,
+    /// Logical or of booleans, or bitwise or of `u32` values.
+    Or,
+    /// Logical xor of booleans, or bitwise xor of `u32` values.
+    Xor,
+}
+
+impl std::fmt::Display for BinOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let symbol = match self {
+            BinOp::Add => "+",
+            BinOp::Sub => "-",
+            BinOp::Mul => "*",
+            BinOp::Div => "/",
+            BinOp::Rem => "%",
+            BinOp::Eq => "==",
+            BinOp::Neq => "!=",
+            BinOp::Lt => "<",
+            BinOp::Lte => "<=",
+            BinOp::Gt => ">",
+            BinOp::Gte => ">=",
+            BinOp::And => "&",
+            BinOp::Or => "|",
+            BinOp::Xor => "^",
+        };
+        write!(f, "{symbol}")
+    }
+}
+
+/// A unary operation on a felt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnOp {
+    /// The additive inverse of a felt.
+    Neg,
+    /// The logical not of a boolean.
+    Not,
+}
+
+impl std::fmt::Display for UnOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnOp::Neg => write!(f, "-"),
+            UnOp::Not => write!(f, "!"),
+        }
+    }
+}
+
+/// An expression, which leaves a single value on the stack.
+///
+/// Expressions are built with the constructors and the operators `+`, `-`, `*`, `/`, `%`, `&`,
+/// `|`, `^`, `!` and unary `-`, and with methods for the comparisons.
+///
+/// # Example
+///
+/// ```
+/// use rust_masm::Expr;
+///
+/// // (a + 1) * b < 10
+/// let expr = ((Expr::var("a") + Expr::felt(1)) * Expr::var("b")).lt(Expr::felt(10));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Felt(u64),
+    U32(u32),
+    /// A word, with its elements in memory order, so the first one ends up deepest on the stack.
+    Word([u64; 4]),
+    Var(String),
+    Unary(UnOp, Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    /// A call of a function of the module, with its arguments.
+    Call(String, Vec<Expr>),
+}
+
+impl Expr {
+    /// A felt constant.
+    pub fn felt(value: u64) -> Self {
+        Expr::Felt(value)
+    }
+
+    /// A `u32` constant.
+    pub fn u32(value: u32) -> Self {
+        Expr::U32(value)
+    }
+
+    /// A word constant, in memory order.
+    pub fn word(value: [u64; 4]) -> Self {
+        Expr::Word(value)
+    }
+
+    /// Reads a variable or parameter.
+    pub fn var(name: &str) -> Self {
+        Expr::Var(name.to_string())
+    }
+
+    /// Calls a function of the module.
+    pub fn call(name: &str, args: Vec<Expr>) -> Self {
+        Expr::Call(name.to_string(), args)
+    }
+
+    /// Applies a binary operation to the expression and `rhs`.
+    pub fn binary(self, op: BinOp, rhs: Expr) -> Self {
+        Expr::Binary(op, Box::new(self), Box::new(rhs))
+    }
+
+    /// `self == rhs`, as a boolean felt.
+    pub fn eq(self, rhs: Expr) -> Self {
+        self.binary(BinOp::Eq, rhs)
+    }
+
+    /// `self != rhs`, as a boolean felt.
+    pub fn neq(self, rhs: Expr) -> Self {
+        self.binary(BinOp::Neq, rhs)
+    }
+
+    /// `self < rhs`, as a boolean felt.
+    pub fn lt(self, rhs: Expr) -> Self {
+        self.binary(BinOp::Lt, rhs)
+    }
+
+    /// `self <= rhs`, as a boolean felt.
+    pub fn lte(self, rhs: Expr) -> Self {
+        self.binary(BinOp::Lte, rhs)
+    }
+
+    /// `self > rhs`, as a boolean felt.
+    pub fn gt(self, rhs: Expr) -> Self {
+        self.binary(BinOp::Gt, rhs)
+    }
+
+    /// `self >= rhs`, as a boolean felt.
+    pub fn gte(self, rhs: Expr) -> Self {
+        self.binary(BinOp::Gte, rhs)
+    }
+}
+
+macro_rules! binary_operator {
+    ($trait:ident, $method:ident, $op:expr) => {
+        impl ops::$trait for Expr {
+            type Output = Expr;
+
+            fn $method(self, rhs: Expr) -> Expr {
+                self.binary($op, rhs)
+            }
+        }
+    };
+}
+
+binary_operator!(Add, add, BinOp::Add);
+binary_operator!(Sub, sub, BinOp::Sub);
+binary_operator!(Mul, mul, BinOp::Mul);
+binary_operator!(Div, div, BinOp::Div);
+binary_operator!(Rem, rem, BinOp::Rem);
+binary_operator!(BitAnd, bitand, BinOp::And);
+binary_operator!(BitOr, bitor, BinOp::Or);
+binary_operator!(BitXor, bitxor, BinOp::Xor);
+
+impl ops::Neg for Expr {
+    type Output = Expr;
+
+    fn neg(self) -> Expr {
+        Expr::Unary(UnOp::Neg, Box::new(self))
+    }
+}
+
+impl ops::Not for Expr {
+    type Output = Expr;
+
+    fn not(self) -> Expr {
+        Expr::Unary(UnOp::Not, Box::new(self))
+    }
+}
+
+/// A statement of the body of a function.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Stmt {
+    /// Declares a variable, which is in scope until the end of the enclosing block.
+    Let(String, Type, Expr),
+    /// Assigns a new value to a variable or parameter.
+    Assign(String, Expr),
+    /// Runs the first block if the boolean condition is `1`, and the second block otherwise.
+    If(Expr, Vec<Stmt>, Vec<Stmt>),
+    /// Runs the block while the boolean condition is `1`.
+    While(Expr, Vec<Stmt>),
+    /// Evaluates an expression, usually a call, and drops its value.
+    Expr(Expr),
+    /// Returns a value. It must be the last statement of the function.
+    Return(Expr),
+}
+
+impl Stmt {
+    /// `let name: ty = value;`
+    pub fn local(name: &str, ty: Type, value: Expr) -> Self {
+        Stmt::Let(name.to_string(), ty, value)
+    }
+
+    /// `name = value;`
+    pub fn assign(name: &str, value: Expr) -> Self {
+        Stmt::Assign(name.to_string(), value)
+    }
+
+    /// `if condition { then } else { otherwise }`
+    pub fn if_else(condition: Expr, then: Vec<Stmt>, otherwise: Vec<Stmt>) -> Self {
+        Stmt::If(condition, then, otherwise)
+    }
+
+    /// `if condition { then }`
+    pub fn if_then(condition: Expr, then: Vec<Stmt>) -> Self {
+        Stmt::If(condition, then, Vec::new())
+    }
+
+    /// `while condition { body }`
+    pub fn while_loop(condition: Expr, body: Vec<Stmt>) -> Self {
+        Stmt::While(condition, body)
+    }
+
+    /// `return value;`
+    pub fn ret(value: Expr) -> Self {
+        Stmt::Return(value)
+    }
+}
+
+/// A function, which compiles to a procedure of the same name.
+///
+/// The arguments of a call are pushed in order, so the last parameter is on top of the stack
+/// when the procedure starts. The procedure consumes its arguments and leaves its return value,
+/// if any, in their place.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Function {
+    pub name: String,
+    pub params: Vec<(String, Type)>,
+    pub returns: Option<Type>,
+    pub body: Vec<Stmt>,
+}
+
+impl Function {
+    /// Creates a function with no parameters, no return value and an empty body.
+    pub fn new(name: &str) -> Self {
+        Function {
+            name: name.to_string(),
+            params: Vec::new(),
+            returns: None,
+            body: Vec::new(),
+        }
+    }
+
+    /// Adds a parameter after the existing ones.
+    pub fn with_param(mut self, name: &str, ty: Type) -> Self {
+        self.params.push((name.to_string(), ty));
+        self
+    }
+
+    /// Sets the type of the return value.
+    pub fn with_returns(mut self, ty: Type) -> Self {
+        self.returns = Some(ty);
+        self
+    }
+
+    /// Sets the statements of the body.
+    pub fn with_body(mut self, body: Vec<Stmt>) -> Self {
+        self.body = body;
+        self
+    }
+}
@@ -0,0 +1,208 @@
+mod codegen;
+mod ir;
+
+pub use ir::{BinOp, Expr, Function, Stmt, Type, UnOp};
+
+use std::collections::HashMap;
+
+use crate::Proc;
+use codegen::{FunctionCompiler, Signature};
+
+/// An error found while compiling a module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompileError {
+    UnknownVariable(String),
+    UnknownFunction(String),
+    DuplicateFunction(String),
+    RecursiveCall(String),
+    /// The context, the expected type and the type that was found.
+    TypeMismatch(String, Type, Type),
+    UnsupportedOperation(String, Type),
+    /// The function, the number of parameters and the number of arguments.
+    ArgumentCount(String, usize, usize),
+    NoReturnValue(String),
+    MissingReturn(String),
+    MisplacedReturn(String),
+    UnexpectedReturn(String),
+    StackTooDeep(String),
+}
+
+impl std::fmt::Display for CompileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownVariable(name) => write!(f, "Unknown variable {name}"),
+            Self::UnknownFunction(name) => write!(f, "Unknown function {name}"),
+            Self::DuplicateFunction(name) => write!(f, "Duplicate function name {name}"),
+            Self::RecursiveCall(name) => write!(f, "Function {name} calls itself, which Miden assembly does not support"),
+            Self::TypeMismatch(context, expected, found) => write!(f, "Type mismatch in {context}: expected {expected}, found {found}"),
+            Self::UnsupportedOperation(op, ty) => write!(f, "Operation {op} is not supported for {ty} values"),
+            Self::ArgumentCount(name, params, args) => write!(f, "Function {name} takes {params} arguments, but {args} were given"),
+            Self::NoReturnValue(name) => write!(f, "Function {name} does not return a value"),
+            Self::MissingReturn(name) => write!(f, "Function {name} must end with a return statement"),
+            Self::MisplacedReturn(name) => write!(f, "The return statement of function {name} must be its last statement"),
+            Self::UnexpectedReturn(name) => write!(f, "Function {name} has no return type, but returns a value"),
+            Self::StackTooDeep(name) => write!(f, "An expression of function {name} needs a value deeper than the 16 reachable stack elements"),
+        }
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+/// A set of functions that can call each other, compiled together to procedures.
+///
+/// Felt and `u32` variables are kept on the operand stack, where they are copied with `dup` and
+/// moved with `movup` on their last use, and assigned with `swap`. Words, and the variables
+/// that would be out of reach of `dup` on the stack, are spilled to the locals of the
+/// procedure. Calls compile to `exec`.
+///
+/// # Example
+///
+/// ```
+/// use rust_masm::{Expr, Function, MidenProgram, Module, Stmt, Type};
+///
+/// // fn sum_to(n: u32) -> u32 {
+/// //     let total: u32 = 0;
+/// //     while n > 0 { total = total + n; n = n - 1; }
+/// //     return total;
+/// // }
+/// let sum_to = Function::new("sum_to")
+///     .with_param("n", Type::U32)
+///     .with_returns(Type::U32)
+///     .with_body(vec![
+///         Stmt::local("total", Type::U32, Expr::u32(0)),
+///         Stmt::while_loop(
+///             Expr::var("n").gt(Expr::u32(0)),
+///             vec![
+///                 Stmt::assign("total", Expr::var("total") + Expr::var("n")),
+///                 Stmt::assign("n", Expr::var("n") - Expr::u32(1)),
+///             ],
+///         ),
+///         Stmt::ret(Expr::var("total")),
+///     ]);
+///
+/// let procs = Module::new().with_function(sum_to).compile().unwrap();
+///
+/// let mut program = MidenProgram::new();
+/// program.add_procs(procs);
+/// program.push(10);
+/// program.exec("sum_to");
+/// assert_eq!(program.get_stack()[0], 55u64.into());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Module {
+    pub functions: Vec<Function>,
+}
+
+impl Module {
+    /// Creates an empty module.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a function to the module.
+    pub fn with_function(mut self, function: Function) -> Self {
+        self.functions.push(function);
+        self
+    }
+
+    /// Compiles the functions of the module.
+    ///
+    /// # Returns
+    ///
+    /// A procedure for each function, ordered so that every procedure comes after the
+    /// procedures it calls, or the first error found.
+    pub fn compile(&self) -> Result<Vec<Proc>, CompileError> {
+        let mut signatures = HashMap::new();
+        for function in self.functions.iter() {
+            let signature = Signature {
+                params: function.params.iter().map(|(_, ty)| *ty).collect(),
+                returns: function.returns,
+            };
+            if signatures
+                .insert(function.name.clone(), signature)
+                .is_some()
+            {
+                return Err(CompileError::DuplicateFunction(function.name.clone()));
+            }
+        }
+
+        let mut order = Vec::new();
+        let mut visiting = Vec::new();
+        for function in self.functions.iter() {
+            self.visit(&function.name, &mut visiting, &mut order)?;
+        }
+
+        order
+            .into_iter()
+            .map(|function| FunctionCompiler::new(function, &signatures).compile())
+            .collect()
+    }
+
+    /// Adds a function after the functions it calls, depth first.
+    fn visit<'a>(
+        &'a self,
+        name: &str,
+        visiting: &mut Vec<String>,
+        order: &mut Vec<&'a Function>,
+    ) -> Result<(), CompileError> {
+        if order.iter().any(|function| function.name == name) {
+            return Ok(());
+        }
+        if visiting.iter().any(|visited| visited == name) {
+            return Err(CompileError::RecursiveCall(name.to_string()));
+        }
+        let function = self
+            .functions
+            .iter()
+            .find(|function| function.name == name)
+            .ok_or_else(|| CompileError::UnknownFunction(name.to_string()))?;
+
+        visiting.push(name.to_string());
+        let mut callees = Vec::new();
+        calls_in_block(&function.body, &mut callees);
+        for callee in callees {
+            self.visit(&callee, visiting, order)?;
+        }
+        visiting.pop();
+
+        order.push(function);
+        Ok(())
+    }
+}
+
+fn calls_in_block(block: &[Stmt], calls: &mut Vec<String>) {
+    for stmt in block {
+        match stmt {
+            Stmt::Let(_, _, value)
+            | Stmt::Assign(_, value)
+            | Stmt::Expr(value)
+            | Stmt::Return(value) => calls_in_expr(value, calls),
+            Stmt::If(condition, then, otherwise) => {
+                calls_in_expr(condition, calls);
+                calls_in_block(then, calls);
+                calls_in_block(otherwise, calls);
+            }
+            Stmt::While(condition, body) => {
+                calls_in_expr(condition, calls);
+                calls_in_block(body, calls);
+            }
+        }
+    }
+}
+
+fn calls_in_expr(expr: &Expr, calls: &mut Vec<String>) {
+    match expr {
+        Expr::Felt(_) | Expr::U32(_) | Expr::Word(_) | Expr::Var(_) => {}
+        Expr::Unary(_, operand) => calls_in_expr(operand, calls),
+        Expr::Binary(_, lhs, rhs) => {
+            calls_in_expr(lhs, calls);
+            calls_in_expr(rhs, calls);
+        }
+        Expr::Call(name, args) => {
+            calls.push(name.clone());
+            for arg in args {
+                calls_in_expr(arg, calls);
+            }
+        }
+    }
+}
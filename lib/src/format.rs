@@ -0,0 +1,282 @@
+use crate::{parse, tokenize};
+
+/// The indentation used for each nested block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Indent {
+    Tabs,
+    Spaces(usize),
+}
+
+impl Indent {
+    /// Returns the indentation string for the given block depth.
+    pub fn render(&self, depth: usize) -> String {
+        match self {
+            Indent::Tabs => "\t".repeat(depth),
+            Indent::Spaces(n) => " ".repeat(n * depth),
+        }
+    }
+}
+
+/// How instructions that share a source line are laid out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layout {
+    /// Every instruction is written on its own line.
+    OnePerLine,
+    /// Instructions written on the same source line stay on the same line. Block
+    /// instructions (`if.true`, `else`, `end`, ...) are always written on their own line.
+    Grouped,
+}
+
+/// How `push` immediates are written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImmediateStyle {
+    Preserve,
+    Decimal,
+    Hex,
+}
+
+/// Options for [`format_masm`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FormatOptions {
+    pub indent: Indent,
+    pub layout: Layout,
+    pub immediates: ImmediateStyle,
+    pub sort_imports: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            indent: Indent::Tabs,
+            layout: Layout::OnePerLine,
+            immediates: ImmediateStyle::Preserve,
+            sort_imports: true,
+        }
+    }
+}
+
+enum Line {
+    Blank,
+    Comment(String),
+    Code(Vec<String>, Option<String>),
+}
+
+#[derive(PartialEq)]
+enum BlockEdge {
+    Open,
+    Middle,
+    Close,
+    None,
+}
+
+fn block_edge(op: &str) -> BlockEdge {
+    match op.split('.').next().unwrap_or("") {
+        "begin" | "proc" | "export" | "if" | "while" | "repeat" => BlockEdge::Open,
+        "else" => BlockEdge::Middle,
+        "end" => BlockEdge::Close,
+        _ => BlockEdge::None,
+    }
+}
+
+fn is_import(line: &Line) -> bool {
+    match line {
+        Line::Code(ops, None) => ops.len() == 1 && ops[0].starts_with("use."),
+        _ => false,
+    }
+}
+
+fn format_immediate(value: &str, style: ImmediateStyle) -> String {
+    let parsed = match value.strip_prefix("0x") {
+        // long hex words are pushed as four elements and are always kept as written
+        Some(hex) if hex.len() < 16 => u64::from_str_radix(hex, 16).ok(),
+        Some(_) => None,
+        None => value.parse::<u64>().ok(),
+    };
+
+    match (parsed, style) {
+        (Some(n), ImmediateStyle::Decimal) => n.to_string(),
+        // the parser only accepts hex immediates with an even number of digits
+        (Some(n), ImmediateStyle::Hex) if n < 1 << 56 => {
+            let hex = format!("{:x}", n);
+            if hex.len() % 2 == 0 {
+                format!("0x{}", hex)
+            } else {
+                format!("0x0{}", hex)
+            }
+        }
+        _ => value.to_string(),
+    }
+}
+
+fn format_op(op: &str, options: &FormatOptions) -> String {
+    if options.immediates == ImmediateStyle::Preserve || !op.starts_with("push.") {
+        return op.to_string();
+    }
+
+    let mut parts = op.split('.');
+    let mut formatted = parts.next().unwrap_or("").to_string();
+    for part in parts {
+        formatted.push('.');
+        formatted.push_str(&format_immediate(part, options.immediates));
+    }
+    formatted
+}
+
+fn split_lines(source: &str) -> Vec<Line> {
+    source
+        .lines()
+        .map(|line| {
+            let (code, comment) = match line.find('#') {
+                Some(i) => (&line[..i], Some(line[i..].trim_end().to_string())),
+                None => (line, None),
+            };
+            let ops: Vec<String> = tokenize(code)
+                .iter()
+                .map(|token| token.parts().join("."))
+                .collect();
+
+            match (ops.is_empty(), comment) {
+                (true, None) => Line::Blank,
+                (true, Some(comment)) => Line::Comment(comment),
+                (false, comment) => Line::Code(ops, comment),
+            }
+        })
+        .collect()
+}
+
+fn sort_imports(lines: &mut [Line]) {
+    let mut start = 0;
+    while start < lines.len() {
+        if !is_import(&lines[start]) {
+            start += 1;
+            continue;
+        }
+        let mut end = start;
+        while end < lines.len() && is_import(&lines[end]) {
+            end += 1;
+        }
+        lines[start..end].sort_by(|a, b| match (a, b) {
+            (Line::Code(a, _), Line::Code(b, _)) => a.cmp(b),
+            _ => std::cmp::Ordering::Equal,
+        });
+        start = end;
+    }
+}
+
+/// Formats Miden assembly source code.
+///
+/// Blocks are indented with the same rules as [`crate::MidenProgram::get_masm`], line comments
+/// and `#!` doc comments are kept in place, runs of blank lines are collapsed and consecutive
+/// `use` statements are sorted.
+///
+/// # Arguments
+///
+/// * `source` - The Miden assembly source to format.
+/// * `options` - The formatting options.
+///
+/// # Returns
+///
+/// The formatted source, or the error of the parser if the source is not valid Miden assembly,
+/// or an error message if the blocks of the source are unbalanced.
+///
+/// # Example
+///
+/// ```
+/// use rust_masm::{format_masm, FormatOptions};
+/// let formatted = format_masm("begin push.1 push.2 add end", &FormatOptions::default()).unwrap();
+/// assert_eq!(formatted, "begin\n\tpush.1\n\tpush.2\n\tadd\nend\n");
+/// ```
+pub fn format_masm(source: &str, options: &FormatOptions) -> Result<String, String> {
+    parse(tokenize(source))?;
+
+    let mut lines = split_lines(source);
+    if options.sort_imports {
+        sort_imports(&mut lines);
+    }
+
+    let mut out: Vec<String> = Vec::new();
+    let mut depth: usize = 0;
+
+    for (line_number, line) in lines.iter().enumerate() {
+        match line {
+            Line::Blank => {
+                if out.last().is_some_and(|last| !last.is_empty()) {
+                    out.push(String::new());
+                }
+            }
+            Line::Comment(comment) => {
+                out.push(format!("{}{}", options.indent.render(depth), comment));
+            }
+            Line::Code(ops, comment) => {
+                let mut group: Vec<String> = Vec::new();
+
+                for op in ops {
+                    let edge = block_edge(op);
+                    if (edge != BlockEdge::None || options.layout == Layout::OnePerLine)
+                        && !group.is_empty()
+                    {
+                        out.push(format!(
+                            "{}{}",
+                            options.indent.render(depth),
+                            group.join(" ")
+                        ));
+                        group.clear();
+                    }
+
+                    match edge {
+                        BlockEdge::Open => {
+                            out.push(format!("{}{}", options.indent.render(depth), op));
+                            depth += 1;
+                        }
+                        BlockEdge::Middle | BlockEdge::Close => {
+                            if depth == 0 {
+                                return Err(format!(
+                                    "Unexpected `{}` on line {}",
+                                    op,
+                                    line_number + 1
+                                ));
+                            }
+                            out.push(format!("{}{}", options.indent.render(depth - 1), op));
+                            if edge == BlockEdge::Close {
+                                depth -= 1;
+                            }
+                        }
+                        BlockEdge::None => group.push(format_op(op, options)),
+                    }
+                }
+
+                if !group.is_empty() {
+                    out.push(format!(
+                        "{}{}",
+                        options.indent.render(depth),
+                        group.join(" ")
+                    ));
+                }
+
+                if let Some(comment) = comment {
+                    if let Some(last) = out.last_mut() {
+                        last.push(' ');
+                        last.push_str(comment);
+                    }
+                }
+
+                // top level items are separated by a blank line, like in `get_masm`
+                if depth == 0 && block_edge(ops.last().unwrap()) == BlockEdge::Close {
+                    out.push(String::new());
+                }
+            }
+        }
+    }
+
+    if depth != 0 {
+        return Err("Expected `end` before the end of the file".to_string());
+    }
+
+    while out.last().is_some_and(|last| last.is_empty()) {
+        out.pop();
+    }
+
+    let mut formatted = out.join("\n");
+    formatted.push('\n');
+    Ok(formatted)
+}
@@ -1,13 +1,13 @@
 use std::collections::HashMap;
 
 use miden::{
-    crypto::{MerkleStore, MerkleTree, NodeIndex, PartialMerkleTree, RpoDigest, SimpleSmt},
-    math::Felt,
-    Word,
+    crypto::{MerkleStore, MerkleTree, NodeIndex, PartialMerkleTree, Rpo256, RpoDigest, SimpleSmt},
+    math::{Felt, StarkField},
+    AdviceInputs, StackInputs, Word,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum MerkleData {
     /// String representation of a merkle tree. The merkle tree is represented as a vector of
     /// 32 byte hex strings where each string represents a leaf in the tree.
@@ -25,25 +25,69 @@ pub enum MerkleData {
     PartialMerkleTree(Vec<((u8, u64), String)>),
 }
 
-#[derive(Deserialize)]
+/// The inputs of a program, in the format of the `.inputs` files of the Miden CLI.
+///
+/// Inputs can be read from a file with [`Inputs::try_from_file`], written back with
+/// [`Inputs::to_json`], or built with [`Inputs::builder`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct Inputs {
     /// String representation of the initial operand stack, composed of chained field elements.
     pub operand_stack: Vec<u64>,
     /// Optional string representation of the initial advice stack, composed of chained field
     /// elements.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub advice_stack: Option<Vec<u64>>,
     /// Optional map of 32 byte hex strings to vectors of u64s representing the initial advice map.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub advice_map: Option<HashMap<String, Vec<u64>>>,
     /// Optional vector of merkle data which will be loaded into the initial merkle store. Merkle
     /// data is represented as 32 byte hex strings and node indexes are represented as u64s.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub merkle_store: Option<Vec<MerkleData>>,
 }
 
 impl Inputs {
+    /// Reads the inputs from a JSON inputs file.
+    ///
+    /// # Panics
+    ///
+    /// If the file cannot be read or is not a valid inputs file. Use [`Inputs::try_from_file`]
+    /// to get the error instead.
     pub fn from_file(file: &str) -> Self {
-        let file = std::fs::File::open(file).unwrap();
-        let reader = std::io::BufReader::new(file);
-        serde_json::from_reader(reader).unwrap()
+        Self::try_from_file(file).unwrap()
+    }
+
+    /// Reads the inputs from a JSON inputs file, or returns an error if the file cannot be read or
+    /// is not a valid inputs file.
+    pub fn try_from_file(file: &str) -> Result<Self, String> {
+        let reader = std::fs::File::open(file)
+            .map(std::io::BufReader::new)
+            .map_err(|e| format!("failed to open inputs file `{file}` - {e}"))?;
+        serde_json::from_reader(reader)
+            .map_err(|e| format!("failed to parse inputs file `{file}` - {e}"))
+    }
+
+    /// Serializes the inputs to JSON, in the format read by [`Inputs::from_file`].
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Writes the inputs to a JSON inputs file.
+    pub fn write(&self, file: &str) -> std::io::Result<()> {
+        std::fs::write(file, self.to_json())
+    }
+
+    /// Returns a builder to create inputs from field elements, words and digests.
+    pub fn builder() -> InputsBuilder {
+        InputsBuilder::default()
+    }
+
+    /// Checks that every value is a valid field element, that every hex string is 32 bytes and
+    /// that the Merkle data forms valid trees.
+    pub fn validate(&self) -> Result<(), String> {
+        self.parse_stack_inputs()?;
+        self.parse_advice_inputs()?;
+        Ok(())
     }
 
     pub fn new(
@@ -60,6 +104,41 @@ impl Inputs {
         }
     }
 
+    /// Parse the operand stack into the `StackInputs` of the VM.
+    pub fn parse_stack_inputs(&self) -> Result<StackInputs, String> {
+        if let Some(value) = self.operand_stack.iter().find(|v| **v >= Felt::MODULUS) {
+            return Err(format!(
+                "operand stack value `{value}` is not a valid field element"
+            ));
+        }
+        StackInputs::try_from_values(self.operand_stack.iter().copied())
+            .map_err(|e| format!("failed to parse the operand stack - {e}"))
+    }
+
+    /// Parse the advice stack, advice map and Merkle store into the `AdviceInputs` of the VM.
+    pub fn parse_advice_inputs(&self) -> Result<AdviceInputs, String> {
+        let mut advice_inputs = AdviceInputs::default();
+
+        if let Some(advice_stack) = &self.advice_stack {
+            if let Some(value) = advice_stack.iter().find(|v| **v >= Felt::MODULUS) {
+                return Err(format!(
+                    "advice stack value `{value}` is not a valid field element"
+                ));
+            }
+            advice_inputs = advice_inputs
+                .with_stack_values(advice_stack.iter().copied())
+                .map_err(|e| format!("failed to parse the advice stack - {e}"))?;
+        }
+        if let Some(map) = self.parse_advice_map()? {
+            advice_inputs = advice_inputs.with_map(map);
+        }
+        if let Some(merkle_store) = self.parse_merkle_store()? {
+            advice_inputs = advice_inputs.with_merkle_store(merkle_store);
+        }
+
+        Ok(advice_inputs)
+    }
+
     /// Parse advice map data from the input file.
     pub fn parse_advice_map(&self) -> Result<Option<HashMap<[u8; 32], Vec<Felt>>>, String> {
         let advice_map = match &self.advice_map {
@@ -70,10 +149,7 @@ impl Inputs {
         let map = advice_map
             .iter()
             .map(|(k, v)| {
-                // decode hex key
-                let mut key = [0u8; 32];
-                hex::decode_to_slice(k, &mut key)
-                    .map_err(|e| format!("failed to decode advice map key `{k}` - {e}"))?;
+                let key = decode_hex(k, "advice map key")?;
 
                 // convert values to Felt
                 let values = v
@@ -105,7 +181,8 @@ impl Inputs {
                     let tree = MerkleTree::new(leaves)
                         .map_err(|e| format!("failed to parse a Merkle tree: {e}"))?;
                     merkle_store.extend(tree.inner_nodes());
-                    println!(
+                    log::debug!(
+                        target: "rust_masm",
                         "Added Merkle tree with root {} to the Merkle store",
                         tree.root()
                     );
@@ -115,7 +192,8 @@ impl Inputs {
                     let tree = SimpleSmt::with_leaves(u64::BITS as u8, entries)
                         .map_err(|e| format!("failed to parse a Sparse Merkle Tree: {e}"))?;
                     merkle_store.extend(tree.inner_nodes());
-                    println!(
+                    log::debug!(
+                        target: "rust_masm",
                         "Added Sparse Merkle tree with root {} to the Merkle store",
                         tree.root()
                     );
@@ -125,7 +203,8 @@ impl Inputs {
                     let tree = PartialMerkleTree::with_leaves(entries)
                         .map_err(|e| format!("failed to parse a Partial Merkle Tree: {e}"))?;
                     merkle_store.extend(tree.inner_nodes());
-                    println!(
+                    log::debug!(
+                        target: "rust_masm",
                         "Added Partial Merkle tree with root {} to the Merkle store",
                         tree.root()
                     );
@@ -173,11 +252,10 @@ impl Inputs {
             .collect()
     }
 
+    /// Parse a `Word` from 32 bytes of hex, with or without a `0x` prefix, with each element in
+    /// little-endian byte order.
     pub fn parse_word(word_hex: &str) -> Result<Word, String> {
-        let word_value = &word_hex[2..];
-        let mut word_data = [0u8; 32];
-        hex::decode_to_slice(word_value, &mut word_data)
-            .map_err(|e| format!("failed to decode `Word` from hex {word_hex} - {e}"))?;
+        let word_data = decode_hex(word_hex, "`Word`")?;
         let mut word = Word::default();
         for (i, value) in word_data.chunks(8).enumerate() {
             word[i] = Felt::try_from(value).map_err(|e| {
@@ -198,3 +276,176 @@ impl Default for Inputs {
         }
     }
 }
+
+/// Decode 32 bytes of hex, with or without a `0x` prefix.
+fn decode_hex(value: &str, name: &str) -> Result<[u8; 32], String> {
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    if digits.len() != 64 {
+        return Err(format!(
+            "failed to decode {name} from hex `{value}` - expected 64 hex digits, found {}",
+            digits.len()
+        ));
+    }
+
+    let mut bytes = [0u8; 32];
+    hex::decode_to_slice(digits, &mut bytes)
+        .map_err(|e| format!("failed to decode {name} from hex `{value}` - {e}"))?;
+    Ok(bytes)
+}
+
+/// Encode a word as hex with a `0x` prefix, in the format read by [`Inputs::parse_word`].
+fn word_to_hex(word: Word) -> String {
+    RpoDigest::from(word).to_string()
+}
+
+/// Values that can be added to the operand stack, the advice stack or the advice map of
+/// [`Inputs`]: integers, field elements, words and digests.
+pub trait InputValues {
+    /// Returns the field elements of the value as integers, in order.
+    fn into_input_values(self) -> Vec<u64>;
+}
+
+impl InputValues for u64 {
+    fn into_input_values(self) -> Vec<u64> {
+        vec![self]
+    }
+}
+
+impl InputValues for Felt {
+    fn into_input_values(self) -> Vec<u64> {
+        vec![self.as_int()]
+    }
+}
+
+impl InputValues for Word {
+    fn into_input_values(self) -> Vec<u64> {
+        self.iter().map(|n| n.as_int()).collect()
+    }
+}
+
+impl InputValues for RpoDigest {
+    fn into_input_values(self) -> Vec<u64> {
+        Word::from(self).into_input_values()
+    }
+}
+
+/// Builds [`Inputs`] from field elements, words and digests instead of integers and hex strings.
+///
+/// # Example
+///
+/// ```
+/// use rust_masm::{Inputs, MidenProgram};
+/// use miden::{crypto::RpoDigest, math::Felt, Word};
+///
+/// let key = RpoDigest::from([Felt::new(1), Felt::new(2), Felt::new(3), Felt::new(4)]);
+/// let leaves: Vec<Word> = (0..4).map(|n| [Felt::new(n), Felt::new(0), Felt::new(0), Felt::new(0)]).collect();
+///
+/// let inputs = Inputs::builder()
+///     .stack([1u64, 2])
+///     .advice_stack([Felt::new(3)])
+///     .advice_map_entry(key, [5u64, 6])
+///     .merkle_tree(leaves)
+///     .build()
+///     .unwrap();
+///
+/// let program = MidenProgram::parse_with_inputs("begin adv_push.1 add end", inputs).unwrap();
+/// assert_eq!(program.get_stack()[0], Felt::new(5));
+/// ```
+#[derive(Debug, Default)]
+pub struct InputsBuilder {
+    inputs: Inputs,
+}
+
+impl InputsBuilder {
+    /// Appends values to the operand stack, in the order of an inputs file.
+    pub fn stack<V: InputValues>(mut self, values: impl IntoIterator<Item = V>) -> Self {
+        self.inputs
+            .operand_stack
+            .extend(values.into_iter().flat_map(|v| v.into_input_values()));
+        self
+    }
+
+    /// Appends values to the advice stack, in the order of an inputs file.
+    pub fn advice_stack<V: InputValues>(mut self, values: impl IntoIterator<Item = V>) -> Self {
+        self.inputs
+            .advice_stack
+            .get_or_insert_with(Vec::new)
+            .extend(values.into_iter().flat_map(|v| v.into_input_values()));
+        self
+    }
+
+    /// Adds an entry to the advice map, replacing any values already stored under `key`.
+    pub fn advice_map_entry<V: InputValues>(
+        mut self,
+        key: RpoDigest,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        self.inputs
+            .advice_map
+            .get_or_insert_with(HashMap::new)
+            .insert(
+                word_to_hex(key.into()),
+                values
+                    .into_iter()
+                    .flat_map(|v| v.into_input_values())
+                    .collect(),
+            );
+        self
+    }
+
+    /// Adds values to the advice map under their RPO hash, computed with
+    /// `Rpo256::hash_elements`, as read by `adv.push_mapval` when the hash is on the stack.
+    pub fn hashed_advice_map_entry<V: InputValues>(
+        self,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        let values: Vec<u64> = values
+            .into_iter()
+            .flat_map(|v| v.into_input_values())
+            .collect();
+        let elements: Vec<Felt> = values.iter().map(|v| Felt::new(*v)).collect();
+        self.advice_map_entry(Rpo256::hash_elements(&elements), values)
+    }
+
+    /// Adds a Merkle tree with the given leaves to the Merkle store.
+    pub fn merkle_tree(self, leaves: impl IntoIterator<Item = Word>) -> Self {
+        let leaves = leaves.into_iter().map(word_to_hex).collect();
+        self.merkle_data(MerkleData::MerkleTree(leaves))
+    }
+
+    /// Adds a Sparse Merkle Tree with the given leaves, keyed by index, to the Merkle store.
+    pub fn sparse_merkle_tree(self, leaves: impl IntoIterator<Item = (u64, Word)>) -> Self {
+        let leaves = leaves
+            .into_iter()
+            .map(|(index, leaf)| (index, word_to_hex(leaf)))
+            .collect();
+        self.merkle_data(MerkleData::SparseMerkleTree(leaves))
+    }
+
+    /// Adds a Partial Merkle Tree with the given leaves, keyed by depth and index, to the Merkle
+    /// store.
+    pub fn partial_merkle_tree(
+        self,
+        leaves: impl IntoIterator<Item = ((u8, u64), RpoDigest)>,
+    ) -> Self {
+        let leaves = leaves
+            .into_iter()
+            .map(|(index, leaf)| (index, word_to_hex(leaf.into())))
+            .collect();
+        self.merkle_data(MerkleData::PartialMerkleTree(leaves))
+    }
+
+    fn merkle_data(mut self, data: MerkleData) -> Self {
+        self.inputs
+            .merkle_store
+            .get_or_insert_with(Vec::new)
+            .push(data);
+        self
+    }
+
+    /// Validates the inputs with [`Inputs::validate`] and returns them.
+    pub fn build(self) -> Result<Inputs, String> {
+        self.inputs.validate()?;
+        Ok(self.inputs)
+    }
+}
@@ -8,3 +8,17 @@ pub use inputs::*;
 mod parser;
 
 pub use parser::*;
+
+mod format;
+
+pub use format::*;
+
+mod compiler;
+
+pub use compiler::*;
+
+#[cfg(feature = "wasm")]
+mod wasm;
+
+#[cfg(feature = "wasm")]
+pub use wasm::*;
@@ -1,6 +1,6 @@
 use miden::math::Felt;
 
-use crate::Instruction;
+use crate::{AdviceInjector, Instruction};
 
 use super::token::Token;
 
@@ -203,6 +203,41 @@ pub fn parse_loc_storew(op: &Token) -> Result<Instruction, String> {
     }
 }
 
+pub fn parse_adv_inject(op: &Token) -> Result<Instruction, String> {
+    let offset = |op: &Token| match op.parts[2].parse::<u8>() {
+        Ok(offset) if offset <= 12 => Ok(offset),
+        _ => Err(format!(
+            "parameter '{}' is invalid, must be between 0 and 12",
+            op.parts[2]
+        )),
+    };
+
+    if op.num_parts() < 2 {
+        return Err("Missing param, adv.<injector>".to_string());
+    }
+
+    match (op.parts[1], op.num_parts()) {
+        ("push_mapval", 2) => Ok(Instruction::AdvInject(AdviceInjector::PushMapVal)),
+        ("push_mapval", 3) => match offset(op)? {
+            0 => Ok(Instruction::AdvInject(AdviceInjector::PushMapVal)),
+            offset => Ok(Instruction::AdvInject(AdviceInjector::PushMapValImm(
+                offset,
+            ))),
+        },
+        ("push_mapvaln", 2) => Ok(Instruction::AdvInject(AdviceInjector::PushMapValN)),
+        ("push_mapvaln", 3) => match offset(op)? {
+            0 => Ok(Instruction::AdvInject(AdviceInjector::PushMapValN)),
+            offset => Ok(Instruction::AdvInject(AdviceInjector::PushMapValNImm(
+                offset,
+            ))),
+        },
+        ("push_mapval" | "push_mapvaln", _) => {
+            Err(format!("Too many arguments for adv.{}", op.parts[1]))
+        }
+        (injector, _) => Err(format!("advice injector 'adv.{injector}' is not supported")),
+    }
+}
+
 pub fn parse_adv_push(op: &Token) -> Result<Instruction, String> {
     match op.num_parts() {
         0 => unreachable!(),
@@ -25,21 +25,79 @@ fn simple_instruction(op: &Token, instruction: Instruction) -> Result<Instructio
     }
 }
 
-pub fn parse(tokens: Vec<Token>) -> Result<(VecDeque<Instruction>, Vec<Proc>), String> {
+/// A parsed program.
+#[derive(Clone, Debug, Default)]
+pub struct ParsedMasm {
+    /// The instructions of the `begin` block, or of a fragment without one.
+    pub instructions: VecDeque<Instruction>,
+    pub procedures: Vec<Proc>,
+    /// The imported module paths.
+    pub imports: Vec<String>,
+    /// The comments above `begin`.
+    pub comments: Vec<Instruction>,
+    /// The comments after the `begin` block.
+    pub trailing_comments: Vec<Instruction>,
+}
+
+pub fn parse(tokens: Vec<Token>) -> Result<ParsedMasm, String> {
     use Instruction::*;
 
     let mut procedures: Vec<Proc> = Vec::new();
 
+    let mut imports: Vec<String> = Vec::new();
+
     let mut instructions: VecDeque<Instruction> = VecDeque::new();
 
+    let mut comments: Vec<Instruction> = Vec::new();
+
     let mut scope = 0;
     let mut in_proc = false;
 
     let mut has_begin = false;
 
+    // comments outside of any block are attached to the next proc, or to the next instruction
+    let mut pending_comments: Vec<Instruction> = Vec::new();
+
+    // whether the previous token was added to a block, so an inline comment can follow it
+    let mut attach_inline = false;
+
     for token in tokens.iter() {
         let parts = token.parts();
+        if token.inline && attach_inline {
+            let comment = InlineComment(format!("{}{}", &parts[0][1..], parts[1]));
+            if in_proc {
+                let index = procedures.len() - 1;
+                procedures[index].add_instruction(comment);
+            } else {
+                instructions.push_back(comment);
+            }
+            continue;
+        }
+        if scope == 0 && !matches!(parts[0], "#" | "#!" | "proc" | "use" | "begin") {
+            instructions.extend(pending_comments.drain(..));
+        }
+        attach_inline = !matches!(parts[0], "#" | "#!" | "use");
         match parts[0] {
+            "#" | "#!" => {
+                let comment = if parts[0] == "#" {
+                    Comment(parts[1].to_string())
+                } else {
+                    DocComment(parts[1].to_string())
+                };
+                if scope == 0 {
+                    pending_comments.push(comment);
+                } else if in_proc {
+                    let index = procedures.len() - 1;
+                    procedures[index].add_instruction(comment);
+                } else {
+                    instructions.push_back(comment);
+                }
+            }
+            "use" => match token.num_parts() {
+                2 if scope == 0 && !has_begin => imports.push(parts[1].to_string()),
+                2 => return Err("Unexpected use".to_string()),
+                _ => return Err("Expected a module path after use".to_string()),
+            },
             "assert" => {
                 if in_proc {
                     let index = procedures.len() - 1;
@@ -72,6 +130,14 @@ pub fn parse(tokens: Vec<Token>) -> Result<(VecDeque<Instruction>, Vec<Proc>), S
                     instructions.push_back(sys_ops::parse_assert_eqw(token)?);
                 }
             }
+            "debug" => {
+                if in_proc {
+                    let index = procedures.len() - 1;
+                    procedures[index].add_instruction(sys_ops::parse_debug(token)?);
+                } else {
+                    instructions.push_back(sys_ops::parse_debug(token)?);
+                }
+            }
 
             "proc" => match token.num_parts() {
                 0 => unreachable!(),
@@ -79,7 +145,15 @@ pub fn parse(tokens: Vec<Token>) -> Result<(VecDeque<Instruction>, Vec<Proc>), S
                     return Err(format!("Expected name after proc",));
                 }
                 2..=3 => {
-                    procedures.push(Proc::new(parts[1]));
+                    let num_locals = match parts.get(2) {
+                        Some(n) => n
+                            .parse::<u16>()
+                            .map_err(|_| format!("Invalid number of locals {n} after proc"))?,
+                        None => 0,
+                    };
+                    let mut procedure = Proc::new(parts[1]).with_locals(num_locals);
+                    procedure.comments = std::mem::take(&mut pending_comments);
+                    procedures.push(procedure);
                     in_proc = true;
                     scope += 1;
                 }
@@ -90,6 +164,7 @@ pub fn parse(tokens: Vec<Token>) -> Result<(VecDeque<Instruction>, Vec<Proc>), S
 
             "begin" => {
                 if !has_begin {
+                    comments.append(&mut pending_comments);
                     has_begin = true;
                     scope += 1;
                 } else {
@@ -357,7 +432,8 @@ pub fn parse(tokens: Vec<Token>) -> Result<(VecDeque<Instruction>, Vec<Proc>), S
 
             "end" => {
                 scope -= 1;
-                if scope != 0 {
+                // blocks can also be opened outside of `begin`, when parsing a fragment of a program
+                if scope != 0 || (!in_proc && !has_begin) {
                     if in_proc {
                         let index = procedures.len() - 1;
                         procedures[index].add_instruction(END);
@@ -366,6 +442,7 @@ pub fn parse(tokens: Vec<Token>) -> Result<(VecDeque<Instruction>, Vec<Proc>), S
                     }
                 } else {
                     in_proc = false;
+                    attach_inline = false;
                 }
             }
 
@@ -434,6 +511,15 @@ pub fn parse(tokens: Vec<Token>) -> Result<(VecDeque<Instruction>, Vec<Proc>), S
                 }
             }
 
+            "sdepth" => {
+                if in_proc {
+                    let index = procedures.len() - 1;
+                    procedures[index].add_instruction(simple_instruction(token, SDepth)?);
+                } else {
+                    instructions.push_back(simple_instruction(token, SDepth)?);
+                }
+            }
+
             "padw" => {
                 if in_proc {
                     let index = procedures.len() - 1;
@@ -533,6 +619,15 @@ pub fn parse(tokens: Vec<Token>) -> Result<(VecDeque<Instruction>, Vec<Proc>), S
                 }
             }
 
+            "adv" => {
+                if in_proc {
+                    let index = procedures.len() - 1;
+                    procedures[index].add_instruction(io_ops::parse_adv_inject(token)?);
+                } else {
+                    instructions.push_back(io_ops::parse_adv_inject(token)?);
+                }
+            }
+
             "adv_loadw" => {
                 if in_proc {
                     let index = procedures.len() - 1;
@@ -641,6 +736,25 @@ pub fn parse(tokens: Vec<Token>) -> Result<(VecDeque<Instruction>, Vec<Proc>), S
                 }
             },
 
+            "syscall" => match token.num_parts() {
+                0 => unreachable!(),
+                1 => {
+                    return Err("Expected name after syscall".to_string());
+                }
+                2 => {
+                    if in_proc {
+                        let index = procedures.len() - 1;
+                        procedures[index]
+                            .add_instruction(Instruction::Syscall(parts[1].to_string()));
+                    } else {
+                        instructions.push_back(Instruction::Syscall(parts[1].to_string()));
+                    }
+                }
+                _ => {
+                    return Err("Too many arguments after syscall".to_string());
+                }
+            },
+
             "cswap" => {
                 if in_proc {
                     let index = procedures.len() - 1;
@@ -1145,5 +1259,21 @@ pub fn parse(tokens: Vec<Token>) -> Result<(VecDeque<Instruction>, Vec<Proc>), S
         }
     }
 
-    Ok((instructions, procedures))
+    // comments at the end are kept after the `begin` block, or after the instructions of a
+    // fragment
+    let trailing_comments = match has_begin {
+        true => pending_comments,
+        false => {
+            instructions.extend(pending_comments);
+            Vec::new()
+        }
+    };
+
+    Ok(ParsedMasm {
+        instructions,
+        procedures,
+        imports,
+        comments,
+        trailing_comments,
+    })
 }
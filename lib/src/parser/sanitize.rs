@@ -2,10 +2,9 @@ pub fn sanitize(input: &str) -> String {
     input
         .lines()
         .filter_map(|line| {
-            let mut parts = line.splitn(2, '#');
-            let code = parts.next().unwrap_or("").trim();
-            if !code.is_empty() {
-                Some(format!("{}\n", code))
+            let line = line.trim();
+            if !line.is_empty() {
+                Some(format!("{}\n", line))
             } else {
                 None
             }
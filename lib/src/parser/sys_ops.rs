@@ -1,4 +1,4 @@
-use crate::Instruction;
+use crate::{DebugOptions, Instruction};
 
 use super::token::Token;
 
@@ -33,3 +33,51 @@ pub fn parse_assert_eqw(op: &Token) -> Result<Instruction, String> {
         _ => Err("Too many arguments for assert_eqw".to_string()),
     }
 }
+
+fn parse_debug_param<T: std::str::FromStr>(op: &Token, index: usize) -> Result<T, String> {
+    op.parts()[index]
+        .parse::<T>()
+        .map_err(|_| format!("Invalid debug parameter: {}", op.parts()[index]))
+}
+
+pub fn parse_debug(op: &Token) -> Result<Instruction, String> {
+    if op.num_parts() < 2 {
+        return Err("Expected stack, mem or local after debug".to_string());
+    }
+
+    let options = match (op.parts()[1], op.num_parts()) {
+        ("stack", 2) => DebugOptions::StackAll,
+        ("stack", 3) => match parse_debug_param::<u16>(op, 2)? {
+            0 => return Err("debug.stack expects at least one element".to_string()),
+            n => DebugOptions::StackTop(n),
+        },
+        ("mem", 2) => DebugOptions::MemAll,
+        ("mem", 3) => {
+            let address = parse_debug_param(op, 2)?;
+            DebugOptions::MemInterval(address, address)
+        }
+        ("mem", 4) => {
+            let (start, end) = (parse_debug_param(op, 2)?, parse_debug_param(op, 3)?);
+            if start > end {
+                return Err(format!("Invalid memory interval {}..{}", start, end));
+            }
+            DebugOptions::MemInterval(start, end)
+        }
+        ("local", 2) => DebugOptions::LocalAll,
+        ("local", 3) => {
+            let index = parse_debug_param(op, 2)?;
+            DebugOptions::LocalInterval(index, index)
+        }
+        ("local", 4) => {
+            let (start, end) = (parse_debug_param(op, 2)?, parse_debug_param(op, 3)?);
+            if start > end {
+                return Err(format!("Invalid local interval {}..{}", start, end));
+            }
+            DebugOptions::LocalInterval(start, end)
+        }
+        ("stack" | "mem" | "local", _) => return Err("Too many arguments for debug".to_string()),
+        (option, _) => return Err(format!("Unknown debug option: {}", option)),
+    };
+
+    Ok(Instruction::Debug(options))
+}
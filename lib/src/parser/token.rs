@@ -2,14 +2,27 @@
 pub struct Token<'a> {
     /// The dot-separated parts of a token, e.g. `push.1` is split into `['push', '1']`.
     pub parts: Vec<&'a str>,
+    /// Whether a comment follows code on the same line.
+    pub inline: bool,
 }
 
 impl<'a> Token<'a> {
     pub fn new(parts: &'a str) -> Self {
         Self {
             parts: parts.split('.').collect(),
+            inline: false,
         }
     }
+    /// Creates a comment token from a line comment (`# ...`) or a doc comment (`#! ...`).
+    /// The first part is the comment marker and the second part is the rest of the line.
+    pub fn comment(comment: &'a str) -> Self {
+        let marker = if comment.starts_with("#!") { 2 } else { 1 };
+        Self {
+            parts: vec![&comment[..marker], comment[marker..].trim_end()],
+            inline: false,
+        }
+    }
+
     pub fn parts(&self) -> &[&str] {
         &self.parts
     }
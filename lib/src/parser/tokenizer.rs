@@ -1,8 +1,23 @@
 use super::Token;
 
 pub fn tokenize<'a>(sanitized: &'a str) -> Vec<Token<'a>> {
-    sanitized
-        .split_whitespace()
-        .map(|word| Token::new(word))
-        .collect()
+    let mut tokens = Vec::new();
+
+    for line in sanitized.lines() {
+        let (code, comment) = match line.find('#') {
+            Some(i) => (&line[..i], Some(&line[i..])),
+            None => (line, None),
+        };
+
+        tokens.extend(code.split_whitespace().map(Token::new));
+
+        if let Some(comment) = comment {
+            tokens.push(Token {
+                inline: !code.trim().is_empty(),
+                ..Token::comment(comment)
+            });
+        }
+    }
+
+    tokens
 }
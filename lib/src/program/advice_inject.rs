@@ -1,10 +1,87 @@
+use miden::{
+    crypto::{Rpo256, RpoDigest},
+    math::{Felt, StarkField},
+    Word,
+};
 
+use super::{instruction::AdviceInjector, Instruction, MidenProgram};
 
+impl MidenProgram {
+    /// Adds an entry to the advice map of the simulator and of the inputs the program runs with
+    /// on the Miden VM, replacing any values already stored under `key`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key, read by `adv.push_mapval` from the word on the stack.
+    /// * `values` - The values pushed onto the advice stack by `adv.push_mapval`.
+    pub fn add_advice_map_entry(&mut self, key: RpoDigest, values: Vec<Felt>) {
+        self.advice_map
+            .insert(key, values.iter().map(|n| n.as_int()).collect());
+        self.advice_inputs =
+            std::mem::take(&mut self.advice_inputs).with_map([(key.as_bytes(), values)]);
+    }
 
+    /// Adds values to the advice map under their RPO hash, computed with
+    /// `Rpo256::hash_elements`.
+    ///
+    /// # Returns
+    ///
+    /// The hash the values are stored under, to push with [`MidenProgram::push_digest`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use miden::math::Felt;
+    /// use rust_masm::MidenProgram;
+    ///
+    /// let mut program = MidenProgram::new();
+    /// let key = program.add_hashed_advice_values(vec![Felt::new(7), Felt::new(8)]);
+    ///
+    /// program.push_digest(key);
+    /// program.adv_push_mapval(0);
+    /// program.adv_push(2);
+    /// assert_eq!(program.get_stack()[0], Felt::new(8));
+    /// assert_eq!(program.get_stack()[1], Felt::new(7));
+    /// ```
+    pub fn add_hashed_advice_values(&mut self, values: Vec<Felt>) -> RpoDigest {
+        let key = Rpo256::hash_elements(&values);
+        self.add_advice_map_entry(key, values);
+        key
+    }
 
+    /// Pushes the four elements of a digest so that it is the top word of the stack, in the
+    /// order `adv.push_mapval` reads advice map keys.
+    pub fn push_digest(&mut self, digest: RpoDigest) {
+        for value in Word::from(digest) {
+            self.add_instruction(Instruction::Push(value));
+        }
+    }
 
+    /// Pushes the values stored in the advice map under the word at `offset` on the stack onto
+    /// the advice stack, with `adv.push_mapval`.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The position of the key on the stack, from 0 to 12.
+    pub fn adv_push_mapval(&mut self, offset: u8) {
+        let injector = match offset {
+            0 => AdviceInjector::PushMapVal,
+            _ => AdviceInjector::PushMapValImm(offset),
+        };
+        self.add_instruction(Instruction::AdvInject(injector));
+    }
 
-
-
-
-
+    /// Pushes the values stored in the advice map under the word at `offset` on the stack onto
+    /// the advice stack, followed by their number, with `adv.push_mapvaln`.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The position of the key on the stack, from 0 to 12.
+    pub fn adv_push_mapvaln(&mut self, offset: u8) {
+        let injector = match offset {
+            0 => AdviceInjector::PushMapValN,
+            _ => AdviceInjector::PushMapValNImm(offset),
+        };
+        self.add_instruction(Instruction::AdvInject(injector));
+    }
+}
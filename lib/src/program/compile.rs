@@ -0,0 +1,214 @@
+use std::collections::VecDeque;
+
+use miden::{Kernel, Program, ProgramInfo};
+use miden_assembly::{Assembler, AssemblyError};
+
+pub use miden_assembly::{LibraryNamespace, MaslLibrary, Version as LibraryVersion};
+
+use super::{Digest, Instruction, MidenProgram, Proc};
+
+/// A program assembled by the Miden VM assembler, returned by [`MidenProgram::compile`].
+#[derive(Clone, Debug)]
+pub struct CompiledProgram {
+    program: Program,
+    masm: String,
+    skipped_decorators: Vec<String>,
+}
+
+/// The parts of a program its assembly depends on, kept with the cached [`CompiledProgram`] to
+/// tell whether it is still up to date without rendering the program again.
+#[derive(Clone, Debug)]
+pub(crate) struct CompileKey {
+    imports: Vec<String>,
+    procedures: Vec<Proc>,
+    instructions: VecDeque<Instruction>,
+}
+
+impl CompileKey {
+    fn new(program: &MidenProgram) -> Self {
+        CompileKey {
+            imports: program.imports.clone(),
+            procedures: program
+                .internal_programs_order
+                .iter()
+                .map(|name| program.internal_programs[name].borrow().clone())
+                .collect(),
+            instructions: program.instructions.clone(),
+        }
+    }
+
+    fn matches(&self, program: &MidenProgram) -> bool {
+        self.imports == program.imports
+            && self.instructions == program.instructions
+            && self.procedures.len() == program.internal_programs_order.len()
+            && self
+                .procedures
+                .iter()
+                .zip(program.internal_programs_order.iter())
+                .all(|(proc, name)| {
+                    program
+                        .internal_programs
+                        .get(name)
+                        .is_some_and(|current| *current.borrow() == *proc)
+                })
+    }
+}
+
+impl CompiledProgram {
+    /// Returns the assembled `miden::Program`.
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    /// Returns the hash of the program, which is the root of its MAST.
+    pub fn hash(&self) -> Digest {
+        self.program.hash()
+    }
+
+    /// Returns the kernel the program was assembled against.
+    pub fn kernel(&self) -> &Kernel {
+        self.program.kernel()
+    }
+
+    /// Returns the program hash and kernel needed to verify proofs of the program.
+    pub fn program_info(&self) -> ProgramInfo {
+        ProgramInfo::from(self.program.clone())
+    }
+
+    /// Returns the MASM source that was given to the assembler.
+    pub fn masm(&self) -> &str {
+        &self.masm
+    }
+
+    /// Returns the `debug` decorators that were commented out of the source, because the
+    /// assembler does not accept them.
+    ///
+    /// These are not diagnostics of the assembler: the miden-assembly 0.7 assembler reports no
+    /// warnings, only the error that stops the assembly. The skipped decorators are the only
+    /// information about a successful assembly besides the program itself.
+    pub fn skipped_decorators(&self) -> &[String] {
+        &self.skipped_decorators
+    }
+}
+
+impl MidenProgram {
+    /// Adds a `use` statement for a library module to the program.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the module, such as `std::math::u64`.
+    pub fn add_import(&mut self, path: &str) {
+        if !self.imports.iter().any(|import| import == path) {
+            self.imports.push(path.to_string());
+        }
+    }
+
+    /// Returns the module paths imported by the program.
+    pub fn get_imports(&self) -> &[String] {
+        &self.imports
+    }
+
+    /// Adds a library that provides the modules imported by the program and returns the program.
+    ///
+    /// # Arguments
+    ///
+    /// * `library` - The library to give to the assembler.
+    ///
+    /// # Returns
+    ///
+    /// The program with the library.
+    pub fn with_library(mut self, library: MaslLibrary) -> Self {
+        self.libraries.push(library);
+        self.compiled = None;
+        self
+    }
+
+    /// Sets the kernel whose procedures the program calls with `syscall` and returns the program.
+    ///
+    /// # Arguments
+    ///
+    /// * `kernel_source` - The MASM source of the kernel module.
+    ///
+    /// # Returns
+    ///
+    /// The program with the kernel.
+    pub fn with_kernel(mut self, kernel_source: &str) -> Self {
+        self.kernel = Some(kernel_source.to_string());
+        self.compiled = None;
+        self
+    }
+
+    /// Assembles the program with the libraries and kernel of the program.
+    ///
+    /// The result is cached, so the program is only assembled again after its imports, procedures
+    /// or instructions change.
+    ///
+    /// The miden-assembly 0.7 assembler emits no diagnostics, so a successful assembly returns no
+    /// warnings. [`CompiledProgram::skipped_decorators`] lists the `debug` decorators that were
+    /// commented out of the source before assembling it.
+    ///
+    /// # Returns
+    ///
+    /// The compiled program, or the error returned by the assembler.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_masm::MidenProgram;
+    ///
+    /// let mut program = MidenProgram::new();
+    /// program.push(1);
+    ///
+    /// let compiled = program.compile().unwrap();
+    /// assert_eq!(compiled.hash(), program.compile().unwrap().hash());
+    /// ```
+    pub fn compile(&mut self) -> Result<CompiledProgram, AssemblyError> {
+        if let Some((key, compiled)) = &self.compiled {
+            if key.matches(self) {
+                return Ok(compiled.clone());
+            }
+        }
+
+        let (masm, skipped_decorators) = vm_masm(&self.get_masm());
+
+        let mut assembler = Assembler::default().with_libraries(self.libraries.iter())?;
+        if let Some(kernel) = &self.kernel {
+            assembler = assembler.with_kernel(kernel)?;
+        }
+        let program = assembler.compile(&masm)?;
+
+        for decorator in skipped_decorators.iter() {
+            self.emit_diagnostic(&format!(
+                "`{}` is not supported by the assembler",
+                decorator
+            ));
+        }
+
+        let compiled = CompiledProgram {
+            program,
+            masm,
+            skipped_decorators,
+        };
+        self.compiled = Some((CompileKey::new(self), compiled.clone()));
+        Ok(compiled)
+    }
+}
+
+/// Comments out the `debug` decorators that the miden-vm 0.7 assembler does not accept, and
+/// returns them.
+fn vm_masm(masm: &str) -> (String, Vec<String>) {
+    let mut skipped_decorators = Vec::new();
+    let source = masm
+        .lines()
+        .map(|line| {
+            let op = line.trim_start();
+            if op.starts_with("debug.mem") || op.starts_with("debug.local") {
+                skipped_decorators.push(op.to_string());
+                format!("{}#{}\n", &line[..line.len() - op.len()], op)
+            } else {
+                format!("{}\n", line)
+            }
+        })
+        .collect();
+    (source, skipped_decorators)
+}
@@ -4,7 +4,7 @@ use miden::math::Felt;
 
 use crate::Program;
 
-use super::instruction::Instruction;
+use super::instruction::{DebugOptions, Instruction};
 
 pub struct EmptyProgram {
     instructions: VecDeque<Instruction>,
@@ -165,6 +165,15 @@ impl EmptyProgram {
         self.add_instruction(Instruction::PRINT(message.to_string()));
     }
 
+    /// Pushes a `debug` decorator that prints the selected state of the program.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The state to print.
+    pub fn debug(&mut self, options: DebugOptions) {
+        self.add_instruction(Instruction::Debug(options));
+    }
+
     /// Pushes `Drop` instruction onto the stack.
     pub fn drop(&mut self) {
         self.add_instruction(Instruction::Drop);
@@ -217,6 +226,11 @@ impl EmptyProgram {
         self.add_instruction(Instruction::PadW);
     }
 
+    /// Pushes `SDepth` instruction onto the stack.
+    pub fn sdepth(&mut self) {
+        self.add_instruction(Instruction::SDepth);
+    }
+
     /// Pushes `MovUp` instruction with value `n` onto the stack.
     ///
     /// # Arguments
@@ -529,6 +543,16 @@ impl EmptyProgram {
         self.add_instruction(Instruction::Exec(name.to_string()));
     }
 
+    /// Pushes `Syscall` instruction onto the stack. Kernel procedures are not simulated, the
+    /// call only takes effect when the program runs on the VM.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the kernel procedure.
+    pub fn syscall(&mut self, name: &str) {
+        self.add_instruction(Instruction::Syscall(name.to_string()));
+    }
+
     /// Pushes `Increment` instruction onto the stack.
     pub fn increment(&mut self) {
         self.add_instruction(Instruction::Increment);
@@ -1,4 +1,10 @@
-#[derive(Debug, Clone, PartialEq, Eq)]
+use miden::ExecutionError;
+use miden_assembly::AssemblyError;
+#[cfg(feature = "prover")]
+use miden_vm::VerificationError;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MidenProgramError {
     LocStoreInBegin,
     LocLoadInBegin,
@@ -6,6 +12,7 @@ pub enum MidenProgramError {
     LocalProcNotFound(u16, String),
     ParamOutOfBounds(u64, u64, u64),
     AdviceStackReadOutOfBounds(usize, usize),
+    AdviceMapKeyNotFound(String),
     DivideByZero,
     ModulusByZero,
     DivModByZero,
@@ -17,6 +24,8 @@ pub enum MidenProgramError {
     U32InvalidSubtraction(u64, u64),
     TopValueInvalid(String, usize, usize, usize),
     ZeroInvertInvalid,
+    MemoryAddressOutOfBounds(u64),
+    LocalIndexOutOfBounds(u16, u16),
 }
 
 impl std::fmt::Display for MidenProgramError {
@@ -35,6 +44,7 @@ impl std::fmt::Display for MidenProgramError {
             Self::LocStoreInBegin => write!(f, "Cannot store to local in BEGIN block"),
             Self::LocLoadInBegin => write!(f, "Cannot load from local in BEGIN block"),
             Self::AdviceStackReadOutOfBounds( value, max) => write!(f, "Advice stack read out of bounds: {value} > {max}"),
+            Self::AdviceMapKeyNotFound(key) => write!(f, "AdviceMapKeyNotFound({key}), the advice map has no values for key {key}"),
             Self::NotBinaryValue( value) => write!(f, "NotBinaryValue({value}), {value} is not binary"),
             Self::InvalidParameter(op, value, min, max) => write!(f, "InvalidParameter({value}), {op} is invalid, must be between {min} and {max}"),
             Self::TopValueInvalid(op, value, min, max) => write!(f, "TopValueInvalid({value}), {op} is invalid, must be between {min} and {max}"),
@@ -42,7 +52,71 @@ impl std::fmt::Display for MidenProgramError {
             Self::NotU32Value(value) => write!(f, "NotU32Value({value}), {value} is not a u32 value"),
             Self::U32Overflow(value) => write!(f, "U32Overflow({value}), {value} is too large to be a u32 value"),
             Self::U32InvalidSubtraction(a, b) => write!(f, "U32InvalidSubtraction({a}), {a} is less than {b}"),
+            Self::MemoryAddressOutOfBounds(addr) => write!(f, "MemoryAddressOutOfBounds({addr}), {addr} is not a valid memory address"),
+            Self::LocalIndexOutOfBounds(index, count) => write!(f, "LocalIndexOutOfBounds({index}), the procedure only has {count} locals"),
 
         }
     }
 }
+
+/// An error returned by the Miden VM when a program is assembled, executed, proved or verified,
+/// or when a proof, state, program or outputs file cannot be read or written.
+#[derive(Debug)]
+pub enum VmError {
+    InvalidOptions(String),
+    InvalidInputs(String),
+    Io(std::io::Error),
+    InvalidProofFile(String),
+    InvalidStateFile(String),
+    InvalidProgramFile(String),
+    InvalidOutputsFile(String),
+    Assembly(AssemblyError),
+    Execution(ExecutionError),
+    #[cfg(feature = "prover")]
+    Verification(VerificationError),
+}
+
+impl std::fmt::Display for VmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidOptions(message) => write!(f, "Invalid options: {message}"),
+            Self::InvalidInputs(message) => write!(f, "Invalid inputs: {message}"),
+            Self::Io(e) => write!(f, "IO error: {e}"),
+            Self::InvalidProofFile(message) => write!(f, "Invalid proof file: {message}"),
+            Self::InvalidStateFile(message) => write!(f, "Invalid state file: {message}"),
+            Self::InvalidProgramFile(message) => write!(f, "Invalid program file: {message}"),
+            Self::InvalidOutputsFile(message) => write!(f, "Invalid outputs file: {message}"),
+            Self::Assembly(e) => write!(f, "Assembly error: {e}"),
+            Self::Execution(e) => write!(f, "Execution error: {e}"),
+            #[cfg(feature = "prover")]
+            Self::Verification(e) => write!(f, "Verification error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for VmError {}
+
+impl From<std::io::Error> for VmError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<AssemblyError> for VmError {
+    fn from(error: AssemblyError) -> Self {
+        Self::Assembly(error)
+    }
+}
+
+impl From<ExecutionError> for VmError {
+    fn from(error: ExecutionError) -> Self {
+        Self::Execution(error)
+    }
+}
+
+#[cfg(feature = "prover")]
+impl From<VerificationError> for VmError {
+    fn from(error: VerificationError) -> Self {
+        Self::Verification(error)
+    }
+}
@@ -0,0 +1,133 @@
+use super::{error::MidenProgramError, instruction::DebugOptions, MidenProgram};
+
+/// Receives the output produced while a program is simulated.
+///
+/// Every method has an empty default implementation, so a sink only implements the events it
+/// cares about. Programs are silent unless a sink is set with [`MidenProgram::with_event_sink`].
+pub trait EventSink {
+    /// Called when a `PRINT` instruction is executed.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message of the `PRINT` instruction.
+    /// * `program` - The program, in the state it is in when the instruction is executed.
+    fn print(&mut self, _message: &str, _program: &MidenProgram) {}
+
+    /// Called when a `debug` decorator is executed.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The state the decorator asks to print.
+    /// * `program` - The program, in the state it is in when the decorator is executed.
+    fn debug(&mut self, _options: &DebugOptions, _program: &MidenProgram) {}
+
+    /// Called when an instruction is rejected by the simulator.
+    fn error(&mut self, _error: &MidenProgramError) {}
+
+    /// Called with informational messages about the simulation.
+    fn diagnostic(&mut self, _message: &str) {}
+}
+
+/// Renders the state selected by a `debug` decorator.
+fn render_debug(options: &DebugOptions, program: &MidenProgram) -> String {
+    match options {
+        DebugOptions::StackAll => format!("stack : {:?}", program.get_stack()),
+        DebugOptions::StackTop(n) => format!(
+            "stack : {:?}",
+            program
+                .get_stack()
+                .iter()
+                .take(*n as usize)
+                .collect::<Vec<_>>()
+        ),
+        DebugOptions::MemAll => format!("ram : {:?}", program.get_ram_memory()),
+        DebugOptions::MemInterval(start, end) => format!(
+            "ram : {:?}",
+            (*start..=*end)
+                .filter_map(|address| program
+                    .get_ram_memory()
+                    .get(&address)
+                    .map(|word| (address, word)))
+                .collect::<Vec<_>>()
+        ),
+        DebugOptions::LocalAll => format!("loc : {:?}", program.get_loc_memory()),
+        DebugOptions::LocalInterval(start, end) => format!(
+            "loc : {:?}",
+            (*start..=*end)
+                .filter_map(|index| program
+                    .get_loc_memory()
+                    .get(index as usize)
+                    .map(|word| (index, word)))
+                .collect::<Vec<_>>()
+        ),
+    }
+}
+
+/// An `EventSink` that discards every event. This is the default sink of a program.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SilentSink;
+
+impl EventSink for SilentSink {}
+
+/// An `EventSink` that writes every event to stdout.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StdoutSink;
+
+impl EventSink for StdoutSink {
+    fn print(&mut self, message: &str, program: &MidenProgram) {
+        println!("____________________________________________________\n");
+        println!("{}", message);
+        println!("stack : {:?}\n", program.get_stack());
+        println!("ram : {:?}\n", program.get_ram_memory());
+        println!("loc : {:?}\n", program.get_loc_memory());
+        println!("____________________________________________________\n");
+    }
+
+    fn debug(&mut self, options: &DebugOptions, program: &MidenProgram) {
+        println!("debug.{} => {}", options, render_debug(options, program));
+    }
+
+    fn error(&mut self, error: &MidenProgramError) {
+        println!("Error: {}", error);
+    }
+
+    fn diagnostic(&mut self, message: &str) {
+        println!("{}", message);
+    }
+}
+
+/// An `EventSink` that forwards every event to the [`log`] crate, so the output of a program
+/// ends up wherever the logger of the application sends it.
+///
+/// `PRINT` and `debug` output is logged at the `info` level, errors at the `warn` level and diagnostics at
+/// the `debug` level, all with the `rust_masm` target.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LogSink;
+
+impl EventSink for LogSink {
+    fn print(&mut self, message: &str, program: &MidenProgram) {
+        log::info!(
+            target: "rust_masm",
+            "{} stack: {:?}",
+            message,
+            program.get_stack()
+        );
+    }
+
+    fn debug(&mut self, options: &DebugOptions, program: &MidenProgram) {
+        log::info!(
+            target: "rust_masm",
+            "debug.{} => {}",
+            options,
+            render_debug(options, program)
+        );
+    }
+
+    fn error(&mut self, error: &MidenProgramError) {
+        log::warn!(target: "rust_masm", "{}", error);
+    }
+
+    fn diagnostic(&mut self, message: &str) {
+        log::debug!(target: "rust_masm", "{}", message);
+    }
+}
@@ -0,0 +1,52 @@
+use std::collections::VecDeque;
+
+use miden::{crypto::RpoDigest, math::Felt};
+
+use crate::{program::instruction::AdviceInjector, Instruction, MidenProgram};
+
+/// Returns the stack offset of the key read by an `adv.push_mapval` or `adv.push_mapvaln`
+/// instruction, and whether the number of values is pushed too.
+pub(crate) fn map_value_read(operand: &Instruction) -> Option<(usize, bool)> {
+    match operand {
+        Instruction::AdvInject(AdviceInjector::PushMapVal) => Some((0, false)),
+        Instruction::AdvInject(AdviceInjector::PushMapValImm(offset)) => {
+            Some((*offset as usize, false))
+        }
+        Instruction::AdvInject(AdviceInjector::PushMapValN) => Some((0, true)),
+        Instruction::AdvInject(AdviceInjector::PushMapValNImm(offset)) => {
+            Some((*offset as usize, true))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the advice map key formed by the word at `offset` on the stack, in the order the
+/// Miden VM reads it: the deepest element of the word is the first element of the key.
+pub(crate) fn map_key(stack: &VecDeque<Felt>, offset: usize) -> RpoDigest {
+    RpoDigest::new([
+        stack[offset + 3],
+        stack[offset + 2],
+        stack[offset + 1],
+        stack[offset],
+    ])
+}
+
+pub fn execute_advice(program: &mut MidenProgram, operand: &Instruction) {
+    if let Some((offset, include_len)) = map_value_read(operand) {
+        let key = map_key(&program.stack, offset);
+        if let Some(values) = program.advice_map.get(&key) {
+            // the first value ends up on top of the advice stack
+            for value in values.iter().rev() {
+                program.advice_stack.push_front(*value);
+            }
+            if include_len {
+                program.advice_stack.push_front(values.len() as u64);
+            }
+        }
+    }
+}
+
+/// Returns the hex of an advice map key, with the `0x` prefix, as in inputs and state files.
+pub(crate) fn key_to_hex(key: &RpoDigest) -> String {
+    key.to_string()
+}
@@ -0,0 +1,11 @@
+use miden::math::Felt;
+
+use crate::{Instruction, MidenProgram};
+
+pub fn execute_environment(program: &mut MidenProgram, operand: &Instruction) {
+    if let Instruction::SDepth = operand {
+        program
+            .stack
+            .push_front(Felt::from(program.stack.len() as u64));
+    }
+}
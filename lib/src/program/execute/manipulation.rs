@@ -16,9 +16,13 @@ pub fn execute_manipulation(program: &mut MidenProgram, operand: &Instruction) {
         Instruction::DropW => {
             for _ in 0..4 {
                 program.stack.pop_front();
-                program.stack.pop_front();
-                program.stack.pop_front();
-                program.stack.pop_front();
+            }
+        }
+
+        Instruction::DupW(n) => {
+            let word: Vec<Felt> = program.stack.range(*n * 4..*n * 4 + 4).copied().collect();
+            for value in word.into_iter().rev() {
+                program.stack.push_front(value);
             }
         }
 
@@ -1,122 +1,81 @@
+use std::collections::{HashMap, VecDeque};
+
 use miden::math::{Felt, FieldElement, StarkField};
 
 use crate::{Instruction, MidenProgram};
 
+/// Returns the word at the top of the stack in memory order, as `mem_storew` writes it: the
+/// top of the stack is the last element of the word.
+pub(crate) fn top_word(stack: &VecDeque<Felt>) -> [Felt; 4] {
+    [stack[3], stack[2], stack[1], stack[0]]
+}
+
+/// Overwrites the word at the top of the stack with `word`, as `mem_loadw` does: the last
+/// element of the word ends up on the top of the stack.
+pub(crate) fn load_word(stack: &mut VecDeque<Felt>, word: [Felt; 4]) {
+    for (i, value) in word.into_iter().rev().enumerate() {
+        stack[i] = value;
+    }
+}
+
+/// Writes `value` to the first element of the word at `key`, leaving the rest of the word
+/// untouched.
+fn store_element(memory: &mut HashMap<u32, [Felt; 4]>, key: u32, value: Felt) {
+    memory.entry(key).or_insert([Felt::ZERO; 4])[0] = value;
+}
+
 pub fn execute_memory(program: &mut MidenProgram, operand: &Instruction) {
     match operand {
         Instruction::MemStore => {
             if let (Some(key), Some(a)) = (program.stack.pop_front(), program.stack.pop_front()) {
-                program
-                    .ram_memory
-                    .insert(key.as_int() as u32, [Felt::ZERO, Felt::ZERO, Felt::ZERO, a]);
+                store_element(&mut program.ram_memory, key.as_int() as u32, a);
             }
-            println!("ram: {:?}", program.ram_memory);
         }
 
         Instruction::MemStoreImm(key) => {
             if let Some(a) = program.stack.pop_front() {
-                program
-                    .ram_memory
-                    .insert(*key, [Felt::ZERO, Felt::ZERO, Felt::ZERO, a]);
+                store_element(&mut program.ram_memory, *key, a);
             }
         }
 
         Instruction::MemLoad => {
             if let Some(key) = program.stack.pop_front() {
-                if let Some([_, _, _, a]) = program.ram_memory.get(&(key.as_int() as u32)) {
-                    program.stack.push_front(*a);
-                } else {
-                    program.stack.push_front(Felt::ZERO);
-                }
+                let word = program.ram_memory.get(&(key.as_int() as u32));
+                program
+                    .stack
+                    .push_front(word.map_or(Felt::ZERO, |word| word[0]));
             }
         }
 
         Instruction::MemLoadImm(key) => {
-            if let Some([_, _, _, a]) = program.ram_memory.get(&key) {
-                program.stack.push_front(*a);
-            } else {
-                program.stack.push_front(Felt::ZERO);
-            }
+            let word = program.ram_memory.get(key);
+            program
+                .stack
+                .push_front(word.map_or(Felt::ZERO, |word| word[0]));
         }
 
         Instruction::MemLoadW => {
-            if let (Some(key), Some(_), Some(_), Some(_), Some(_)) = (
-                program.stack.pop_front(),
-                program.stack.pop_front(),
-                program.stack.pop_front(),
-                program.stack.pop_front(),
-                program.stack.pop_front(),
-            ) {
-                if let Some([a, b, c, d]) = program.ram_memory.get(&(key.as_int() as u32)) {
-                    program.stack.push_front(*d);
-                    program.stack.push_front(*c);
-                    program.stack.push_front(*b);
-                    program.stack.push_front(*a);
-                } else {
-                    program.stack.push_front(Felt::ZERO);
-                    program.stack.push_front(Felt::ZERO);
-                    program.stack.push_front(Felt::ZERO);
-                    program.stack.push_front(Felt::ZERO);
-                }
+            if let Some(key) = program.stack.pop_front() {
+                let word = program.ram_memory.get(&(key.as_int() as u32)).copied();
+                load_word(&mut program.stack, word.unwrap_or([Felt::ZERO; 4]));
             }
         }
 
         Instruction::MemLoadWImm(key) => {
-            if let (Some(_), Some(_), Some(_), Some(_)) = (
-                program.stack.pop_front(),
-                program.stack.pop_front(),
-                program.stack.pop_front(),
-                program.stack.pop_front(),
-            ) {
-                if let Some([a, b, c, d]) = program.ram_memory.get(&key) {
-                    program.stack.push_front(*d);
-                    program.stack.push_front(*c);
-                    program.stack.push_front(*b);
-                    program.stack.push_front(*a);
-                } else {
-                    program.stack.push_front(Felt::ZERO);
-                    program.stack.push_front(Felt::ZERO);
-                    program.stack.push_front(Felt::ZERO);
-                    program.stack.push_front(Felt::ZERO);
-                }
-            }
+            let word = program.ram_memory.get(key).copied();
+            load_word(&mut program.stack, word.unwrap_or([Felt::ZERO; 4]));
         }
 
         Instruction::MemStoreW => {
-            while program.stack.len() < 5 {
-                program.stack.push_back(Felt::ZERO);
-            }
-            if let (Some(key), Some(a), Some(b), Some(c), Some(d)) = (
-                program.stack.pop_front(),
-                program.stack.pop_front(),
-                program.stack.pop_front(),
-                program.stack.pop_front(),
-                program.stack.pop_front(),
-            ) {
-                program.ram_memory.insert(key.as_int() as u32, [a, b, c, d]);
-                program.stack.push_front(d);
-                program.stack.push_front(c);
-                program.stack.push_front(b);
-                program.stack.push_front(a);
+            if let Some(key) = program.stack.pop_front() {
+                let word = top_word(&program.stack);
+                program.ram_memory.insert(key.as_int() as u32, word);
             }
         }
 
         Instruction::MemStoreWImm(key) => {
-            while program.stack.len() < 4 {
-                program.stack.push_back(Felt::ZERO);
-            }
-            if let (Some(a), Some(b), Some(c), Some(d)) = (
-                program.stack.pop_front(),
-                program.stack.pop_front(),
-                program.stack.pop_front(),
-                program.stack.pop_front(),
-            ) {
-                program.ram_memory.insert(*key, [a, b, c, d]);
-                program.stack.push_front(d);
-                program.stack.push_front(c);
-                program.stack.push_front(b);
-                program.stack.push_front(a);
-            }
+            let word = top_word(&program.stack);
+            program.ram_memory.insert(*key, word);
         }
 
         _ => {}
@@ -15,17 +15,21 @@ mod u32_bitwise;
 mod utils;
 mod valid_checker;
 
+use self::advice::execute_advice;
+pub(crate) use self::advice::key_to_hex;
 use self::arithmetic::execute_arithmetic;
 use self::comparison::execute_comparison;
 use self::conditional_manipulation::execute_conditional;
+use self::environment::execute_environment;
 use self::extensions::execute_extensions;
 use self::manipulation::execute_manipulation;
+pub(crate) use self::memory::{load_word, top_word};
 use self::u32_arithmetic::execute_u32_arithmetic;
 use self::u32_bitwise::execute_u32_bitwise;
 use self::{boolean::execute_boolean, memory::execute_memory};
 
 use super::{Instruction, MidenProgram};
-use miden::math::{Felt, FieldElement};
+use miden::math::{Felt, FieldElement, StarkField};
 use std::collections::VecDeque;
 
 impl MidenProgram {
@@ -34,6 +38,7 @@ impl MidenProgram {
         while let Some(op) = block.pop_front() {
             match self.is_valid_operand(&op) {
                 Some(error) => {
+                    self.emit_error(&error);
                     if let Some(op) = self.instructions.get_mut(index) {
                         match op {
                             Instruction::Error(_) | Instruction::CommentedOut(_) => {}
@@ -192,16 +197,14 @@ impl MidenProgram {
                 }
 
                 Instruction::Error(error) => {
-                    println!("Error: {}", error);
+                    self.emit_error(&error);
                 }
 
                 _ => {
                     self.execute_operand(&op);
                 }
             }
-            while self.stack.len() < 16 {
-                self.stack.push_back(Felt::from(0_u64));
-            }
+            self.pad_stack();
         }
     }
 
@@ -215,6 +218,8 @@ impl MidenProgram {
         execute_u32_bitwise(self, op);
         execute_extensions(self, op);
         execute_conditional(self, op);
+        execute_environment(self, op);
+        execute_advice(self, op);
 
         match op {
             Instruction::AdvPush(n) => {
@@ -226,83 +231,24 @@ impl MidenProgram {
             }
 
             Instruction::AdvPipe => {
-                if let (s2, s1, s0, Some(a), t1, t0) = (
-                    (
-                        self.stack.pop_front(),
-                        self.stack.pop_front(),
-                        self.stack.pop_front(),
-                        self.stack.pop_front(),
-                    ),
-                    (
-                        self.stack.pop_front(),
-                        self.stack.pop_front(),
-                        self.stack.pop_front(),
-                        self.stack.pop_front(),
-                    ),
-                    (
-                        self.stack.pop_front(),
-                        self.stack.pop_front(),
-                        self.stack.pop_front(),
-                        self.stack.pop_front(),
-                    ),
-                    self.stack.pop_front(),
-                    (
-                        self.advice_stack.pop_front(),
-                        self.advice_stack.pop_front(),
-                        self.advice_stack.pop_front(),
-                        self.advice_stack.pop_front(),
-                    ),
-                    (
-                        self.advice_stack.pop_front(),
-                        self.advice_stack.pop_front(),
-                        self.advice_stack.pop_front(),
-                        self.advice_stack.pop_front(),
-                    ),
-                ) {
-                    self.stack.push_front(a + Felt::from(2_u64));
-                    // S0
-                    if let (Some(d), Some(c), Some(b), Some(a)) = s0 {
-                        self.stack.push_front(Felt::from(a));
-                        self.stack.push_front(Felt::from(b));
-                        self.stack.push_front(Felt::from(c));
-                        self.stack.push_front(Felt::from(d));
-                    }
-                    // S1
-                    if let (Some(_), Some(_), Some(_), Some(_)) = s1 {
-                        if let (Some(d), Some(c), Some(b), Some(a)) = t0 {
-                            self.stack.push_front(Felt::from(a));
-                            self.stack.push_front(Felt::from(b));
-                            self.stack.push_front(Felt::from(c));
-                            self.stack.push_front(Felt::from(d));
-                            self.ram_memory.insert(
-                                1,
-                                [Felt::from(a), Felt::from(b), Felt::from(c), Felt::from(d)],
-                            );
-                        }
-                    }
-                    // S2
-                    if let (Some(_), Some(_), Some(_), Some(_)) = s2 {
-                        if let (Some(d), Some(c), Some(b), Some(a)) = t1 {
-                            self.stack.push_front(Felt::from(a));
-                            self.stack.push_front(Felt::from(b));
-                            self.stack.push_front(Felt::from(c));
-                            self.stack.push_front(Felt::from(d));
-
-                            self.ram_memory.insert(
-                                0,
-                                [Felt::from(a), Felt::from(b), Felt::from(c), Felt::from(d)],
-                            );
+                // [C, B, A, a, ...] -> [T1, T0, A, a + 2, ...], where T0 and T1 are the next two
+                // words of the advice stack and are also written to memory at a and a + 1.
+                if let Some(a) = self.stack.get(12).copied() {
+                    let addr = a.as_int() as u32;
+                    let values: Vec<Felt> = self
+                        .advice_stack
+                        .drain(..8.min(self.advice_stack.len()))
+                        .map(Felt::from)
+                        .collect();
+                    if values.len() == 8 {
+                        for (i, value) in values.iter().rev().enumerate() {
+                            self.stack[i] = *value;
                         }
-                    }
-                }
-                for _ in 0..8 {
-                    if let (Some(a), Some(_), Some(_), Some(_)) = (
-                        self.advice_stack.pop_front(),
-                        self.advice_stack.pop_front(),
-                        self.advice_stack.pop_front(),
-                        self.advice_stack.pop_front(),
-                    ) {
-                        self.stack.push_front(Felt::from(a));
+                        self.stack[12] = a + Felt::from(2_u64);
+                        self.ram_memory
+                            .insert(addr, [values[0], values[1], values[2], values[3]]);
+                        self.ram_memory
+                            .insert(addr + 1, [values[4], values[5], values[6], values[7]]);
                     }
                 }
             }
@@ -314,16 +260,15 @@ impl MidenProgram {
             }
 
             Instruction::PRINT(message) => {
-                println!("____________________________________________________\n");
-                println!("{}", message);
-                println!("stack : {:?}\n", self.stack);
-                println!("ram : {:?}\n", self.ram_memory);
-                println!("loc : {:?}\n", self.loc_memory);
-                println!("____________________________________________________\n");
+                self.emit_print(message);
+            }
+
+            Instruction::Debug(options) => {
+                self.emit_debug(options);
             }
 
             Instruction::Error(error) => {
-                println!("Error: {}", error);
+                self.emit_error(error);
             }
 
             Instruction::Assert => {
@@ -373,12 +318,20 @@ impl MidenProgram {
             _ => {}
         }
 
-        while self.stack.len() < 16 {
-            self.stack.push_back(Felt::from(0_u64));
-        }
+        self.pad_stack();
     }
 
     pub fn exec(&mut self, name: &str) {
         self.add_instruction(Instruction::Exec(name.to_string()));
     }
+
+    /// Pushes `Syscall` instruction onto the stack. Kernel procedures are not simulated, the
+    /// call only takes effect when the program runs on the VM.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the kernel procedure.
+    pub fn syscall(&mut self, name: &str) {
+        self.add_instruction(Instruction::Syscall(name.to_string()));
+    }
 }
@@ -2,7 +2,7 @@ use miden::math::{Felt, StarkField};
 
 use crate::{Instruction, MidenProgram};
 
-use super::utils::U32_MAX;
+use super::utils::U32_MODULUS;
 
 pub fn execute_u32_arithmetic(program: &mut MidenProgram, operand: &Instruction) {
     match operand {
@@ -29,18 +29,19 @@ pub fn execute_u32_arithmetic(program: &mut MidenProgram, operand: &Instruction)
                 let a_int = a.as_int();
                 let b_int = b.as_int();
                 let c_int = a_int + b_int;
-                let d = c_int / U32_MAX;
+                let d = c_int / U32_MODULUS;
 
-                program.stack.push_front(Felt::from(c_int % U32_MAX));
+                program.stack.push_front(Felt::from(c_int % U32_MODULUS));
                 program.stack.push_front(Felt::from(d));
             }
         }
         Instruction::U32OverflowingAddImm(b) => {
             if let Some(a) = program.stack.pop_front() {
                 let a_int = a.as_int();
-                let c_int = (a_int + *b as u64) % U32_MAX;
+                let c_int = a_int + *b as u64;
 
-                program.stack.push_front(Felt::from(c_int));
+                program.stack.push_front(Felt::from(c_int % U32_MODULUS));
+                program.stack.push_front(Felt::from(c_int / U32_MODULUS));
             }
         }
 
@@ -48,7 +49,7 @@ pub fn execute_u32_arithmetic(program: &mut MidenProgram, operand: &Instruction)
             if let (Some(b), Some(a)) = (program.stack.pop_front(), program.stack.pop_front()) {
                 let a_int = a.as_int();
                 let b_int = b.as_int();
-                let c_int = (a_int + b_int) % U32_MAX;
+                let c_int = (a_int + b_int) % U32_MODULUS;
 
                 program.stack.push_front(Felt::from(c_int));
             }
@@ -56,7 +57,7 @@ pub fn execute_u32_arithmetic(program: &mut MidenProgram, operand: &Instruction)
         Instruction::U32WrappingAddImm(b) => {
             if let Some(a) = program.stack.pop_front() {
                 let a_int = a.as_int();
-                let c_int = (a_int + *b as u64) % U32_MAX;
+                let c_int = (a_int + *b as u64) % U32_MODULUS;
 
                 program.stack.push_front(Felt::from(c_int));
             }
@@ -72,8 +73,8 @@ pub fn execute_u32_arithmetic(program: &mut MidenProgram, operand: &Instruction)
                 let b_int = b.as_int();
                 let c_int = c.as_int();
                 let sum = a_int + b_int + c_int;
-                let d = sum % U32_MAX;
-                let e = sum / U32_MAX;
+                let d = sum % U32_MODULUS;
+                let e = sum / U32_MODULUS;
 
                 program.stack.push_front(Felt::from(d));
                 program.stack.push_front(Felt::from(e));
@@ -90,7 +91,7 @@ pub fn execute_u32_arithmetic(program: &mut MidenProgram, operand: &Instruction)
                 let b_int = b.as_int();
                 let c_int = c.as_int();
                 let sum = a_int + b_int + c_int;
-                let d = sum % U32_MAX;
+                let d = sum % U32_MODULUS;
 
                 program.stack.push_front(Felt::from(d));
             }
@@ -119,10 +120,10 @@ pub fn execute_u32_arithmetic(program: &mut MidenProgram, operand: &Instruction)
             if let (Some(b), Some(a)) = (program.stack.pop_front(), program.stack.pop_front()) {
                 let a_int = a.as_int();
                 let b_int = b.as_int();
-                let c_int = a_int - b_int;
+                let c_int = a_int + U32_MODULUS - b_int;
                 let d = a_int < b_int;
 
-                program.stack.push_front(Felt::from(c_int % U32_MAX));
+                program.stack.push_front(Felt::from(c_int % U32_MODULUS));
                 program.stack.push_front(Felt::from(d as u64));
             }
         }
@@ -131,10 +132,10 @@ pub fn execute_u32_arithmetic(program: &mut MidenProgram, operand: &Instruction)
             if let Some(a) = program.stack.pop_front() {
                 let a_int = a.as_int();
                 let b_int = *b as u64;
-                let c_int = a_int - b_int;
+                let c_int = a_int + U32_MODULUS - b_int;
                 let d = a_int < b_int;
 
-                program.stack.push_front(Felt::from(c_int % U32_MAX));
+                program.stack.push_front(Felt::from(c_int % U32_MODULUS));
                 program.stack.push_front(Felt::from(d as u64));
             }
         }
@@ -143,9 +144,9 @@ pub fn execute_u32_arithmetic(program: &mut MidenProgram, operand: &Instruction)
             if let (Some(b), Some(a)) = (program.stack.pop_front(), program.stack.pop_front()) {
                 let a_int = a.as_int();
                 let b_int = b.as_int();
-                let c_int = a_int - b_int;
+                let c_int = a_int + U32_MODULUS - b_int;
 
-                program.stack.push_front(Felt::from(c_int % U32_MAX));
+                program.stack.push_front(Felt::from(c_int % U32_MODULUS));
             }
         }
 
@@ -153,9 +154,9 @@ pub fn execute_u32_arithmetic(program: &mut MidenProgram, operand: &Instruction)
             if let Some(a) = program.stack.pop_front() {
                 let a_int = a.as_int();
                 let b_int = *b as u64;
-                let c_int = a_int - b_int;
+                let c_int = a_int + U32_MODULUS - b_int;
 
-                program.stack.push_front(Felt::from(c_int % U32_MAX));
+                program.stack.push_front(Felt::from(c_int % U32_MODULUS));
             }
         }
 
@@ -184,9 +185,9 @@ pub fn execute_u32_arithmetic(program: &mut MidenProgram, operand: &Instruction)
                 let a_int = a.as_int();
                 let b_int = b.as_int();
                 let c_int = a_int * b_int;
-                let d = c_int / U32_MAX;
+                let d = c_int / U32_MODULUS;
 
-                program.stack.push_front(Felt::from(c_int % U32_MAX));
+                program.stack.push_front(Felt::from(c_int % U32_MODULUS));
                 program.stack.push_front(Felt::from(d));
             }
         }
@@ -196,9 +197,9 @@ pub fn execute_u32_arithmetic(program: &mut MidenProgram, operand: &Instruction)
                 let a_int = a.as_int();
                 let b_int = *b as u64;
                 let c_int = a_int * b_int;
-                let d = c_int / U32_MAX;
+                let d = c_int / U32_MODULUS;
 
-                program.stack.push_front(Felt::from(c_int % U32_MAX));
+                program.stack.push_front(Felt::from(c_int % U32_MODULUS));
                 program.stack.push_front(Felt::from(d));
             }
         }
@@ -209,7 +210,7 @@ pub fn execute_u32_arithmetic(program: &mut MidenProgram, operand: &Instruction)
                 let b_int = b.as_int();
                 let c_int = a_int * b_int;
 
-                program.stack.push_front(Felt::from(c_int % U32_MAX));
+                program.stack.push_front(Felt::from(c_int % U32_MODULUS));
             }
         }
 
@@ -219,7 +220,7 @@ pub fn execute_u32_arithmetic(program: &mut MidenProgram, operand: &Instruction)
                 let b_int = *b as u64;
                 let c_int = a_int * b_int;
 
-                program.stack.push_front(Felt::from(c_int % U32_MAX));
+                program.stack.push_front(Felt::from(c_int % U32_MODULUS));
             }
         }
 
@@ -235,10 +236,10 @@ pub fn execute_u32_arithmetic(program: &mut MidenProgram, operand: &Instruction)
 
                 program
                     .stack
-                    .push_front(Felt::from((a_int + b_int + c_int) % U32_MAX));
+                    .push_front(Felt::from((a_int * b_int + c_int) % U32_MODULUS));
                 program
                     .stack
-                    .push_front(Felt::from((a_int + b_int + c_int) / U32_MAX));
+                    .push_front(Felt::from((a_int * b_int + c_int) / U32_MODULUS));
             }
         }
 
@@ -254,7 +255,7 @@ pub fn execute_u32_arithmetic(program: &mut MidenProgram, operand: &Instruction)
 
                 program
                     .stack
-                    .push_front(Felt::from((a_int + b_int + c_int) % U32_MAX));
+                    .push_front(Felt::from((a_int * b_int + c_int) % U32_MODULUS));
             }
         }
 
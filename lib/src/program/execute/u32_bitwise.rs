@@ -2,7 +2,7 @@ use miden::math::{Felt, StarkField};
 
 use crate::{Instruction, MidenProgram};
 
-use super::utils::U32_MAX;
+use super::utils::U32_MODULUS;
 
 pub fn execute_u32_bitwise(program: &mut MidenProgram, operand: &Instruction) {
     match operand {
@@ -52,9 +52,9 @@ pub fn execute_u32_bitwise(program: &mut MidenProgram, operand: &Instruction) {
                 let a_int = a.as_int();
                 let b_int = b.as_int();
 
-                program.stack.push_front(Felt::from(
-                    (a_int * 2_i32.pow(b_int as u32) as u64) % U32_MAX,
-                ));
+                program
+                    .stack
+                    .push_front(Felt::from((a_int * (1u64 << b_int as u32)) % U32_MODULUS));
             }
         }
 
@@ -64,7 +64,7 @@ pub fn execute_u32_bitwise(program: &mut MidenProgram, operand: &Instruction) {
 
                 program
                     .stack
-                    .push_front(Felt::from((a_int * 2_i32.pow(*b) as u64) % U32_MAX));
+                    .push_front(Felt::from((a_int * (1u64 << *b)) % U32_MODULUS));
             }
         }
 
@@ -73,9 +73,9 @@ pub fn execute_u32_bitwise(program: &mut MidenProgram, operand: &Instruction) {
                 let a_int = a.as_int();
                 let b_int = b.as_int();
 
-                program.stack.push_front(Felt::from(
-                    (a_int * 2_i32.pow(b_int as u32) as u64) % U32_MAX,
-                ));
+                program
+                    .stack
+                    .push_front(Felt::from((a_int * (1u64 << b_int as u32)) % U32_MODULUS));
             }
         }
 
@@ -85,7 +85,7 @@ pub fn execute_u32_bitwise(program: &mut MidenProgram, operand: &Instruction) {
 
                 program
                     .stack
-                    .push_front(Felt::from((a_int * 2_i32.pow(*b) as u64) % U32_MAX));
+                    .push_front(Felt::from((a_int * (1u64 << *b)) % U32_MODULUS));
             }
         }
 
@@ -94,9 +94,9 @@ pub fn execute_u32_bitwise(program: &mut MidenProgram, operand: &Instruction) {
                 let a_int = a.as_int();
                 let b_int = b.as_int();
 
-                program.stack.push_front(Felt::from(
-                    (a_int / 2_i32.pow(b_int as u32) as u64) % U32_MAX,
-                ));
+                program
+                    .stack
+                    .push_front(Felt::from((a_int / (1u64 << b_int as u32)) % U32_MODULUS));
             }
         }
 
@@ -106,7 +106,7 @@ pub fn execute_u32_bitwise(program: &mut MidenProgram, operand: &Instruction) {
 
                 program
                     .stack
-                    .push_front(Felt::from((a_int / 2_i32.pow(*b) as u64) % U32_MAX));
+                    .push_front(Felt::from((a_int / (1u64 << *b)) % U32_MODULUS));
             }
         }
 
@@ -115,9 +115,9 @@ pub fn execute_u32_bitwise(program: &mut MidenProgram, operand: &Instruction) {
                 let a_int = a.as_int();
                 let b_int = b.as_int();
 
-                program.stack.push_front(Felt::from(
-                    (a_int / 2_i32.pow(b_int as u32) as u64) % U32_MAX,
-                ));
+                program
+                    .stack
+                    .push_front(Felt::from((a_int / (1u64 << b_int as u32)) % U32_MODULUS));
             }
         }
 
@@ -127,7 +127,7 @@ pub fn execute_u32_bitwise(program: &mut MidenProgram, operand: &Instruction) {
 
                 program
                     .stack
-                    .push_front(Felt::from((a_int / 2_i32.pow(*b) as u64) % U32_MAX));
+                    .push_front(Felt::from((a_int / (1u64 << *b)) % U32_MODULUS));
             }
         }
 
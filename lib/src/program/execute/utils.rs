@@ -1,4 +1,6 @@
 pub const U32_MAX: u64 = u32::MAX as u64;
+/// The modulus of wrapping `u32` arithmetic, `2^32`.
+pub const U32_MODULUS: u64 = 1 << 32;
 
 pub fn max(a: u64, b: u64) -> u64 {
     if a > b {
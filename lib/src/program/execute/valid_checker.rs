@@ -1,7 +1,10 @@
 use crate::{program::error::MidenProgramError, Instruction, MidenProgram};
 use miden::math::{Felt, FieldElement, StarkField};
 
-use super::utils::{max, U32_MAX};
+use super::{
+    advice::{key_to_hex, map_key, map_value_read},
+    utils::{max, U32_MAX},
+};
 
 impl MidenProgram {
     pub fn is_valid_operand(&mut self, operand: &Instruction) -> Option<MidenProgramError> {
@@ -50,6 +53,63 @@ impl MidenProgram {
                     ));
                 }
             }
+            Instruction::MemLoad
+            | Instruction::MemLoadW
+            | Instruction::MemStore
+            | Instruction::MemStoreW => {
+                if let Some(addr) = self.stack.front() {
+                    if addr.as_int() > U32_MAX {
+                        return Some(MidenProgramError::MemoryAddressOutOfBounds(addr.as_int()));
+                    }
+                }
+            }
+            Instruction::LocLoad(_) | Instruction::LocLoadW(_) if !self.in_procedure() => {
+                return Some(MidenProgramError::LocLoadInBegin);
+            }
+            Instruction::LocStore(_) | Instruction::LocStoreW(_) if !self.in_procedure() => {
+                return Some(MidenProgramError::LocStoreInBegin);
+            }
+            Instruction::LocLoad(index)
+            | Instruction::LocLoadW(index)
+            | Instruction::LocStore(index)
+            | Instruction::LocStoreW(index) => {
+                let count = self.get_loc_memory().len();
+                if *index as usize >= count {
+                    return Some(MidenProgramError::LocalIndexOutOfBounds(
+                        *index,
+                        count as u16,
+                    ));
+                }
+            }
+            Instruction::AdvInject(_) => {
+                if let Some((offset, _)) = map_value_read(operand) {
+                    if offset > 12 {
+                        return Some(MidenProgramError::InvalidParameter(
+                            operand.to_string(),
+                            offset,
+                            0,
+                            12,
+                        ));
+                    }
+                    let key = map_key(&self.stack, offset);
+                    if !self.advice_map.contains_key(&key) {
+                        return Some(MidenProgramError::AdviceMapKeyNotFound(key_to_hex(&key)));
+                    }
+                }
+            }
+            Instruction::AdvPipe if self.advice_stack.len() < 8 => {
+                return Some(MidenProgramError::AdviceStackReadOutOfBounds(
+                    8,
+                    self.advice_stack.len(),
+                ));
+            }
+            Instruction::AdvPipe => {
+                if let Some(addr) = self.stack.get(12) {
+                    if addr.as_int() > U32_MAX - 1 {
+                        return Some(MidenProgramError::MemoryAddressOutOfBounds(addr.as_int()));
+                    }
+                }
+            }
             // Manipulation
             Instruction::Dup(n) => {
                 if !(*n <= 15) {
@@ -151,9 +211,9 @@ impl MidenProgram {
                     let b_int = b.as_int();
                     let c_int = a_int + b_int;
 
-                    if a_int >= U32_MAX || b_int >= U32_MAX {
+                    if a_int > U32_MAX || b_int > U32_MAX {
                         return Some(MidenProgramError::NotU32Value(max(a_int, b_int)));
-                    } else if c_int >= U32_MAX {
+                    } else if c_int > U32_MAX {
                         return Some(MidenProgramError::U32Overflow(c_int));
                     }
                 }
@@ -165,9 +225,9 @@ impl MidenProgram {
                     let b_int = *b as u64;
                     let c_int = a_int + b_int;
 
-                    if a_int >= U32_MAX || b_int >= U32_MAX {
+                    if a_int > U32_MAX || b_int > U32_MAX {
                         return Some(MidenProgramError::NotU32Value(max(a_int, b_int)));
-                    } else if c_int >= U32_MAX {
+                    } else if c_int > U32_MAX {
                         return Some(MidenProgramError::U32Overflow(c_int));
                     }
                 }
@@ -180,7 +240,7 @@ impl MidenProgram {
 
                     if a_int < b_int {
                         return Some(MidenProgramError::U32InvalidSubtraction(a_int, b_int));
-                    } else if a_int >= U32_MAX || b_int >= U32_MAX {
+                    } else if a_int > U32_MAX || b_int > U32_MAX {
                         return Some(MidenProgramError::NotU32Value(if a_int < b_int {
                             a_int
                         } else {
@@ -197,7 +257,7 @@ impl MidenProgram {
 
                     if a_int < b_int {
                         return Some(MidenProgramError::U32InvalidSubtraction(a_int, b_int));
-                    } else if a_int >= U32_MAX || b_int >= U32_MAX {
+                    } else if a_int > U32_MAX || b_int > U32_MAX {
                         return Some(MidenProgramError::NotU32Value(if a_int < b_int {
                             a_int
                         } else {
@@ -213,9 +273,9 @@ impl MidenProgram {
                     let b_int = b.as_int();
                     let c_int = a_int * b_int;
 
-                    if a_int >= U32_MAX || b_int >= U32_MAX {
+                    if a_int > U32_MAX || b_int > U32_MAX {
                         return Some(MidenProgramError::NotU32Value(max(a_int, b_int)));
-                    } else if c_int >= U32_MAX {
+                    } else if c_int > U32_MAX {
                         return Some(MidenProgramError::U32Overflow(c_int));
                     }
                 }
@@ -227,9 +287,9 @@ impl MidenProgram {
                     let b_int = *b as u64;
                     let c_int = a_int * b_int;
 
-                    if a_int >= U32_MAX || b_int >= U32_MAX {
+                    if a_int > U32_MAX || b_int > U32_MAX {
                         return Some(MidenProgramError::NotU32Value(max(a_int, b_int)));
-                    } else if c_int >= U32_MAX {
+                    } else if c_int > U32_MAX {
                         return Some(MidenProgramError::U32Overflow(c_int));
                     }
                 }
@@ -242,7 +302,7 @@ impl MidenProgram {
 
                     if b_int == 0 {
                         return Some(MidenProgramError::DivideByZero);
-                    } else if a_int >= U32_MAX || b_int >= U32_MAX {
+                    } else if a_int > U32_MAX || b_int > U32_MAX {
                         return Some(MidenProgramError::NotU32Value(if b_int == 0 {
                             b_int
                         } else {
@@ -264,7 +324,7 @@ impl MidenProgram {
                             1,
                             U32_MAX as usize,
                         ));
-                    } else if a_int >= U32_MAX || b_int >= U32_MAX {
+                    } else if a_int > U32_MAX || b_int > U32_MAX {
                         return Some(MidenProgramError::NotU32Value(if b_int == 0 {
                             b_int
                         } else {
@@ -281,7 +341,7 @@ impl MidenProgram {
 
                     if b_int == 0 {
                         return Some(MidenProgramError::ModulusByZero);
-                    } else if a_int >= U32_MAX || b_int >= U32_MAX {
+                    } else if a_int > U32_MAX || b_int > U32_MAX {
                         return Some(MidenProgramError::NotU32Value(max(a_int, b_int)));
                     }
                 }
@@ -299,7 +359,7 @@ impl MidenProgram {
                             1,
                             U32_MAX as usize,
                         ));
-                    } else if a_int >= U32_MAX || b_int >= U32_MAX {
+                    } else if a_int > U32_MAX || b_int > U32_MAX {
                         return Some(MidenProgramError::NotU32Value(max(a_int, b_int)));
                     }
                 }
@@ -312,7 +372,7 @@ impl MidenProgram {
 
                     if b_int == 0 {
                         return Some(MidenProgramError::DivModByZero);
-                    } else if a_int >= U32_MAX || b_int >= U32_MAX {
+                    } else if a_int > U32_MAX || b_int > U32_MAX {
                         return Some(MidenProgramError::NotU32Value(max(a_int, b_int)));
                     }
                 }
@@ -330,7 +390,7 @@ impl MidenProgram {
                             1,
                             U32_MAX as usize,
                         ));
-                    } else if a_int >= U32_MAX || b_int >= U32_MAX {
+                    } else if a_int > U32_MAX || b_int > U32_MAX {
                         return Some(MidenProgramError::NotU32Value(max(a_int, b_int)));
                     }
                 }
@@ -341,7 +401,7 @@ impl MidenProgram {
                     let a_int = a.as_int();
                     let b_int = b.as_int();
 
-                    if a_int >= U32_MAX || b_int >= U32_MAX {
+                    if a_int > U32_MAX || b_int > U32_MAX {
                         return Some(MidenProgramError::NotU32Value(max(a_int, b_int)));
                     }
                 }
@@ -351,7 +411,7 @@ impl MidenProgram {
                 if let Some(a) = self.stack.get(0) {
                     let a_int = a.as_int();
 
-                    if a_int >= U32_MAX {}
+                    if a_int > U32_MAX {}
                 }
             }
 
@@ -367,7 +427,7 @@ impl MidenProgram {
                             0,
                             30,
                         ));
-                    } else if a_int >= U32_MAX {
+                    } else if a_int > U32_MAX {
                         return Some(MidenProgramError::NotU32Value(max(a_int, b_int)));
                     }
                 }
@@ -385,7 +445,7 @@ impl MidenProgram {
                             0,
                             30,
                         ));
-                    } else if a_int >= U32_MAX {
+                    } else if a_int > U32_MAX {
                         return Some(MidenProgramError::NotU32Value(max(a_int, b_int)));
                     }
                 }
@@ -402,7 +462,7 @@ impl MidenProgram {
                             0,
                             30,
                         ));
-                    } else if a_int >= U32_MAX {
+                    } else if a_int > U32_MAX {
                         return Some(MidenProgramError::NotU32Value(max(a_int, b_int)));
                     }
                 }
@@ -419,7 +479,7 @@ impl MidenProgram {
                             0,
                             30,
                         ));
-                    } else if a_int >= U32_MAX {
+                    } else if a_int > U32_MAX {
                         return Some(MidenProgramError::NotU32Value(max(a_int, b_int)));
                     }
                 }
@@ -436,7 +496,7 @@ impl MidenProgram {
                             0,
                             30,
                         ));
-                    } else if a_int >= U32_MAX {
+                    } else if a_int > U32_MAX {
                         return Some(MidenProgramError::NotU32Value(max(a_int, b_int)));
                     }
                 }
@@ -453,7 +513,7 @@ impl MidenProgram {
                             0,
                             30,
                         ));
-                    } else if a_int >= U32_MAX {
+                    } else if a_int > U32_MAX {
                         return Some(MidenProgramError::NotU32Value(max(a_int, b_int)));
                     }
                 }
@@ -470,7 +530,7 @@ impl MidenProgram {
                             0,
                             30,
                         ));
-                    } else if a_int >= U32_MAX {
+                    } else if a_int > U32_MAX {
                         return Some(MidenProgramError::NotU32Value(max(a_int, b_int)));
                     }
                 }
@@ -487,7 +547,7 @@ impl MidenProgram {
                             0,
                             30,
                         ));
-                    } else if a_int >= U32_MAX {
+                    } else if a_int > U32_MAX {
                         return Some(MidenProgramError::NotU32Value(max(a_int, b_int)));
                     }
                 }
@@ -1,6 +1,9 @@
 use super::error::MidenProgramError;
 use miden::math::Felt;
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+use serde::{Deserialize, Serialize};
+
+use super::serialize::felt;
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum SignatureKind {
     RpoFalcon512,
 }
@@ -13,7 +16,7 @@ impl std::fmt::Display for SignatureKind {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum AdviceInjector {
     PushMapVal,
     PushMapValImm(u8),
@@ -55,7 +58,37 @@ impl std::fmt::Display for AdviceInjector {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// The state printed by a `debug` decorator.
+///
+/// miden-vm 0.7 only assembles the `stack` variants, so the `mem` and `local` variants are
+/// commented out when a program is assembled by [`crate::MidenProgram::prove`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum DebugOptions {
+    StackAll,
+    StackTop(u16),
+    MemAll,
+    MemInterval(u32, u32),
+    LocalAll,
+    LocalInterval(u16, u16),
+}
+
+impl std::fmt::Display for DebugOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use DebugOptions::*;
+        match self {
+            StackAll => write!(f, "stack"),
+            StackTop(n) => write!(f, "stack.{n}"),
+            MemAll => write!(f, "mem"),
+            MemInterval(start, end) if start == end => write!(f, "mem.{start}"),
+            MemInterval(start, end) => write!(f, "mem.{start}.{end}"),
+            LocalAll => write!(f, "local"),
+            LocalInterval(start, end) if start == end => write!(f, "local.{start}"),
+            LocalInterval(start, end) => write!(f, "local.{start}.{end}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Instruction {
     // assertions and tests
     Assert,
@@ -63,7 +96,7 @@ pub enum Instruction {
     AssertEq,
     AssertEqW,
 
-    Push(Felt),
+    Push(#[serde(with = "felt")] Felt),
     Drop,
     DropW,
     Dup(usize),   // 1-15
@@ -72,6 +105,7 @@ pub enum Instruction {
     SwapW(usize), // 1-3
     SwapDw,
     PadW,
+    SDepth,
 
     MovUp(usize),  // 2-15
     MovUpW(usize), // 2-3
@@ -81,13 +115,13 @@ pub enum Instruction {
 
     // Arithmetic and Boolean operations
     Add,
-    AddImm(Felt),
+    AddImm(#[serde(with = "felt")] Felt),
     Sub,
-    SubImm(Felt),
+    SubImm(#[serde(with = "felt")] Felt),
     Mul,
-    MulImm(Felt),
+    MulImm(#[serde(with = "felt")] Felt),
     Div,
-    DivImm(Felt),
+    DivImm(#[serde(with = "felt")] Felt),
     Neg,
     Inv,
     Incr,
@@ -102,9 +136,9 @@ pub enum Instruction {
 
     // Comparison operations
     Eq,
-    EqImm(Felt),
+    EqImm(#[serde(with = "felt")] Felt),
     Neq,
-    NeqImm(Felt),
+    NeqImm(#[serde(with = "felt")] Felt),
     EqW,
     Lt,
     Lte,
@@ -139,6 +173,7 @@ pub enum Instruction {
     BEGIN,
 
     PRINT(String),
+    Debug(DebugOptions),
 
     MemLoad,
     MemLoadImm(u32),
@@ -158,6 +193,7 @@ pub enum Instruction {
     Decrement,
 
     Exec(String),
+    Syscall(String),
 
     Error(MidenProgramError),
 
@@ -275,6 +311,10 @@ pub enum Instruction {
     U32UncheckedMax,
 
     CommentedOut(String),
+    Comment(String),
+    DocComment(String),
+    /// A comment written after an instruction on the same line, emitted back on that line.
+    InlineComment(String),
 }
 
 impl std::fmt::Display for Instruction {
@@ -295,6 +335,7 @@ impl std::fmt::Display for Instruction {
             Self::SwapW(value) => write!(f, "swapw.{value}"),
             Self::SwapDw => write!(f, "swapdw"),
             Self::PadW => write!(f, "padw"),
+            Self::SDepth => write!(f, "sdepth"),
             Self::MovUp(value) => write!(f, "movup.{value}"),
             Self::MovUpW(value) => write!(f, "movupw.{value}"),
             Self::MovDn(value) => write!(f, "movdn.{value}"),
@@ -372,13 +413,18 @@ impl std::fmt::Display for Instruction {
             Self::REPEAT(value) => write!(f, "repeat.{value}"),
             Self::BEGIN => write!(f, "begin"),
             Self::Exec(value) => write!(f, "exec.{value}"),
-            Self::PRINT(_) => write!(f, ""),
+            Self::Syscall(value) => write!(f, "syscall.{value}"),
+            Self::PRINT(_) => write!(f, "debug.stack"),
+            Self::Debug(options) => write!(f, "debug.{options}"),
 
             Self::Increment => write!(f, "add.1"),
             Self::Decrement => write!(f, "sub.1"),
 
             Self::Error(e) => write!(f, "{e}"),
             Self::CommentedOut(message) => write!(f, "#{message}"),
+            Self::Comment(comment) => write!(f, "#{comment}"),
+            Self::DocComment(comment) => write!(f, "#!{comment}"),
+            Self::InlineComment(comment) => write!(f, "#{comment}"),
 
             // u32 arithmetic operations
             Self::U32CheckedAdd => write!(f, "u32checked_add"),
@@ -33,11 +33,12 @@ impl MidenProgram {
         self.add_instruction(op);
     }
     /// Stores the second value on the stack in memory at the address specified by the first value on the stack.
+    /// The value is written to the first element of the word at that address; the other elements are left untouched.
     pub fn mem_store(&mut self) {
         self.add_instruction(Instruction::MemStore);
     }
 
-    /// Stores the first value in the stack in memory at the address specified by `n`.
+    /// Stores the first value in the stack in memory at the address specified by `n`, in the first element of the word.
     ///
     /// # Arguments
     ///
@@ -58,12 +59,12 @@ impl MidenProgram {
         self.add_instruction(Instruction::MemStoreWImm(n));
     }
 
-    /// Assumes top value on the stack is an address and pops it off, then loads the value at that address from RAM onto the stack.
+    /// Assumes top value on the stack is an address and pops it off, then loads the first element of the word at that address from RAM onto the stack.
     pub fn mem_load(&mut self) {
         self.add_instruction(Instruction::MemLoad);
     }
 
-    /// Loads the value at address `n` from RAM onto the stack.
+    /// Loads the first element of the word at address `n` from RAM onto the stack.
     ///
     /// # Arguments
     ///
@@ -0,0 +1,483 @@
+//! Integers of several 32-bit limbs, operated on with the `u32` instructions.
+//!
+//! An integer of `n` limbs takes `n` stack elements, with its most significant limb on top, as
+//! in `std::math::u64`. In the builders below, limbs are listed least significant first.
+
+use super::{
+    instruction::Instruction,
+    stack_builder::{StackBuilder, StackBuilderError, StackValue},
+};
+use crate::{MidenProgram, Proc, Program};
+use miden::math::Felt;
+
+type Result<T> = std::result::Result<T, StackBuilderError>;
+
+/// The limbs of an integer, the least significant first.
+pub(crate) type Limbs = Vec<StackValue>;
+
+/// Where the limbs of an integer live while an operation runs.
+#[derive(Clone, Debug)]
+pub(crate) enum Register {
+    Stack(Limbs),
+    /// The locals from the given index on, a limb per local.
+    Locals(u16),
+}
+
+impl Register {
+    /// Returns the limb for a change, which must be written back with `put`.
+    fn take(&mut self, b: &mut StackBuilder, i: usize) -> Result<StackValue> {
+        match self {
+            Register::Stack(limbs) => Ok(limbs[i]),
+            Register::Locals(base) => {
+                Ok(b.apply(Instruction::LocLoad(*base + i as u16), &[], 1)?[0])
+            }
+        }
+    }
+
+    /// Returns a copy of the limb.
+    fn copy(&self, b: &mut StackBuilder, i: usize) -> Result<StackValue> {
+        match self {
+            Register::Stack(limbs) => b.dup(limbs[i]),
+            Register::Locals(base) => {
+                Ok(b.apply(Instruction::LocLoad(*base + i as u16), &[], 1)?[0])
+            }
+        }
+    }
+
+    fn put(&mut self, b: &mut StackBuilder, i: usize, value: StackValue) -> Result<()> {
+        match self {
+            Register::Stack(limbs) => {
+                limbs[i] = value;
+                Ok(())
+            }
+            Register::Locals(base) => {
+                b.apply(Instruction::LocStore(*base + i as u16), &[value], 0)?;
+                Ok(())
+            }
+        }
+    }
+
+    fn stack_limbs(&self) -> Limbs {
+        match self {
+            Register::Stack(limbs) => limbs.clone(),
+            Register::Locals(_) => Vec::new(),
+        }
+    }
+
+    /// Replaces the limbs of a stack register with the next limbs of `values`.
+    fn with_stack_limbs(&self, values: &mut impl Iterator<Item = StackValue>) -> Register {
+        match self {
+            Register::Stack(limbs) => Register::Stack(values.take(limbs.len()).collect()),
+            Register::Locals(base) => Register::Locals(*base),
+        }
+    }
+}
+
+/// Names the integer of `n` limbs below the values of the builder.
+pub(crate) fn input(b: &mut StackBuilder, n: usize) -> Limbs {
+    let mut limbs: Limbs = (0..n).map(|_| b.input()).collect();
+    limbs.reverse();
+    limbs
+}
+
+/// Pushes the integer with the given limbs, the least significant first.
+pub(crate) fn push(program: &mut MidenProgram, limbs: &[u32]) {
+    for limb in limbs {
+        program.push(*limb as u64);
+    }
+}
+
+/// Builds the instructions of an operation and adds them to the program.
+pub(crate) fn emit<F>(program: &mut MidenProgram, build: F)
+where
+    F: FnOnce(&mut StackBuilder) -> Result<()>,
+{
+    let mut b = StackBuilder::new();
+    build(&mut b).expect("limb operations stay within reach of the stack");
+    program.add_program(&mut b);
+}
+
+/// Builds an operation on the two integers of `n` limbs on top of the stack, `b` on top of
+/// `a`, which leaves the returned values, the last one on top.
+pub(crate) fn emit_binary<F>(program: &mut MidenProgram, n: usize, build: F)
+where
+    F: FnOnce(&mut StackBuilder, Limbs, Limbs) -> Result<Vec<StackValue>>,
+{
+    emit(program, |b| {
+        let rhs = input(b, n);
+        let lhs = input(b, n);
+        let outputs = build(b, lhs, rhs)?;
+        b.move_to_top(&outputs)
+    });
+}
+
+/// Like `emit_binary`, with the operation in a procedure that is added to the program on first
+/// use and called with `exec`.
+pub(crate) fn exec_binary<F>(
+    program: &mut MidenProgram,
+    name: &str,
+    num_locals: u16,
+    n: usize,
+    build: F,
+) where
+    F: FnOnce(&mut StackBuilder, Limbs, Limbs) -> Result<Vec<StackValue>>,
+{
+    if !program.get_proc_names().iter().any(|proc| proc == name) {
+        let mut b = StackBuilder::new();
+        let rhs = input(&mut b, n);
+        let lhs = input(&mut b, n);
+        build(&mut b, lhs, rhs)
+            .and_then(|outputs| b.move_to_top(&outputs))
+            .expect("limb operations stay within reach of the stack");
+
+        let mut proc = Proc::new(name).with_locals(num_locals);
+        proc.add_instructions(b.into_program().get_instructions());
+        program.add_proc(proc);
+    }
+    program.exec(name);
+}
+
+/// Stores the limbs to the locals from `base` on.
+pub(crate) fn store(b: &mut StackBuilder, limbs: Limbs, base: u16) -> Result<Register> {
+    for (i, limb) in limbs.into_iter().enumerate() {
+        b.apply(Instruction::LocStore(base + i as u16), &[limb], 0)?;
+    }
+    Ok(Register::Locals(base))
+}
+
+pub(crate) fn drop(b: &mut StackBuilder, limbs: Limbs) -> Result<()> {
+    limbs.into_iter().try_for_each(|limb| b.drop(limb))
+}
+
+/// `lhs + rhs`, with the carry out of the most significant limb.
+pub(crate) fn overflowing_add(
+    b: &mut StackBuilder,
+    lhs: Limbs,
+    rhs: Limbs,
+) -> Result<(Limbs, StackValue)> {
+    let mut sum = Vec::new();
+    let mut carry = None;
+    for (x, y) in lhs.into_iter().zip(rhs) {
+        let out = match carry {
+            None => b.apply(Instruction::U32OverflowingAdd, &[x, y], 2)?,
+            Some(carry) => b.apply(Instruction::U32OverflowingAdd3, &[carry, x, y], 2)?,
+        };
+        carry = Some(out[0]);
+        sum.push(out[1]);
+    }
+    Ok((sum, carry.expect("integers have at least one limb")))
+}
+
+/// `lhs - rhs`, with the borrow out of the most significant limb, which is `1` when `lhs < rhs`.
+pub(crate) fn overflowing_sub(
+    b: &mut StackBuilder,
+    lhs: Limbs,
+    rhs: Limbs,
+) -> Result<(Limbs, StackValue)> {
+    let mut limbs = lhs.into_iter().zip(rhs);
+    sub_with(b, limbs.len(), |_, _| Ok(limbs.next().unwrap()))
+}
+
+/// Subtracts limb by limb, with the operands of each limb returned by `limb` just before they
+/// are used, so copies do not pile up on the stack.
+fn sub_with<F>(b: &mut StackBuilder, n: usize, mut limb: F) -> Result<(Limbs, StackValue)>
+where
+    F: FnMut(&mut StackBuilder, usize) -> Result<(StackValue, StackValue)>,
+{
+    let mut difference = Vec::new();
+    let mut borrow: Option<StackValue> = None;
+    for i in 0..n {
+        let (x, y) = limb(b, i)?;
+        let out = b.apply(Instruction::U32OverflowingSub, &[x, y], 2)?;
+        match borrow {
+            None => {
+                borrow = Some(out[0]);
+                difference.push(out[1]);
+            }
+            Some(previous) => {
+                let out_borrow = b.apply(Instruction::U32OverflowingSub, &[out[1], previous], 2)?;
+                borrow = Some(b.or(out[0], out_borrow[0])?);
+                difference.push(out_borrow[1]);
+            }
+        }
+    }
+    Ok((difference, borrow.expect("integers have at least one limb")))
+}
+
+/// Asserts that a carry or borrow is `0`.
+pub(crate) fn assert_zero(b: &mut StackBuilder, flag: StackValue) -> Result<()> {
+    b.apply(Instruction::Assertz, &[flag], 0)?;
+    Ok(())
+}
+
+pub(crate) fn lt(b: &mut StackBuilder, lhs: Limbs, rhs: Limbs) -> Result<StackValue> {
+    let (difference, borrow) = overflowing_sub(b, lhs, rhs)?;
+    drop(b, difference)?;
+    Ok(borrow)
+}
+
+pub(crate) fn eq(b: &mut StackBuilder, lhs: Limbs, rhs: Limbs) -> Result<StackValue> {
+    let mut result: Option<StackValue> = None;
+    for (x, y) in lhs.into_iter().zip(rhs) {
+        let equal = b.eq(x, y)?;
+        result = Some(match result {
+            None => equal,
+            Some(result) => b.and(result, equal)?,
+        });
+    }
+    Ok(result.expect("integers have at least one limb"))
+}
+
+pub(crate) fn eqz(b: &mut StackBuilder, limbs: Limbs) -> Result<StackValue> {
+    let mut result: Option<StackValue> = None;
+    for x in limbs {
+        let zero = b.apply(Instruction::EqImm(Felt::new(0)), &[x], 1)?[0];
+        result = Some(match result {
+            None => zero,
+            Some(result) => b.and(result, zero)?,
+        });
+    }
+    Ok(result.expect("integers have at least one limb"))
+}
+
+/// Applies a bitwise `u32` instruction to each pair of limbs.
+pub(crate) fn bitwise(
+    b: &mut StackBuilder,
+    instruction: Instruction,
+    lhs: Limbs,
+    rhs: Limbs,
+) -> Result<Limbs> {
+    lhs.into_iter()
+        .zip(rhs)
+        .map(|(x, y)| Ok(b.apply(instruction.clone(), &[x, y], 1)?[0]))
+        .collect()
+}
+
+/// `limbs << shift`, discarding the bits shifted out.
+pub(crate) fn shl(b: &mut StackBuilder, limbs: Limbs, shift: u32) -> Result<Limbs> {
+    let n = limbs.len();
+    let (whole, bits) = ((shift / 32) as usize, shift % 32);
+    let mut result: Limbs = (0..whole.min(n)).map(|_| b.push(0)).collect();
+    let mut carry: Option<StackValue> = None;
+    for (i, x) in limbs.into_iter().enumerate() {
+        if i + whole >= n {
+            b.drop(x)?;
+        } else if bits == 0 {
+            result.push(x);
+        } else {
+            // x * 2^bits splits into the bits that stay in the limb and the bits that move up
+            let out = b.apply(Instruction::U32OverflowingMulImm(1 << bits), &[x], 2)?;
+            result.push(match carry {
+                None => out[1],
+                Some(carry) => b.add(out[1], carry)?,
+            });
+            carry = Some(out[0]);
+        }
+    }
+    if let Some(carry) = carry {
+        b.drop(carry)?;
+    }
+    Ok(result)
+}
+
+/// `limbs >> shift`.
+pub(crate) fn shr(b: &mut StackBuilder, limbs: Limbs, shift: u32) -> Result<Limbs> {
+    let n = limbs.len();
+    let (whole, bits) = ((shift / 32) as usize, shift % 32);
+    let mut result: Vec<Option<StackValue>> = vec![None; n];
+    let mut low: Option<StackValue> = None;
+    for (i, x) in limbs.into_iter().enumerate().rev() {
+        if i < whole {
+            b.drop(x)?;
+        } else if bits == 0 {
+            result[i - whole] = Some(x);
+        } else {
+            // x * 2^(32 - bits) splits into x >> bits and the bits that move down a limb
+            let out = b.apply(Instruction::U32OverflowingMulImm(1 << (32 - bits)), &[x], 2)?;
+            result[i - whole] = Some(match low {
+                None => out[0],
+                Some(low) => b.add(out[0], low)?,
+            });
+            low = Some(out[1]);
+        }
+    }
+    if let Some(low) = low {
+        b.drop(low)?;
+    }
+    Ok(result
+        .into_iter()
+        .map(|limb| limb.unwrap_or_else(|| b.push(0)))
+        .collect())
+}
+
+/// The product of two integers of `n` limbs, truncated to `size` limbs, which is `n` for a
+/// wrapping product and `2 * n` for the full one.
+pub(crate) fn mul(
+    b: &mut StackBuilder,
+    mut lhs: Register,
+    mut rhs: Register,
+    n: usize,
+    size: usize,
+) -> Result<Limbs> {
+    // the limbs of stack operands are copied but for their last use
+    let mut uses_lhs: Vec<usize> = (0..n).map(|j| n.min(size - j)).collect();
+    let mut uses_rhs: Vec<usize> = (0..n).map(|i| n.min(size - i)).collect();
+    fn fetch(
+        b: &mut StackBuilder,
+        register: &mut Register,
+        uses: &mut [usize],
+        i: usize,
+    ) -> Result<StackValue> {
+        uses[i] -= 1;
+        match uses[i] {
+            0 => register.take(b, i),
+            _ => register.copy(b, i),
+        }
+    }
+
+    let mut product: Vec<Option<StackValue>> = vec![None; size];
+    for i in 0..n.min(size) {
+        let mut carry: Option<StackValue> = None;
+        for j in 0..n.min(size - i) {
+            let x = fetch(b, &mut lhs, &mut uses_lhs, j)?;
+            let y = fetch(b, &mut rhs, &mut uses_rhs, i)?;
+            let k = i + j;
+            let last = k == size - 1;
+
+            // x * y + product[k] + carry, split into the limb k and the carry to the next limb
+            let (high, low) = match (product[k].take(), last) {
+                (None, false) => {
+                    let out = b.apply(Instruction::U32OverflowingMul, &[x, y], 2)?;
+                    (Some(out[0]), out[1])
+                }
+                (None, true) => (None, b.apply(Instruction::U32WrappingMul, &[x, y], 1)?[0]),
+                (Some(sum), false) => {
+                    let out = b.apply(Instruction::U32OverflowingMadd, &[sum, x, y], 2)?;
+                    (Some(out[0]), out[1])
+                }
+                (Some(sum), true) => (
+                    None,
+                    b.apply(Instruction::U32WrappingMadd, &[sum, x, y], 1)?[0],
+                ),
+            };
+            let (high, low) = match (carry, high) {
+                (None, _) => (high, low),
+                (Some(carry), None) => (
+                    None,
+                    b.apply(Instruction::U32WrappingAdd, &[low, carry], 1)?[0],
+                ),
+                (Some(carry), Some(high)) => {
+                    let out = b.apply(Instruction::U32OverflowingAdd, &[low, carry], 2)?;
+                    // x * y + sum + carry < 2^64, so the high limb does not overflow
+                    (Some(b.add(high, out[0])?), out[1])
+                }
+            };
+            product[k] = Some(low);
+            carry = high;
+        }
+        if let Some(carry) = carry {
+            product[i + n] = Some(carry);
+        }
+    }
+
+    Ok(product
+        .into_iter()
+        .map(|limb| limb.expect("every limb of the product is computed"))
+        .collect())
+}
+
+/// The quotient and remainder of `lhs / rhs`, with a restoring division of a bit per iteration
+/// of a `repeat` block. The remainder is kept on the stack, or in the locals from `locals` on.
+/// Fails if `rhs` is `0`.
+pub(crate) fn divmod(
+    b: &mut StackBuilder,
+    lhs: Register,
+    rhs: Register,
+    locals: Option<u16>,
+    n: usize,
+) -> Result<(Limbs, Limbs)> {
+    let copies = (0..n).map(|i| rhs.copy(b, i)).collect::<Result<Limbs>>()?;
+    let zero = eqz(b, copies)?;
+    assert_zero(b, zero)?;
+
+    let remainder = match locals {
+        None => Register::Stack((0..n).map(|_| b.push(0)).collect()),
+        Some(base) => {
+            let zeros = (0..n).map(|_| b.push(0)).collect();
+            store(b, zeros, base)?
+        }
+    };
+
+    let registers = [lhs, rhs, remainder];
+    let state: Limbs = registers.iter().flat_map(Register::stack_limbs).collect();
+    let state = b.repeat(32 * n, &state, |b, state| {
+        let mut values = state.into_iter();
+        let [mut quotient, divisor, mut remainder] = registers
+            .clone()
+            .map(|register| register.with_stack_limbs(&mut values));
+        division_step(b, n, &mut quotient, &divisor, &mut remainder)?;
+        Ok([quotient, divisor, remainder]
+            .iter()
+            .flat_map(Register::stack_limbs)
+            .collect())
+    })?;
+    let mut values = state.into_iter();
+    let [mut quotient, divisor, mut remainder] =
+        registers.map(|register| register.with_stack_limbs(&mut values));
+
+    drop(b, divisor.stack_limbs())?;
+    let quotient = (0..n)
+        .map(|i| quotient.take(b, i))
+        .collect::<Result<Limbs>>()?;
+    let remainder = (0..n)
+        .map(|i| remainder.take(b, i))
+        .collect::<Result<Limbs>>()?;
+    Ok((quotient, remainder))
+}
+
+/// Shifts the next bit of the dividend, which starts in the quotient, into the remainder, and
+/// subtracts the divisor from the remainder if it fits, setting the bit of the quotient.
+fn division_step(
+    b: &mut StackBuilder,
+    n: usize,
+    quotient: &mut Register,
+    divisor: &Register,
+    remainder: &mut Register,
+) -> Result<()> {
+    let mut bit: Option<StackValue> = None;
+    for register in [&mut *quotient, &mut *remainder] {
+        for i in 0..n {
+            let limb = register.take(b, i)?;
+            let out = b.apply(Instruction::U32OverflowingMulImm(2), &[limb], 2)?;
+            let shifted = match bit {
+                None => out[1],
+                Some(bit) => b.add(out[1], bit)?,
+            };
+            register.put(b, i, shifted)?;
+            bit = Some(out[0]);
+        }
+    }
+    // the remainder is less than the divisor, so shifting it left does not overflow
+    b.drop(bit.expect("integers have at least one limb"))?;
+
+    let (difference, borrow) = sub_with(b, n, |b, i| {
+        Ok((remainder.copy(b, i)?, divisor.copy(b, i)?))
+    })?;
+
+    let fits = b.dup(borrow)?;
+    let fits = b.not(fits)?;
+    let low = quotient.take(b, 0)?;
+    let low = b.add(low, fits)?;
+    quotient.put(b, 0, low)?;
+
+    for (i, difference) in difference.into_iter().enumerate() {
+        let condition = match i == n - 1 {
+            true => borrow,
+            false => b.dup(borrow)?,
+        };
+        let limb = remainder.take(b, i)?;
+        let limb = b.apply(Instruction::CDrop, &[difference, limb, condition], 1)?[0];
+        remainder.put(b, i, limb)?;
+    }
+    Ok(())
+}
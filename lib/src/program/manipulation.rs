@@ -56,6 +56,11 @@ impl MidenProgram {
         self.add_instruction(Instruction::PadW);
     }
 
+    /// Pushes the current depth of the stack onto the stack.
+    pub fn sdepth(&mut self) {
+        self.add_instruction(Instruction::SDepth);
+    }
+
     /// Moves the nth value up to the top of the stack.
     /// # Arguments
     /// * `n` - The index to move up. Must be in the range 1-15.
@@ -1,33 +1,70 @@
 mod advice_inject;
 mod block;
+mod compile;
 mod empty;
 mod error;
+mod event;
 mod execute;
 mod field;
 mod instruction;
 mod io;
+mod limbs;
 mod manipulation;
+mod outputs;
 mod parser;
 mod proc;
+#[cfg(feature = "prover")]
+mod proof_file;
+#[cfg(feature = "prover")]
+mod prove;
+mod serialize;
+mod stack_builder;
+mod state;
+mod u256;
 mod u32;
+mod u64;
+mod vm;
 use crate::Inputs;
 
 use std::cell::RefCell;
 use std::rc::Rc;
 
+use compile::CompileKey;
+pub use compile::{CompiledProgram, LibraryNamespace, LibraryVersion, MaslLibrary};
 pub use empty::*;
-pub use instruction::Instruction;
+pub use error::{MidenProgramError, VmError};
+pub use event::{EventSink, LogSink, SilentSink, StdoutSink};
+pub use instruction::{AdviceInjector, DebugOptions, Instruction, SignatureKind};
+#[cfg(feature = "prover")]
+pub use proof_file::{ProofFile, PROOF_FILE_VERSION};
+#[cfg(feature = "prover")]
+pub use prove::{
+    verify, verify_with_kernel, ExecutionProof, ProofHash, ProveOptions, ProveResult, ProveTimings,
+    SecurityLevel,
+};
+
+pub use miden::{Digest, Kernel, StackInputs, StackOutputs};
 
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 
 use miden::{
-    crypto::MerkleStore,
+    crypto::{MerkleStore, RpoDigest},
     math::{Felt, FieldElement, StarkField},
-    prove, AdviceInputs, Assembler, DefaultHost, ExecutionProof, MemAdviceProvider, ProvingOptions,
-    StackInputs,
+    AdviceInputs,
 };
 
 pub use self::proc::Proc;
+pub use outputs::Outputs;
+pub use serialize::PROGRAM_FORMAT_VERSION;
+pub(crate) use stack_builder::{eqw_and_drop, MAX_REACH};
+pub use stack_builder::{StackBuilder, StackBuilderError, StackValue};
+pub use state::{MerkleNode, ProgramState, STATE_FILE_VERSION};
+pub use vm::VmOutput;
+
+/// The minimum depth of the operand stack. Like in the Miden VM, values removed from a stack of
+/// this depth are replaced by zeros at the bottom, and values pushed beyond it go to the
+/// overflow table.
+pub const MIN_STACK_DEPTH: usize = 16;
 
 pub trait Program {
     fn get_instructions(&self) -> VecDeque<Instruction>;
@@ -37,7 +74,11 @@ pub struct MidenProgram {
     pub stack: VecDeque<Felt>,
     pub advice_stack: VecDeque<u64>,
     pub instructions: VecDeque<Instruction>,
-    pub advice_map: Option<HashMap<String, Vec<u64>>>,
+    /// The comments above `begin`, and after the `begin` block.
+    pub comments: Vec<Instruction>,
+    pub trailing_comments: Vec<Instruction>,
+    /// The advice map, read by `adv.push_mapval` with the word on the stack as the key.
+    pub advice_map: BTreeMap<RpoDigest, Vec<u64>>,
     pub merkle_store: Option<MerkleStore>,
 
     internal_programs: HashMap<String, Rc<RefCell<Proc>>>,
@@ -47,7 +88,20 @@ pub struct MidenProgram {
     advice_inputs: AdviceInputs,
 
     ram_memory: HashMap<u32, [Felt; 4]>,
-    loc_memory: HashMap<u16, [Felt; 4]>,
+    /// The locals of the procedures that are executing, the frame of each invocation right above
+    /// the frame of its caller.
+    loc_memory: Vec<[Felt; 4]>,
+    /// The start and size of the frame of each procedure that is executing, the innermost last.
+    loc_frames: Vec<(usize, usize)>,
+    /// The name of the procedure that returned last and the locals it returned with.
+    last_loc_frame: Option<(String, Vec<[Felt; 4]>)>,
+
+    event_sink: Rc<RefCell<dyn EventSink>>,
+
+    imports: Vec<String>,
+    libraries: Vec<MaslLibrary>,
+    kernel: Option<String>,
+    compiled: Option<(CompileKey, CompiledProgram)>,
 }
 
 impl MidenProgram {
@@ -58,10 +112,12 @@ impl MidenProgram {
     /// A new `MidenProgram`.
     pub fn new() -> MidenProgram {
         MidenProgram {
-            stack: VecDeque::from(vec![Felt::ZERO; 16]),
+            stack: VecDeque::from(vec![Felt::ZERO; MIN_STACK_DEPTH]),
             instructions: VecDeque::new(),
+            comments: Vec::new(),
+            trailing_comments: Vec::new(),
             advice_stack: VecDeque::new(),
-            advice_map: None,
+            advice_map: BTreeMap::new(),
             merkle_store: None,
 
             internal_programs: HashMap::new(),
@@ -70,7 +126,16 @@ impl MidenProgram {
             stack_inputs: StackInputs::default(),
             advice_inputs: AdviceInputs::default(),
             ram_memory: HashMap::new(),
-            loc_memory: HashMap::new(),
+            loc_memory: Vec::new(),
+            loc_frames: Vec::new(),
+            last_loc_frame: None,
+
+            event_sink: Rc::new(RefCell::new(SilentSink)),
+
+            imports: Vec::new(),
+            libraries: Vec::new(),
+            kernel: None,
+            compiled: None,
         }
     }
 
@@ -82,12 +147,22 @@ impl MidenProgram {
     pub fn get_masm(&self) -> String {
         let mut masm: String = String::new();
 
+        for path in self.imports.iter() {
+            masm.push_str(&format!("use.{}\n", path));
+        }
+        if !self.imports.is_empty() {
+            masm.push('\n');
+        }
+
         for name in self.internal_programs_order.iter() {
             if let Some(proc) = self.internal_programs.get(name) {
                 masm.push_str(&format!("{}\n", proc.borrow().get_masm()));
             }
         }
 
+        for comment in self.comments.iter() {
+            masm.push_str(&format!("{}\n", comment));
+        }
         masm.push_str("begin\n");
 
         let mut scope = 1;
@@ -120,7 +195,16 @@ impl MidenProgram {
                     masm.push_str(&format!("{}{}\n\n", tabs, op));
                 }
 
-                Instruction::PRINT(_) => {}
+                Instruction::InlineComment(_) => {
+                    // appended to the line of the instruction before
+                    let line = masm.trim_end_matches('\n').len();
+                    let newlines = masm.split_off(line);
+                    masm.push_str(&format!(" {}{}", op, newlines));
+                }
+                Instruction::PRINT(message) => {
+                    let tabs = "\t".repeat(scope);
+                    masm.push_str(&format!("{}# {}\n{}{}\n", tabs, message, tabs, op));
+                }
                 _ => {
                     let tabs = "\t".repeat(scope);
                     masm.push_str(&format!("{}{}\n", tabs, op));
@@ -128,12 +212,10 @@ impl MidenProgram {
             }
         }
 
-        masm.push_str(&format!("end\n\n"));
-
-        masm.push_str(&format!(
-            "#stack output : {:?} \n",
-            &self.stack.iter().map(|n| n.as_int()).collect::<Vec<u64>>()
-        ));
+        masm.push_str("end\n");
+        for comment in self.trailing_comments.iter() {
+            masm.push_str(&format!("{}\n", comment));
+        }
 
         masm
     }
@@ -147,8 +229,59 @@ impl MidenProgram {
         &self.stack
     }
 
+    /// Returns the depth of the stack, including the values in the overflow table. The depth is
+    /// never less than [`MIN_STACK_DEPTH`].
+    pub fn get_stack_depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Returns the local at `index` in the frame of the procedure that is currently executing.
+    pub(crate) fn current_local(&mut self, index: u16) -> Option<&mut [Felt; 4]> {
+        let &(start, size) = self.loc_frames.last()?;
+        if index as usize >= size {
+            return None;
+        }
+        self.loc_memory.get_mut(start + index as usize)
+    }
+
+    /// Opens a frame of `size` zeroed locals on top of the frame of the calling procedure.
+    pub(crate) fn push_loc_frame(&mut self, size: usize) {
+        let start = self
+            .loc_frames
+            .last()
+            .map_or(0, |(start, size)| start + size);
+        self.loc_memory.truncate(start);
+        self.loc_memory.resize(start + size, [Felt::ZERO; 4]);
+        self.loc_frames.push((start, size));
+    }
+
+    /// Closes the frame of the procedure `name` that returns and discards its locals.
+    pub(crate) fn pop_loc_frame(&mut self, name: &str) {
+        let locals = self.get_loc_memory().to_vec();
+        self.last_loc_frame = Some((name.to_string(), locals));
+        if let Some((start, _)) = self.loc_frames.pop() {
+            self.loc_memory.truncate(start);
+        }
+    }
+
+    /// Whether a procedure is executing.
+    pub(crate) fn in_procedure(&self) -> bool {
+        !self.loc_frames.is_empty()
+    }
+
+    /// Fills the bottom of the stack with zeros up to [`MIN_STACK_DEPTH`].
+    pub(crate) fn pad_stack(&mut self) {
+        while self.stack.len() < MIN_STACK_DEPTH {
+            self.stack.push_back(Felt::ZERO);
+        }
+    }
+
     /// Returns a reference to the RAM memory.
     ///
+    /// Like in the Miden VM, memory is word-addressed: each address holds a word, stored in the
+    /// order `mem_storew` writes it, so the element that was on the top of the stack is last.
+    /// Addresses that were never written read as four zeros.
+    ///
     /// # Returns
     ///
     /// A reference to the `HashMap` representing the RAM memory.
@@ -156,9 +289,55 @@ impl MidenProgram {
         &self.ram_memory
     }
 
-    /// Prints the Miden Assembly (MASM) representation of the program.
+    /// Returns the locals of the procedure that is currently executing.
+    ///
+    /// Every invocation of a procedure gets a frame of locals, sized by the `N` of its
+    /// `proc.name.N` declaration, with every local set to zero. See [`crate::Proc::with_locals`]
+    /// for how this differs from the Miden VM.
+    ///
+    /// # Returns
+    ///
+    /// The words of the current frame, indexed by local, or an empty slice outside of a procedure.
+    pub fn get_loc_memory(&self) -> &[[Felt; 4]] {
+        self.loc_frames
+            .last()
+            .map_or(&[], |&(start, size)| &self.loc_memory[start..start + size])
+    }
+
+    /// Returns the name and the locals of the procedure that returned last.
+    ///
+    /// Between the instructions of the `begin` block no procedure is executing, so this is the
+    /// frame to look at after an `exec`: the values its locals held when the procedure returned.
+    ///
+    /// # Returns
+    ///
+    /// The name of the procedure and the words of its frame, indexed by local, or `None` if no
+    /// procedure was invoked yet.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_masm::MidenProgram;
+    /// let program = MidenProgram::parse("proc.keep.2 push.9 loc_store.1 end begin exec.keep end")
+    ///     .unwrap();
+    /// let (name, locals) = program.get_last_loc_frame().unwrap();
+    /// assert_eq!(name, "keep");
+    /// assert_eq!(u64::from(locals[1][0]), 9);
+    /// ```
+    pub fn get_last_loc_frame(&self) -> Option<(&str, &[[Felt; 4]])> {
+        self.last_loc_frame
+            .as_ref()
+            .map(|(name, locals)| (name.as_str(), locals.as_slice()))
+    }
+
+    /// Prints the Miden Assembly (MASM) representation of the program, followed by the stack
+    /// output of the simulation.
     pub fn print_masm(&self) {
         println!("{}", self);
+        println!(
+            "#stack output : {:?}",
+            &self.stack.iter().map(|n| n.as_int()).collect::<Vec<u64>>()
+        );
     }
 
     /// Prints the operands in the instruction stack.
@@ -175,36 +354,13 @@ impl MidenProgram {
         std::fs::write(file, self.get_masm()).unwrap();
     }
 
-    /// Proves the program and returns the result.
+    /// Returns a reference to the stack inputs of the program.
     ///
     /// # Returns
     ///
-    /// The result of the proof as an `Option<ExecutionProof>`.
-    pub fn prove(&mut self) -> Option<ExecutionProof> {
-        let assembler = Assembler::default();
-        let masm = self.get_masm();
-
-        match assembler.compile(masm) {
-            Ok(program) => {
-                let advice_provider = MemAdviceProvider::from(self.advice_inputs.clone());
-
-                let host = DefaultHost::new(advice_provider);
-
-                if let Ok((_, proof)) = prove(
-                    &program,
-                    self.stack_inputs.clone(),
-                    host,
-                    ProvingOptions::default(),
-                ) {
-                    Some(proof)
-                } else {
-                    None
-                }
-            }
-            Err(_) => {
-                return None;
-            }
-        }
+    /// A reference to the `StackInputs` the program is proved with.
+    pub fn get_stack_inputs(&self) -> &StackInputs {
+        &self.stack_inputs
     }
 
     /// Sets the inputs of the program and returns the program.
@@ -215,54 +371,103 @@ impl MidenProgram {
     ///
     /// # Returns
     ///
-    /// The program with the specified inputs.
-    pub fn with_inputs(mut self, inputs: Inputs) -> Self {
-        let operand_stack: Vec<Felt> = inputs
-            .operand_stack
-            .iter()
-            .map(|n| Felt::from(*n))
-            .collect();
+    /// The program with the specified inputs, or an error if a value is not a valid field element,
+    /// a hex string is not a valid digest or the Merkle data does not form valid trees.
+    pub fn with_inputs(mut self, inputs: Inputs) -> Result<Self, String> {
+        self.stack_inputs = inputs.parse_stack_inputs()?;
 
-        self.stack_inputs = StackInputs::new(operand_stack.clone());
-
-        let mut i = 0;
         let mut stack: VecDeque<Felt> = VecDeque::new();
-        while i < operand_stack.len() {
-            stack.push_front(operand_stack[i].into());
-            i += 1;
-        }
-
-        while stack.len() < 16 {
-            stack.push_back(Felt::ZERO);
+        for n in inputs.operand_stack.iter() {
+            stack.push_front(Felt::from(*n));
         }
 
         self.stack = stack;
+        self.pad_stack();
 
         if let Some(advice_stack) = inputs.advice_stack.clone() {
             self.advice_inputs = self
                 .advice_inputs
                 .with_stack_values(advice_stack.clone())
-                .unwrap();
+                .map_err(|e| format!("failed to parse the advice stack - {e}"))?;
             self.advice_stack = VecDeque::from(advice_stack);
         }
 
-        if let Some(_) = inputs.advice_map {
-            let map = inputs.parse_advice_map().unwrap().unwrap();
-            self.advice_inputs = self.advice_inputs.with_map(map);
-            self.advice_map = inputs.advice_map.clone();
+        if let Some(map) = inputs.parse_advice_map()? {
+            for (key, values) in map {
+                let digest = RpoDigest::try_from(key).map_err(|e| {
+                    format!(
+                        "advice map key `{}` is not a valid digest - {e}",
+                        hex::encode(key)
+                    )
+                })?;
+                self.add_advice_map_entry(digest, values);
+            }
         }
 
-        if let Some(_) = inputs.merkle_store {
-            let store = inputs.parse_merkle_store().unwrap();
-            if let Some(store) = store {
-                self.advice_inputs = self.advice_inputs.with_merkle_store(store.clone());
-                self.merkle_store = Some(store);
-            }
+        if let Some(store) = inputs.parse_merkle_store()? {
+            self.emit_diagnostic(&format!(
+                "Loaded {} nodes into the Merkle store",
+                store.num_internal_nodes()
+            ));
+            self.advice_inputs = self.advice_inputs.with_merkle_store(store.clone());
+            self.merkle_store = Some(store);
         }
 
+        Ok(self)
+    }
+
+    /// Sets the sink that receives the `PRINT` output, errors and diagnostics of the program
+    /// and returns the program.
+    ///
+    /// # Arguments
+    ///
+    /// * `sink` - The sink to send events to. Keep a clone of the `Rc` to read back what the
+    ///   sink collected.
+    ///
+    /// # Returns
+    ///
+    /// The program with the specified event sink.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_masm::{MidenProgram, StdoutSink};
+    /// use std::{cell::RefCell, rc::Rc};
+    ///
+    /// let mut program = MidenProgram::new().with_event_sink(Rc::new(RefCell::new(StdoutSink)));
+    /// program.push(1);
+    /// program.print("after push");
+    /// ```
+    pub fn with_event_sink<T: EventSink + 'static>(mut self, sink: Rc<RefCell<T>>) -> Self {
+        self.event_sink = sink;
         self
     }
 
+    /// Sets the sink that receives the `PRINT` output, errors and diagnostics of the program.
+    ///
+    /// # Arguments
+    ///
+    /// * `sink` - The sink to send events to.
+    pub fn set_event_sink<T: EventSink + 'static>(&mut self, sink: Rc<RefCell<T>>) {
+        self.event_sink = sink;
+    }
+
+    pub(crate) fn emit_print(&self, message: &str) {
+        self.event_sink.clone().borrow_mut().print(message, self);
+    }
+
+    pub(crate) fn emit_debug(&self, options: &DebugOptions) {
+        self.event_sink.clone().borrow_mut().debug(options, self);
+    }
+
+    pub(crate) fn emit_error(&self, error: &MidenProgramError) {
+        self.event_sink.borrow_mut().error(error);
+    }
+
+    pub(crate) fn emit_diagnostic(&self, message: &str) {
+        self.event_sink.borrow_mut().diagnostic(message);
+    }
+
     /// Sets the operand stack of the program and returns the program.
     ///
     /// # Arguments
@@ -280,11 +485,8 @@ impl MidenProgram {
             i += 1;
         }
 
-        while stack.len() < 16 {
-            stack.push_back(Felt::ZERO);
-        }
-
         self.stack = stack;
+        self.pad_stack();
         self.stack_inputs =
             StackInputs::try_from_values(operand_stack.iter().map(|n| n.as_int())).unwrap();
         self
@@ -318,10 +520,11 @@ impl MidenProgram {
     ///
     /// * `instruction` - The operands to add.
     pub fn add_instructions(&mut self, instructions: &mut VecDeque<Instruction>) {
+        let start = self.instructions.len();
         for instruction in instructions.iter() {
             self.instructions.push_back(instruction.clone());
         }
-        self.execute_block(instructions, 0);
+        self.execute_block(instructions, start);
     }
 
     /// Adds the specified instruction to the instruction stack of the program.
@@ -332,6 +535,7 @@ impl MidenProgram {
     pub fn add_instruction(&mut self, instruction: Instruction) {
         match self.is_valid_operand(&instruction) {
             Some(error) => {
+                self.emit_error(&error);
                 self.instructions
                     .push_back(Instruction::Error(error.clone()));
                 self.instructions
@@ -365,6 +569,15 @@ impl MidenProgram {
         self.add_instruction(Instruction::PRINT(message.to_string()));
     }
 
+    /// Adds a `debug` decorator that prints the selected state of the program when executed.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The state to print.
+    pub fn debug(&mut self, options: DebugOptions) {
+        self.add_instruction(Instruction::Debug(options));
+    }
+
     /// Appends a procedure to the internal programs of the program.
     ///
     /// # Arguments
@@ -383,6 +596,68 @@ impl MidenProgram {
             self.add_proc(program);
         }
     }
+
+    /// Returns the names of the procedures of the program, in the order they were added.
+    pub fn get_proc_names(&self) -> &[String] {
+        &self.internal_programs_order
+    }
+
+    /// Returns the errors of the instructions the simulator rejected, in the procedures and then
+    /// in the main block.
+    ///
+    /// A rejected instruction is commented out of the MASM of the program, so a program with errors
+    /// does not do what its source says, even though it still assembles.
+    pub fn get_errors(&self) -> Vec<MidenProgramError> {
+        let procedures = self
+            .internal_programs_order
+            .iter()
+            .map(|name| self.internal_programs[name].borrow().instructions.clone());
+        procedures
+            .chain(std::iter::once(self.instructions.clone()))
+            .flatten()
+            .filter_map(|instruction| match instruction {
+                Instruction::Error(error) => Some(error),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Clones the program with its own copy of every procedure, so that running one of the programs
+/// leaves the other untouched. Both programs share the same event sink.
+impl Clone for MidenProgram {
+    fn clone(&self) -> Self {
+        MidenProgram {
+            stack: self.stack.clone(),
+            advice_stack: self.advice_stack.clone(),
+            instructions: self.instructions.clone(),
+            comments: self.comments.clone(),
+            trailing_comments: self.trailing_comments.clone(),
+            advice_map: self.advice_map.clone(),
+            merkle_store: self.merkle_store.clone(),
+
+            internal_programs: self
+                .internal_programs
+                .iter()
+                .map(|(name, proc)| (name.clone(), Rc::new(RefCell::new(proc.borrow().clone()))))
+                .collect(),
+            internal_programs_order: self.internal_programs_order.clone(),
+
+            stack_inputs: self.stack_inputs.clone(),
+            advice_inputs: self.advice_inputs.clone(),
+            ram_memory: self.ram_memory.clone(),
+            loc_memory: self.loc_memory.clone(),
+            loc_frames: self.loc_frames.clone(),
+            last_loc_frame: self.last_loc_frame.clone(),
+
+            event_sink: self.event_sink.clone(),
+
+            imports: self.imports.clone(),
+            libraries: self.libraries.clone(),
+            kernel: self.kernel.clone(),
+            compiled: self.compiled.clone(),
+        }
+    }
 }
 
 impl Program for MidenProgram {
@@ -391,6 +666,13 @@ impl Program for MidenProgram {
     }
 }
 
+/// Parsed instructions, such as those returned by `parse` or the `masm!` macro.
+impl Program for VecDeque<Instruction> {
+    fn get_instructions(&self) -> VecDeque<Instruction> {
+        self.clone()
+    }
+}
+
 impl std::fmt::Display for MidenProgram {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.get_masm())
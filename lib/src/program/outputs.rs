@@ -0,0 +1,230 @@
+use std::collections::BTreeMap;
+
+use miden::math::StarkField;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{error::VmError, vm::VmOutput, MidenProgram};
+
+#[derive(Serialize, Deserialize)]
+struct OutputsJson {
+    stack: Vec<String>,
+    #[serde(default)]
+    overflow_addrs: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    memory: Option<BTreeMap<u32, [String; 4]>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    program_hash: Option<String>,
+}
+
+/// The results of running a program, in the format of the `.outputs` files of the Miden CLI.
+///
+/// Outputs files are JSON documents with the operand stack, top first, and the overflow table
+/// addresses, with every value written as a decimal string. The written words of memory, keyed
+/// by address, and the program hash are optional and are left out when missing, so the files
+/// written by the Miden CLI can be read too.
+///
+/// [`MidenProgram::get_outputs`] returns the outputs of the simulator, which has memory but no
+/// overflow addresses or program hash. [`VmOutput::outputs`] returns the outputs of the VM,
+/// which has overflow addresses and a program hash but no memory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Outputs {
+    pub stack: Vec<u64>,
+    pub overflow_addrs: Vec<u64>,
+    pub memory: Option<BTreeMap<u32, [u64; 4]>>,
+    pub program_hash: Option<String>,
+}
+
+fn parse_value(value: &str) -> Result<u64, String> {
+    value
+        .parse()
+        .map_err(|e| format!("invalid value `{value}` - {e}"))
+}
+
+impl TryFrom<OutputsJson> for Outputs {
+    type Error = String;
+
+    fn try_from(json: OutputsJson) -> Result<Self, String> {
+        let parse_all = |values: &[String]| {
+            values
+                .iter()
+                .map(|value| parse_value(value))
+                .collect::<Result<Vec<u64>, String>>()
+        };
+
+        let memory = match json.memory {
+            Some(memory) => Some(
+                memory
+                    .into_iter()
+                    .map(|(address, word)| {
+                        let word = parse_all(&word)?;
+                        Ok((address, [word[0], word[1], word[2], word[3]]))
+                    })
+                    .collect::<Result<BTreeMap<u32, [u64; 4]>, String>>()?,
+            ),
+            None => None,
+        };
+
+        Ok(Outputs {
+            stack: parse_all(&json.stack)?,
+            overflow_addrs: parse_all(&json.overflow_addrs)?,
+            memory,
+            program_hash: json.program_hash,
+        })
+    }
+}
+
+impl From<&Outputs> for OutputsJson {
+    fn from(outputs: &Outputs) -> Self {
+        let to_strings = |values: &[u64]| values.iter().map(|v| v.to_string()).collect();
+
+        OutputsJson {
+            stack: to_strings(&outputs.stack),
+            overflow_addrs: to_strings(&outputs.overflow_addrs),
+            memory: outputs.memory.as_ref().map(|memory| {
+                memory
+                    .iter()
+                    .map(|(address, word)| (*address, word.map(|v| v.to_string())))
+                    .collect()
+            }),
+            program_hash: outputs.program_hash.clone(),
+        }
+    }
+}
+
+impl Serialize for Outputs {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        OutputsJson::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Outputs {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Outputs::try_from(OutputsJson::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Outputs {
+    /// Serializes the outputs to JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Deserializes outputs from JSON.
+    ///
+    /// # Arguments
+    ///
+    /// * `json` - The JSON written by [`Outputs::to_json`] or by the Miden CLI.
+    ///
+    /// # Returns
+    ///
+    /// The outputs, or an error if the JSON is malformed.
+    pub fn from_json(json: &str) -> Result<Self, VmError> {
+        serde_json::from_str(json).map_err(|e| VmError::InvalidOutputsFile(e.to_string()))
+    }
+
+    /// Writes the outputs file to disk.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The path of the file to write.
+    pub fn write(&self, file: &str) -> Result<(), VmError> {
+        std::fs::write(file, self.to_json())?;
+        Ok(())
+    }
+
+    /// Reads an outputs file from disk.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The path of the file to read.
+    ///
+    /// # Returns
+    ///
+    /// The outputs, or an error if the file cannot be read or is not a valid outputs file.
+    pub fn read(file: &str) -> Result<Self, VmError> {
+        Self::from_json(&std::fs::read_to_string(file)?)
+    }
+
+    /// Compares the outputs with expected outputs. The stacks are always compared, while the
+    /// overflow addresses, memory and program hash are only compared when both outputs have
+    /// them, so the outputs of the simulator can be checked against those of the VM.
+    ///
+    /// # Returns
+    ///
+    /// An error describing the first difference, if any.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_masm::{MidenProgram, Outputs};
+    ///
+    /// let mut program = MidenProgram::parse("begin push.1 push.2 add push.0 mem_store end").unwrap();
+    ///
+    /// let simulated = program.get_outputs();
+    /// let executed = program.execute_vm().unwrap().outputs();
+    /// simulated.compare(&executed).unwrap();
+    ///
+    /// let expected = Outputs::from_json(r#"{ "stack": ["1"], "overflow_addrs": [] }"#).unwrap();
+    /// assert!(simulated.compare(&expected).is_err());
+    /// ```
+    pub fn compare(&self, expected: &Outputs) -> Result<(), String> {
+        if self.stack != expected.stack {
+            return Err(format!(
+                "expected stack {:?}, got {:?}",
+                expected.stack, self.stack
+            ));
+        }
+        if !self.overflow_addrs.is_empty()
+            && !expected.overflow_addrs.is_empty()
+            && self.overflow_addrs != expected.overflow_addrs
+        {
+            return Err(format!(
+                "expected overflow addresses {:?}, got {:?}",
+                expected.overflow_addrs, self.overflow_addrs
+            ));
+        }
+        if let (Some(memory), Some(expected_memory)) = (&self.memory, &expected.memory) {
+            if memory != expected_memory {
+                return Err(format!(
+                    "expected memory {expected_memory:?}, got {memory:?}"
+                ));
+            }
+        }
+        if let (Some(hash), Some(expected_hash)) = (&self.program_hash, &expected.program_hash) {
+            if hash != expected_hash {
+                return Err(format!("expected program hash {expected_hash}, got {hash}"));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl VmOutput {
+    /// Returns the outputs of the execution: the stack, the overflow addresses and the program
+    /// hash.
+    pub fn outputs(&self) -> Outputs {
+        Outputs {
+            stack: self.stack().to_vec(),
+            overflow_addrs: self.overflow_addrs().to_vec(),
+            memory: None,
+            program_hash: Some(self.program_hash.to_string()),
+        }
+    }
+}
+
+impl MidenProgram {
+    /// Returns the outputs of the simulator: the stack and the written words of memory.
+    pub fn get_outputs(&self) -> Outputs {
+        Outputs {
+            stack: self.stack.iter().map(|n| n.as_int()).collect(),
+            overflow_addrs: Vec::new(),
+            memory: Some(
+                self.ram_memory
+                    .iter()
+                    .map(|(address, word)| (*address, word.map(|n| n.as_int())))
+                    .collect(),
+            ),
+            program_hash: None,
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{parse, sanitize, tokenize, Inputs, MidenProgram};
+use crate::{parse, sanitize, tokenize, Inputs, MidenProgram, MidenProgramError};
 
 impl MidenProgram {
     /// Parses a Miden assembly string into a MidenProgram.
@@ -19,14 +19,7 @@ impl MidenProgram {
     /// ```
     pub fn parse(masm: &str) -> Result<Self, String> {
         let mut program = MidenProgram::new();
-        let sanitized = sanitize(masm);
-        let tokens = tokenize(&sanitized);
-
-        let (mut instructions, procedures) = parse(tokens)?;
-
-        program.add_procs(procedures);
-
-        program.add_instructions(&mut instructions);
+        program.add_masm(masm)?;
 
         Ok(program)
     }
@@ -49,15 +42,8 @@ impl MidenProgram {
     /// let program = MidenProgram::parse_with_inputs("push.0\npush.1\nadd", Inputs::default()).unwrap();
     /// ```
     pub fn parse_with_inputs(masm: &str, inputs: Inputs) -> Result<Self, String> {
-        let mut program = MidenProgram::new().with_inputs(inputs);
-        let sanitized = sanitize(masm);
-        let tokens = tokenize(&sanitized);
-
-        let (mut instructions, procedures) = parse(tokens)?;
-
-        program.add_procs(procedures);
-
-        program.add_instructions(&mut instructions);
+        let mut program = MidenProgram::new().with_inputs(inputs)?;
+        program.add_masm(masm)?;
 
         Ok(program)
     }
@@ -87,14 +73,7 @@ impl MidenProgram {
 
         match file {
             Ok(masm) => {
-                let sanitized = sanitize(&masm);
-                let tokens = tokenize(&sanitized);
-
-                let (mut instructions, procedures) = parse(tokens)?;
-
-                program.add_procs(procedures);
-
-                program.add_instructions(&mut instructions);
+                program.add_masm(&masm)?;
 
                 Ok(program)
             }
@@ -121,23 +100,66 @@ impl MidenProgram {
     /// }
     /// ```
     pub fn parse_from_file_with_inputs(file: &str, inputs: Inputs) -> Result<Self, String> {
-        let mut program = MidenProgram::new().with_inputs(inputs);
+        let mut program = MidenProgram::new().with_inputs(inputs)?;
         let file = std::fs::read_to_string(file);
 
         match file {
             Ok(masm) => {
-                let sanitized = sanitize(&masm);
-                let tokens = tokenize(&sanitized);
-
-                let (mut instructions, procedures) = parse(tokens)?;
-
-                program.add_procs(procedures);
-
-                program.add_instructions(&mut instructions);
+                program.add_masm(&masm)?;
 
                 Ok(program)
             }
             Err(e) => Err(e.to_string()),
         }
     }
+
+    /// Parses Miden assembly and appends it to the program, simulating the new instructions on
+    /// the current state. The assembly can be a single instruction, several instructions, whole
+    /// blocks or procedure definitions, with or without a `begin ... end` block.
+    ///
+    /// # Arguments
+    ///
+    /// * `masm` - A string containing Miden assembly code.
+    ///
+    /// # Returns
+    ///
+    /// An error message if the assembly cannot be parsed or defines a procedure that already
+    /// exists, in which case the program is left unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_masm::MidenProgram;
+    ///
+    /// let mut program = MidenProgram::new();
+    /// program.add_masm("proc.double\n dup add\nend").unwrap();
+    /// program.add_masm("push.3 exec.double").unwrap();
+    /// assert_eq!(program.get_stack()[0], 6u64.into());
+    /// ```
+    pub fn add_masm(&mut self, masm: &str) -> Result<(), String> {
+        let sanitized = sanitize(masm);
+        let tokens = tokenize(&sanitized);
+
+        let mut parsed = parse(tokens)?;
+
+        if let Some(procedure) = parsed
+            .procedures
+            .iter()
+            .find(|procedure| self.internal_programs.contains_key(&procedure.name))
+        {
+            return Err(MidenProgramError::DuplicateProcName(procedure.name.clone()).to_string());
+        }
+
+        for path in parsed.imports.iter() {
+            self.add_import(path);
+        }
+
+        self.add_procs(parsed.procedures);
+        self.comments.append(&mut parsed.comments);
+        self.trailing_comments.append(&mut parsed.trailing_comments);
+
+        self.add_instructions(&mut parsed.instructions);
+
+        Ok(())
+    }
 }
@@ -1,12 +1,18 @@
-use crate::{Instruction, MidenProgram, Program};
+use crate::{DebugOptions, Instruction, MidenProgram, Program};
+
+use super::execute::{load_word, top_word};
 use miden::math::{Felt, FieldElement};
+use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Proc {
     pub name: String,
     pub instructions: VecDeque<Instruction>,
+    /// Line and doc comments written directly above the procedure.
+    pub comments: Vec<Instruction>,
     loc_count: u16,
+    num_locals: Option<u16>,
 }
 
 impl Proc {
@@ -30,9 +36,43 @@ impl Proc {
         Self {
             name: name.to_string(),
             instructions: VecDeque::new(),
+            comments: Vec::new(),
             loc_count: 0,
+            num_locals: None,
         }
     }
+
+    /// Declares the number of locals of the procedure, like the `N` of `proc.name.N`.
+    ///
+    /// Without a declaration, the procedure gets as many locals as needed by the highest index
+    /// used by its `loc_*` instructions.
+    ///
+    /// The locals of each invocation start at zero, so a `loc_load` of a local the invocation did
+    /// not write reads zero. The Miden VM does not clear locals: there such a read returns
+    /// whatever an earlier procedure left at the same address, which well-formed programs do not
+    /// rely on. The simulator and the VM agree on every program that writes its locals before
+    /// reading them.
+    ///
+    /// # Arguments
+    ///
+    /// * `num_locals` - The number of words of local memory of each invocation.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_masm::Proc;
+    /// let proc = Proc::new("my_proc").with_locals(2);
+    /// assert_eq!(proc.get_num_locals(), 2);
+    /// ```
+    pub fn with_locals(mut self, num_locals: u16) -> Self {
+        self.num_locals = Some(num_locals);
+        self
+    }
+
+    /// Returns the number of locals each invocation of the procedure gets.
+    pub fn get_num_locals(&self) -> u16 {
+        self.num_locals.unwrap_or(self.loc_count)
+    }
     /// Converts the procedure into a Miden assembly string.
     ///
     /// # Returns
@@ -48,9 +88,12 @@ impl Proc {
     /// ```
     pub fn get_masm(&self) -> String {
         let mut masm: String = String::new();
+        for comment in self.comments.iter() {
+            masm.push_str(&format!("{}\n", comment));
+        }
         masm.push_str(&format!("proc.{}", self.name));
-        if self.loc_count > 0 {
-            masm.push_str(&format!(".{}", self.loc_count));
+        if self.get_num_locals() > 0 {
+            masm.push_str(&format!(".{}", self.get_num_locals()));
         }
         masm.push_str(&format!("\n"));
 
@@ -85,7 +128,16 @@ impl Proc {
                     masm.push_str(&format!("{}{}\n\n", tabs, op));
                 }
 
-                Instruction::PRINT(_) => {}
+                Instruction::InlineComment(_) => {
+                    // appended to the line of the instruction before
+                    let line = masm.trim_end_matches('\n').len();
+                    let newlines = masm.split_off(line);
+                    masm.push_str(&format!(" {}{}", op, newlines));
+                }
+                Instruction::PRINT(message) => {
+                    let tabs = "\t".repeat(scope);
+                    masm.push_str(&format!("{}# {}\n{}{}\n", tabs, message, tabs, op));
+                }
                 _ => {
                     let tabs = "\t".repeat(scope);
                     masm.push_str(&format!("{}{}\n", tabs, op));
@@ -285,6 +337,7 @@ impl Proc {
         while let Some(operand) = block.pop_front() {
             match program.is_valid_operand(&operand) {
                 Some(error) => {
+                    program.emit_error(&error);
                     if let Some(op) = self.instructions.get_mut(index) {
                         match op {
                             Instruction::Error(_) | Instruction::CommentedOut(_) => {}
@@ -450,72 +503,38 @@ impl Proc {
     }
 
     pub(crate) fn execute(&mut self, program: &mut MidenProgram) {
+        program.push_loc_frame(self.get_num_locals() as usize);
         self.execute_block(program, &mut self.instructions.clone(), 0);
+        program.pop_loc_frame(&self.name);
     }
 
     pub(crate) fn execute_operand(&mut self, program: &mut MidenProgram, operand: &Instruction) {
         match operand {
             Instruction::LocLoad(key) => {
-                if *key >= self.loc_count {
-                    self.loc_count = key + 1;
-                }
-                if let Some([_, _, _, a]) = program.loc_memory.get(&key) {
-                    program.stack.push_front(*a);
-                } else {
-                    program.stack.push_front(Felt::ZERO);
+                if let Some(word) = program.current_local(*key) {
+                    let value = word[0];
+                    program.stack.push_front(value);
                 }
             }
 
             Instruction::LocLoadW(key) => {
-                if *key >= self.loc_count {
-                    self.loc_count = key + 1;
-                }
-                if let (Some(_), Some(_), Some(_), Some(_)) = (
-                    program.stack.pop_front(),
-                    program.stack.pop_front(),
-                    program.stack.pop_front(),
-                    program.stack.pop_front(),
-                ) {
-                    if let Some([a, b, c, d]) = program.loc_memory.get(&key) {
-                        program.stack.push_front(*d);
-                        program.stack.push_front(*c);
-                        program.stack.push_front(*b);
-                        program.stack.push_front(*a);
-                    } else {
-                        program.stack.push_front(Felt::ZERO);
-                        program.stack.push_front(Felt::ZERO);
-                        program.stack.push_front(Felt::ZERO);
-                        program.stack.push_front(Felt::ZERO);
-                    }
+                if let Some(word) = program.current_local(*key).copied() {
+                    load_word(&mut program.stack, word);
                 }
             }
 
             Instruction::LocStore(key) => {
-                if *key >= self.loc_count {
-                    self.loc_count = key + 1;
-                }
                 if let Some(a) = program.stack.pop_front() {
-                    program
-                        .loc_memory
-                        .insert(*key, [Felt::ZERO, Felt::ZERO, Felt::ZERO, a]);
+                    if let Some(word) = program.current_local(*key) {
+                        word[0] = a;
+                    }
                 }
             }
 
             Instruction::LocStoreW(key) => {
-                if *key >= self.loc_count {
-                    self.loc_count = key + 1;
-                }
-                if let (Some(a), Some(b), Some(c), Some(d)) = (
-                    program.stack.pop_front(),
-                    program.stack.pop_front(),
-                    program.stack.pop_front(),
-                    program.stack.pop_front(),
-                ) {
-                    program.loc_memory.insert(*key, [a, b, c, d]);
-                    program.stack.push_front(d);
-                    program.stack.push_front(c);
-                    program.stack.push_front(b);
-                    program.stack.push_front(a);
+                let value = top_word(&program.stack);
+                if let Some(word) = program.current_local(*key) {
+                    *word = value;
                 }
             }
             _ => {
@@ -533,6 +552,15 @@ impl Proc {
         self.add_instruction(Instruction::PRINT(message.to_string()));
     }
 
+    /// Pushes a `debug` decorator that prints the selected state of the program.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The state to print.
+    pub fn debug(&mut self, options: DebugOptions) {
+        self.add_instruction(Instruction::Debug(options));
+    }
+
     /// Pushes `Drop` instruction onto the stack.
     pub fn drop(&mut self) {
         self.add_instruction(Instruction::Drop);
@@ -585,6 +613,11 @@ impl Proc {
         self.add_instruction(Instruction::PadW);
     }
 
+    /// Pushes `SDepth` instruction onto the stack.
+    pub fn sdepth(&mut self) {
+        self.add_instruction(Instruction::SDepth);
+    }
+
     /// Pushes `MovUp` instruction with value `n` onto the stack.
     ///
     /// # Arguments
@@ -897,6 +930,16 @@ impl Proc {
         self.add_instruction(Instruction::Exec(name.to_string()));
     }
 
+    /// Pushes `Syscall` instruction onto the stack. Kernel procedures are not simulated, the
+    /// call only takes effect when the program runs on the VM.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the kernel procedure.
+    pub fn syscall(&mut self, name: &str) {
+        self.add_instruction(Instruction::Syscall(name.to_string()));
+    }
+
     /// Pushes `Increment` instruction onto the stack.
     pub fn increment(&mut self) {
         self.add_instruction(Instruction::Increment);
@@ -0,0 +1,170 @@
+use miden::{crypto::RpoDigest, math::StarkField};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    error::VmError,
+    prove::{verify_with_kernel, ExecutionProof, ProveResult},
+    Digest, Kernel, StackInputs, StackOutputs,
+};
+
+/// The version written to new proof files. Files with another version are rejected.
+pub const PROOF_FILE_VERSION: u32 = 1;
+
+#[derive(Deserialize)]
+struct Version {
+    version: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ProofFileJson {
+    version: u32,
+    program_hash: String,
+    #[serde(default)]
+    kernel: Vec<String>,
+    stack_inputs: Vec<u64>,
+    stack_outputs: Vec<u64>,
+    overflow_addrs: Vec<u64>,
+    proof: String,
+}
+
+/// A proof together with the public inputs and outputs needed to verify it.
+///
+/// Proof files are JSON documents with a `version` field, the program hash, the procedure hashes
+/// of the kernel, the stack inputs in the order of an inputs file, the stack outputs and the hex
+/// encoded proof.
+#[derive(Debug, Clone)]
+pub struct ProofFile {
+    pub program_hash: Digest,
+    pub kernel: Kernel,
+    pub stack_inputs: StackInputs,
+    pub stack_outputs: StackOutputs,
+    pub proof: ExecutionProof,
+}
+
+impl ProofFile {
+    /// Creates a proof file from the result of proving a program.
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - The result of proving the program.
+    /// * `stack_inputs` - The stack inputs the program was proved with.
+    pub fn new(result: &ProveResult, stack_inputs: StackInputs) -> Self {
+        ProofFile {
+            program_hash: result.program_hash,
+            kernel: result.kernel.clone(),
+            stack_inputs,
+            stack_outputs: result.stack_outputs.clone(),
+            proof: result.proof.clone(),
+        }
+    }
+
+    /// Verifies the proof against the public inputs and outputs of the file.
+    ///
+    /// # Returns
+    ///
+    /// The security level of the proof in bits, or the error returned by the verifier.
+    pub fn verify(&self) -> Result<u32, VmError> {
+        verify_with_kernel(
+            self.program_hash,
+            self.kernel.clone(),
+            self.stack_inputs.clone(),
+            self.stack_outputs.clone(),
+            self.proof.clone(),
+        )
+    }
+
+    /// Serializes the proof file to JSON.
+    pub fn to_json(&self) -> String {
+        let json = ProofFileJson {
+            version: PROOF_FILE_VERSION,
+            program_hash: self.program_hash.to_string(),
+            kernel: self
+                .kernel
+                .proc_hashes()
+                .iter()
+                .map(|hash| hash.to_string())
+                .collect(),
+            // stack inputs are stored in stack order, the file uses the order of an inputs file
+            stack_inputs: self
+                .stack_inputs
+                .values()
+                .iter()
+                .rev()
+                .map(|n| n.as_int())
+                .collect(),
+            stack_outputs: self.stack_outputs.stack().to_vec(),
+            overflow_addrs: self.stack_outputs.overflow_addrs().to_vec(),
+            proof: hex::encode(self.proof.to_bytes()),
+        };
+        serde_json::to_string_pretty(&json).unwrap()
+    }
+
+    /// Deserializes a proof file from JSON.
+    ///
+    /// # Arguments
+    ///
+    /// * `json` - The JSON written by [`ProofFile::to_json`].
+    ///
+    /// # Returns
+    ///
+    /// The proof file, or an error if the JSON is malformed or has an unsupported version.
+    pub fn from_json(json: &str) -> Result<Self, VmError> {
+        let invalid = |e: &dyn std::fmt::Display| VmError::InvalidProofFile(e.to_string());
+
+        let version: Version = serde_json::from_str(json).map_err(|e| invalid(&e))?;
+        if version.version != PROOF_FILE_VERSION {
+            return Err(VmError::InvalidProofFile(format!(
+                "unsupported version {}, expected {}",
+                version.version, PROOF_FILE_VERSION
+            )));
+        }
+
+        let json: ProofFileJson = serde_json::from_str(json).map_err(|e| invalid(&e))?;
+
+        let program_hash = RpoDigest::try_from(json.program_hash.as_str())
+            .map_err(|e| invalid(&format!("{e:?}")))?;
+        let kernel = json
+            .kernel
+            .iter()
+            .map(|hash| RpoDigest::try_from(hash.as_str()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| invalid(&format!("{e:?}")))?;
+        let stack_inputs =
+            StackInputs::try_from_values(json.stack_inputs).map_err(|e| invalid(&e))?;
+        let stack_outputs =
+            StackOutputs::new(json.stack_outputs, json.overflow_addrs).map_err(|e| invalid(&e))?;
+        let proof_bytes = hex::decode(&json.proof).map_err(|e| invalid(&e))?;
+        let proof = ExecutionProof::from_bytes(&proof_bytes).map_err(|e| invalid(&e))?;
+
+        Ok(ProofFile {
+            program_hash,
+            kernel: Kernel::new(&kernel),
+            stack_inputs,
+            stack_outputs,
+            proof,
+        })
+    }
+
+    /// Writes the proof file to disk.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The path of the file to write.
+    pub fn write(&self, file: &str) -> Result<(), VmError> {
+        std::fs::write(file, self.to_json())?;
+        Ok(())
+    }
+
+    /// Reads a proof file from disk.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The path of the file to read.
+    ///
+    /// # Returns
+    ///
+    /// The proof file, or an error if the file cannot be read or is not a valid proof file.
+    pub fn read(file: &str) -> Result<Self, VmError> {
+        Self::from_json(&std::fs::read_to_string(file)?)
+    }
+}
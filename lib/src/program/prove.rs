@@ -0,0 +1,280 @@
+use std::time::{Duration, Instant};
+
+use miden::{DefaultHost, MemAdviceProvider, ProgramInfo};
+use miden_vm::{prove, FieldExtension, HashFunction, ProvingOptions};
+
+pub use miden_vm::ExecutionProof;
+
+use super::{error::VmError, Digest, Kernel, MidenProgram, StackInputs, StackOutputs};
+
+/// The conjectured security level of a proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SecurityLevel {
+    Bits96,
+    Bits128,
+}
+
+/// The hash function used to build a proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProofHash {
+    /// BLAKE3, fast to prove and verify natively.
+    Blake3,
+    /// RPO, slower to prove but well suited for recursive verification inside the VM.
+    Rpo,
+}
+
+/// Options for [`crate::MidenProgram::prove_with_options`].
+///
+/// The number of queries and the blowup factor default to the values of the miden-vm preset
+/// for the selected security level. Lowering them produces faster proofs with a lower security
+/// level, which is useful for tests.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProveOptions {
+    pub security: SecurityLevel,
+    pub hash: ProofHash,
+    pub num_queries: Option<usize>,
+    pub blowup_factor: Option<usize>,
+}
+
+impl Default for ProveOptions {
+    fn default() -> Self {
+        ProveOptions {
+            security: SecurityLevel::Bits96,
+            hash: ProofHash::Blake3,
+            num_queries: None,
+            blowup_factor: None,
+        }
+    }
+}
+
+impl ProveOptions {
+    /// Converts the options into the `ProvingOptions` of the Miden VM.
+    ///
+    /// # Returns
+    ///
+    /// The proving options, or an error if the number of queries is not between 1 and 255 or
+    /// the blowup factor is not a power of 2 between 8 and 128, the range accepted by the
+    /// prover.
+    pub fn to_proving_options(&self) -> Result<ProvingOptions, VmError> {
+        let (num_queries, blowup_factor, grinding_factor, field_extension) = match self.security {
+            SecurityLevel::Bits96 => (27, 8, 16, FieldExtension::Quadratic),
+            SecurityLevel::Bits128 => (27, 16, 21, FieldExtension::Cubic),
+        };
+        let (fri_folding_factor, fri_remainder_max_degree, hash_fn) =
+            match (self.hash, self.security) {
+                (ProofHash::Rpo, _) => (4, 7, HashFunction::Rpo256),
+                (ProofHash::Blake3, SecurityLevel::Bits96) => (8, 255, HashFunction::Blake3_192),
+                (ProofHash::Blake3, SecurityLevel::Bits128) => (8, 255, HashFunction::Blake3_256),
+            };
+
+        let num_queries = self.num_queries.unwrap_or(num_queries);
+        if !(1..=255).contains(&num_queries) {
+            return Err(VmError::InvalidOptions(format!(
+                "number of queries must be between 1 and 255, but was {num_queries}"
+            )));
+        }
+
+        let blowup_factor = self.blowup_factor.unwrap_or(blowup_factor);
+        if !blowup_factor.is_power_of_two() || !(8..=128).contains(&blowup_factor) {
+            return Err(VmError::InvalidOptions(format!(
+                "blowup factor must be a power of 2 between 8 and 128, but was {blowup_factor}"
+            )));
+        }
+
+        Ok(ProvingOptions::new(
+            num_queries,
+            blowup_factor,
+            grinding_factor,
+            field_extension,
+            fri_folding_factor,
+            fri_remainder_max_degree,
+            hash_fn,
+        ))
+    }
+}
+
+/// The time spent in each phase of [`crate::MidenProgram::prove_with_options`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProveTimings {
+    /// Time spent assembling the program.
+    pub assembly: Duration,
+    /// Time spent executing the program and building the STARK proof.
+    pub proving: Duration,
+}
+
+/// The result of proving a program with [`crate::MidenProgram::prove`].
+///
+/// Holds everything needed to verify the execution with [`verify`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProveResult {
+    /// The hash of the proved program.
+    pub program_hash: Digest,
+    /// The kernel the program was assembled against.
+    pub kernel: Kernel,
+    /// The stack outputs of the execution.
+    pub stack_outputs: StackOutputs,
+    /// The proof of the execution.
+    pub proof: ExecutionProof,
+    /// The time spent proving the program.
+    pub timings: ProveTimings,
+}
+
+impl ProveResult {
+    /// Verifies the proof against the stack inputs the program was proved with.
+    ///
+    /// # Arguments
+    ///
+    /// * `stack_inputs` - The stack inputs of the proved execution.
+    ///
+    /// # Returns
+    ///
+    /// The security level of the proof in bits, or the error returned by the verifier.
+    pub fn verify(&self, stack_inputs: StackInputs) -> Result<u32, VmError> {
+        verify_with_kernel(
+            self.program_hash,
+            self.kernel.clone(),
+            stack_inputs,
+            self.stack_outputs.clone(),
+            self.proof.clone(),
+        )
+    }
+}
+
+/// Verifies that a program with the given hash, run with the given stack inputs, produced the
+/// given stack outputs. Use [`verify_with_kernel`] for programs assembled against a kernel.
+///
+/// # Arguments
+///
+/// * `program_hash` - The hash of the program.
+/// * `stack_inputs` - The stack inputs of the execution.
+/// * `stack_outputs` - The stack outputs of the execution.
+/// * `proof` - The proof of the execution.
+///
+/// # Returns
+///
+/// The security level of the proof in bits, or the error returned by the verifier.
+///
+/// # Example
+///
+/// ```
+/// use rust_masm::{verify, MidenProgram};
+///
+/// let mut program = MidenProgram::new();
+/// program.push(1);
+/// program.push(2);
+/// program.add();
+///
+/// let result = program.prove().unwrap();
+/// let stack_inputs = program.get_stack_inputs().clone();
+/// assert!(verify(result.program_hash, stack_inputs, result.stack_outputs, result.proof).is_ok());
+/// ```
+pub fn verify(
+    program_hash: Digest,
+    stack_inputs: StackInputs,
+    stack_outputs: StackOutputs,
+    proof: ExecutionProof,
+) -> Result<u32, VmError> {
+    verify_with_kernel(
+        program_hash,
+        Kernel::default(),
+        stack_inputs,
+        stack_outputs,
+        proof,
+    )
+}
+
+/// Verifies that a program with the given hash, assembled against the given kernel and run with
+/// the given stack inputs, produced the given stack outputs.
+///
+/// # Arguments
+///
+/// * `program_hash` - The hash of the program.
+/// * `kernel` - The kernel the program was assembled against.
+/// * `stack_inputs` - The stack inputs of the execution.
+/// * `stack_outputs` - The stack outputs of the execution.
+/// * `proof` - The proof of the execution.
+///
+/// # Returns
+///
+/// The security level of the proof in bits, or the error returned by the verifier.
+pub fn verify_with_kernel(
+    program_hash: Digest,
+    kernel: Kernel,
+    stack_inputs: StackInputs,
+    stack_outputs: StackOutputs,
+    proof: ExecutionProof,
+) -> Result<u32, VmError> {
+    let program_info = ProgramInfo::new(program_hash, kernel);
+    Ok(miden_vm::verify(
+        program_info,
+        stack_inputs,
+        stack_outputs,
+        proof,
+    )?)
+}
+
+impl MidenProgram {
+    /// Proves the program with the inputs of the program and the default `ProveOptions`.
+    ///
+    /// # Returns
+    ///
+    /// A `ProveResult` with the program hash, the stack outputs and the proof, or the error
+    /// returned by the assembler or the prover.
+    pub fn prove(&mut self) -> Result<ProveResult, VmError> {
+        self.prove_with_options(&ProveOptions::default())
+    }
+
+    /// Proves the program with the inputs of the program.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The security level, hash function and proof parameters to prove with.
+    ///
+    /// # Returns
+    ///
+    /// A `ProveResult` with the program hash, the stack outputs, the proof and the time spent in
+    /// each phase, or the error returned by the assembler or the prover.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_masm::{MidenProgram, ProveOptions, SecurityLevel};
+    ///
+    /// let mut program = MidenProgram::new();
+    /// program.push(1);
+    ///
+    /// let options = ProveOptions {
+    ///     security: SecurityLevel::Bits128,
+    ///     ..ProveOptions::default()
+    /// };
+    /// let result = program.prove_with_options(&options).unwrap();
+    /// assert!(result.verify(program.get_stack_inputs().clone()).is_ok());
+    /// ```
+    pub fn prove_with_options(&mut self, options: &ProveOptions) -> Result<ProveResult, VmError> {
+        let proving_options = options.to_proving_options()?;
+
+        let start = Instant::now();
+        let compiled = self.compile()?;
+        let assembly = start.elapsed();
+
+        let advice_provider = MemAdviceProvider::from(self.advice_inputs.clone());
+        let host = DefaultHost::new(advice_provider);
+
+        let start = Instant::now();
+        let (stack_outputs, proof) = prove(
+            compiled.program(),
+            self.stack_inputs.clone(),
+            host,
+            proving_options,
+        )?;
+        let proving = start.elapsed();
+
+        Ok(ProveResult {
+            program_hash: compiled.hash(),
+            kernel: compiled.kernel().clone(),
+            stack_outputs,
+            proof,
+            timings: ProveTimings { assembly, proving },
+        })
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::VecDeque;
+
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{error::VmError, instruction::Instruction, proc::Proc, MidenProgram};
+
+/// The version written to serialized programs and procedures. Data with another version is
+/// rejected.
+pub const PROGRAM_FORMAT_VERSION: u32 = 1;
+
+/// Serializes field elements as their canonical `u64` value and rejects values that are not
+/// below the modulus, for use with `#[serde(with = "felt")]`.
+pub(crate) mod felt {
+    use miden::math::{Felt, StarkField};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Felt, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(value.as_int())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Felt, D::Error> {
+        let value = u64::deserialize(deserializer)?;
+        if value >= Felt::MODULUS {
+            return Err(D::Error::custom(format!(
+                "{value} is not a valid field element"
+            )));
+        }
+        Ok(Felt::from(value))
+    }
+}
+
+/// The code of a program: the modules it imports, its procedures in the order they were added
+/// and its main instructions.
+#[derive(Serialize, Deserialize)]
+struct ProgramCode {
+    imports: Vec<String>,
+    procs: Vec<Proc>,
+    instructions: VecDeque<Instruction>,
+}
+
+#[derive(Deserialize)]
+struct Version {
+    version: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ProgramFile<T> {
+    version: u32,
+    program: T,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ProcFile<T> {
+    version: u32,
+    proc: T,
+}
+
+fn invalid(e: impl std::fmt::Display) -> VmError {
+    VmError::InvalidProgramFile(e.to_string())
+}
+
+fn check_version(version: u32) -> Result<(), VmError> {
+    if version != PROGRAM_FORMAT_VERSION {
+        return Err(VmError::InvalidProgramFile(format!(
+            "unsupported version {version}, expected {PROGRAM_FORMAT_VERSION}"
+        )));
+    }
+    Ok(())
+}
+
+fn from_json<T: DeserializeOwned>(json: &str) -> Result<T, VmError> {
+    let version: Version = serde_json::from_str(json).map_err(invalid)?;
+    check_version(version.version)?;
+    serde_json::from_str(json).map_err(invalid)
+}
+
+fn from_bytes<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, VmError> {
+    // the version is the first field, so it can be read before the rest of the layout is known
+    let version: u32 = bincode::deserialize(bytes).map_err(invalid)?;
+    check_version(version)?;
+    bincode::deserialize(bytes).map_err(invalid)
+}
+
+/// Programs serialize to their imports, procedures and main instructions. The inputs, the
+/// libraries and the simulator state are not part of the serialized program.
+impl Serialize for MidenProgram {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let procs: Vec<Proc> = self
+            .internal_programs_order
+            .iter()
+            .map(|name| self.internal_programs[name].borrow().clone())
+            .collect();
+        ProgramCode {
+            imports: self.imports.clone(),
+            procs,
+            instructions: self.instructions.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Deserializing a program simulates its instructions on a new program.
+impl<'de> Deserialize<'de> for MidenProgram {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut code = ProgramCode::deserialize(deserializer)?;
+
+        let mut program = MidenProgram::new();
+        for path in code.imports.iter() {
+            program.add_import(path);
+        }
+        program.add_procs(code.procs);
+        program.add_instructions(&mut code.instructions);
+        Ok(program)
+    }
+}
+
+impl MidenProgram {
+    /// Serializes the program to JSON, with a `version` field.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_masm::MidenProgram;
+    ///
+    /// let program = MidenProgram::parse("proc.double dup add end begin push.2 exec.double end").unwrap();
+    /// let json = program.to_json();
+    ///
+    /// let restored = MidenProgram::from_json(&json).unwrap();
+    /// assert_eq!(restored.get_masm(), program.get_masm());
+    /// assert_eq!(restored.get_stack()[0], 4u64.into());
+    /// ```
+    pub fn to_json(&self) -> String {
+        let file = ProgramFile {
+            version: PROGRAM_FORMAT_VERSION,
+            program: self,
+        };
+        serde_json::to_string_pretty(&file).unwrap()
+    }
+
+    /// Deserializes a program from the JSON written by [`MidenProgram::to_json`] and simulates
+    /// its instructions.
+    ///
+    /// # Returns
+    ///
+    /// The program, or an error if the JSON is malformed or has an unsupported version.
+    pub fn from_json(json: &str) -> Result<Self, VmError> {
+        from_json::<ProgramFile<MidenProgram>>(json).map(|file| file.program)
+    }
+
+    /// Serializes the program to a compact binary format, with a version.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let file = ProgramFile {
+            version: PROGRAM_FORMAT_VERSION,
+            program: self,
+        };
+        bincode::serialize(&file).unwrap()
+    }
+
+    /// Deserializes a program from the bytes written by [`MidenProgram::to_bytes`] and
+    /// simulates its instructions.
+    ///
+    /// # Returns
+    ///
+    /// The program, or an error if the bytes are malformed or have an unsupported version.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, VmError> {
+        from_bytes::<ProgramFile<MidenProgram>>(bytes).map(|file| file.program)
+    }
+}
+
+impl Proc {
+    /// Serializes the procedure to JSON, with a `version` field.
+    pub fn to_json(&self) -> String {
+        let file = ProcFile {
+            version: PROGRAM_FORMAT_VERSION,
+            proc: self,
+        };
+        serde_json::to_string_pretty(&file).unwrap()
+    }
+
+    /// Deserializes a procedure from the JSON written by [`Proc::to_json`].
+    ///
+    /// # Returns
+    ///
+    /// The procedure, or an error if the JSON is malformed or has an unsupported version.
+    pub fn from_json(json: &str) -> Result<Self, VmError> {
+        from_json::<ProcFile<Proc>>(json).map(|file| file.proc)
+    }
+
+    /// Serializes the procedure to a compact binary format, with a version.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let file = ProcFile {
+            version: PROGRAM_FORMAT_VERSION,
+            proc: self,
+        };
+        bincode::serialize(&file).unwrap()
+    }
+
+    /// Deserializes a procedure from the bytes written by [`Proc::to_bytes`].
+    ///
+    /// # Returns
+    ///
+    /// The procedure, or an error if the bytes are malformed or have an unsupported version.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, VmError> {
+        from_bytes::<ProcFile<Proc>>(bytes).map(|file| file.proc)
+    }
+}
@@ -0,0 +1,541 @@
+use std::collections::VecDeque;
+
+use super::{empty::EmptyProgram, instruction::Instruction};
+use crate::Program;
+
+/// The deepest element `dup`, `swap`, `movup` and `movdn` can reach.
+pub(crate) const MAX_REACH: usize = 15;
+
+/// Compares the two words on top of the stack with `eqw`, which keeps both words, and drops them
+/// from under the result.
+pub(crate) fn eqw_and_drop() -> [Instruction; 4] {
+    [
+        Instruction::EqW,
+        Instruction::MovDn(8),
+        Instruction::DropW,
+        Instruction::DropW,
+    ]
+}
+
+/// A value on the stack of a [`StackBuilder`], either a felt or a word. Values are written as
+/// `%n`, and words as `%wn`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StackValue {
+    id: usize,
+    size: usize,
+}
+
+impl StackValue {
+    /// Returns whether the value is a word.
+    pub fn is_word(&self) -> bool {
+        self.size == 4
+    }
+}
+
+impl std::fmt::Display for StackValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_word() {
+            write!(f, "%w{}", self.id)
+        } else {
+            write!(f, "%{}", self.id)
+        }
+    }
+}
+
+/// An error returned by a [`StackBuilder`] operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StackBuilderError {
+    /// The value was consumed by an earlier operation, or is used twice by the same one.
+    Consumed(StackValue),
+    /// The value and its depth, which is out of reach of `dup` and `movup`.
+    OutOfReach(StackValue, usize),
+    ExpectedFelt(StackValue),
+    ExpectedWord(StackValue),
+    /// The body of a block does not leave values of the same sizes as it starts with.
+    UnbalancedBlock,
+}
+
+impl std::fmt::Display for StackBuilderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Consumed(value) => write!(
+                f,
+                "Value {value} was already consumed, use dup to keep a copy"
+            ),
+            Self::OutOfReach(value, depth) => write!(
+                f,
+                "Value {value} is at depth {depth}, out of reach of dup and movup"
+            ),
+            Self::ExpectedFelt(value) => write!(f, "Value {value} is a word, expected a felt"),
+            Self::ExpectedWord(value) => write!(f, "Value {value} is a felt, expected a word"),
+            Self::UnbalancedBlock => write!(
+                f,
+                "The body of a block must leave values of the same sizes as it starts with"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StackBuilderError {}
+
+/// Builds an [`EmptyProgram`] from operations on named stack values instead of stack positions.
+///
+/// Every operation takes the values it works on, brings them to the top of the stack with
+/// `swap`, `movup`, `swapw` or `movupw`, and consumes them. Use [`StackBuilder::dup`] to keep
+/// a copy of a value that is needed again. Using a consumed value is an error.
+///
+/// # Example
+///
+/// ```
+/// use rust_masm::{MidenProgram, StackBuilder};
+///
+/// let mut b = StackBuilder::new();
+/// let a = b.push(5);
+/// let c = b.push(7);
+/// let a2 = b.dup(a).unwrap();
+/// // (5 + 7) * 5, with `a` moved up from under `c`
+/// let sum = b.add(a, c).unwrap();
+/// let product = b.mul(sum, a2).unwrap();
+/// assert_eq!(b.depth_of(product), Ok(0));
+/// assert!(b.add(a, product).is_err());
+///
+/// let mut program = MidenProgram::new();
+/// program.add_program(&mut b);
+/// assert_eq!(program.get_stack()[0], 60u64.into());
+/// ```
+pub struct StackBuilder {
+    program: EmptyProgram,
+    /// The value each element of the stack belongs to, the top of the stack last. The elements
+    /// below are not tracked.
+    slots: Vec<usize>,
+    next_id: usize,
+}
+
+impl Default for StackBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StackBuilder {
+    pub fn new() -> Self {
+        Self {
+            program: EmptyProgram::new(),
+            slots: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Returns the program built so far.
+    pub fn into_program(self) -> EmptyProgram {
+        self.program
+    }
+
+    fn new_value(&mut self, size: usize) -> StackValue {
+        self.next_id += 1;
+        StackValue {
+            id: self.next_id - 1,
+            size,
+        }
+    }
+
+    fn push_value(&mut self, size: usize) -> StackValue {
+        let value = self.new_value(size);
+        for _ in 0..size {
+            self.slots.push(value.id);
+        }
+        value
+    }
+
+    /// Names the first untracked felt below the values of the builder, such as an input of the
+    /// program. Calling it repeatedly names the inputs from the top of the stack down.
+    pub fn input(&mut self) -> StackValue {
+        let value = self.new_value(1);
+        self.slots.insert(0, value.id);
+        value
+    }
+
+    /// Names the first untracked word below the values of the builder.
+    pub fn input_word(&mut self) -> StackValue {
+        let value = self.new_value(4);
+        for _ in 0..4 {
+            self.slots.insert(0, value.id);
+        }
+        value
+    }
+
+    /// Returns the depth of the top element of a value, with `0` for the top of the stack.
+    pub fn depth_of(&self, value: StackValue) -> Result<usize, StackBuilderError> {
+        self.slots
+            .iter()
+            .rposition(|id| *id == value.id)
+            .map(|index| self.slots.len() - 1 - index)
+            .ok_or(StackBuilderError::Consumed(value))
+    }
+
+    fn check_reach(&self, value: StackValue) -> Result<usize, StackBuilderError> {
+        let depth = self.depth_of(value)?;
+        if depth + value.size - 1 > MAX_REACH {
+            return Err(StackBuilderError::OutOfReach(value, depth));
+        }
+        Ok(depth)
+    }
+
+    fn check_felt(value: StackValue) -> Result<(), StackBuilderError> {
+        match value.is_word() {
+            true => Err(StackBuilderError::ExpectedFelt(value)),
+            false => Ok(()),
+        }
+    }
+
+    fn check_word(value: StackValue) -> Result<(), StackBuilderError> {
+        match value.is_word() {
+            true => Ok(()),
+            false => Err(StackBuilderError::ExpectedWord(value)),
+        }
+    }
+
+    /// Brings a value to the top of the stack.
+    fn move_up(&mut self, value: StackValue) -> Result<(), StackBuilderError> {
+        let depth = self.check_reach(value)?;
+        match (value.is_word(), depth) {
+            (_, 0) => return Ok(()),
+            (false, 1) => self.program.swap(),
+            (false, depth) => self.program.movup_n(depth),
+            (true, 4) => self.program.swapw(),
+            (true, 8) => self.program.movupw_n(2),
+            (true, 12) => self.program.movupw_n(3),
+            (true, depth) => {
+                // the deepest element first, so the word keeps its order
+                for _ in 0..4 {
+                    self.program.movup_n(depth + 3);
+                }
+            }
+        }
+
+        let index = self.slots.len() - 1 - depth;
+        self.slots.drain(index + 1 - value.size..=index);
+        for _ in 0..value.size {
+            self.slots.push(value.id);
+        }
+        Ok(())
+    }
+
+    /// Brings the values to the top of the stack, the last one on top, unless they are there
+    /// already.
+    pub fn move_to_top(&mut self, values: &[StackValue]) -> Result<(), StackBuilderError> {
+        for (i, value) in values.iter().enumerate() {
+            if values[..i].contains(value) {
+                return Err(StackBuilderError::Consumed(*value));
+            }
+            self.check_reach(*value)?;
+        }
+
+        let expected: Vec<usize> = values
+            .iter()
+            .flat_map(|value| std::iter::repeat_n(value.id, value.size))
+            .collect();
+        if self.slots.ends_with(&expected) {
+            return Ok(());
+        }
+        for value in values {
+            self.move_up(*value)?;
+        }
+        Ok(())
+    }
+
+    /// Emits an instruction that consumes the given values, which are brought to the top of the
+    /// stack first, and pushes `outputs` felts.
+    ///
+    /// # Returns
+    ///
+    /// The values pushed by the instruction, with the top of the stack first.
+    pub fn apply(
+        &mut self,
+        instruction: Instruction,
+        inputs: &[StackValue],
+        outputs: usize,
+    ) -> Result<Vec<StackValue>, StackBuilderError> {
+        self.move_to_top(inputs)?;
+        self.program.add_instruction(instruction);
+
+        let consumed: usize = inputs.iter().map(|value| value.size).sum();
+        self.slots.truncate(self.slots.len() - consumed);
+        let mut values: Vec<StackValue> = (0..outputs).map(|_| self.push_value(1)).collect();
+        values.reverse();
+        Ok(values)
+    }
+
+    fn unary(
+        &mut self,
+        instruction: Instruction,
+        a: StackValue,
+    ) -> Result<StackValue, StackBuilderError> {
+        Self::check_felt(a)?;
+        Ok(self.apply(instruction, &[a], 1)?[0])
+    }
+
+    fn binary(
+        &mut self,
+        instruction: Instruction,
+        a: StackValue,
+        b: StackValue,
+    ) -> Result<StackValue, StackBuilderError> {
+        Self::check_felt(a)?;
+        Self::check_felt(b)?;
+        Ok(self.apply(instruction, &[a, b], 1)?[0])
+    }
+
+    /// Emits a `repeat.count` block.
+    ///
+    /// The body is built by `body` with a builder of its own, which gets the values of `state`
+    /// and returns the values that take their place for the next iteration. Every other value
+    /// of the body must be consumed.
+    ///
+    /// # Returns
+    ///
+    /// The values left by the last iteration, in the order of `state`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_masm::{MidenProgram, StackBuilder};
+    ///
+    /// // 3^5
+    /// let mut b = StackBuilder::new();
+    /// let base = b.push(3);
+    /// let one = b.push(1);
+    /// let result = b
+    ///     .repeat(5, &[base, one], |b, state| {
+    ///         let base = b.dup(state[0])?;
+    ///         let product = b.mul(state[1], base)?;
+    ///         Ok(vec![state[0], product])
+    ///     })
+    ///     .unwrap();
+    /// b.drop(result[0]).unwrap();
+    ///
+    /// let mut program = MidenProgram::new();
+    /// program.add_program(&mut b);
+    /// assert_eq!(program.get_stack()[0], 243u64.into());
+    /// ```
+    pub fn repeat<F>(
+        &mut self,
+        count: usize,
+        state: &[StackValue],
+        body: F,
+    ) -> Result<Vec<StackValue>, StackBuilderError>
+    where
+        F: FnOnce(&mut StackBuilder, Vec<StackValue>) -> Result<Vec<StackValue>, StackBuilderError>,
+    {
+        self.move_to_top(state)?;
+
+        // the ids continue from this builder, so values of this builder are not found in the body
+        let mut inner = StackBuilder {
+            program: EmptyProgram::new(),
+            slots: Vec::new(),
+            next_id: self.next_id,
+        };
+        let mut inputs: Vec<StackValue> = state
+            .iter()
+            .rev()
+            .map(|value| match value.is_word() {
+                true => inner.input_word(),
+                false => inner.input(),
+            })
+            .collect();
+        inputs.reverse();
+
+        let outputs = body(&mut inner, inputs)?;
+        let size =
+            |values: &[StackValue]| values.iter().map(|value| value.size).collect::<Vec<_>>();
+        if size(&outputs) != size(state) {
+            return Err(StackBuilderError::UnbalancedBlock);
+        }
+        inner.move_to_top(&outputs)?;
+        if inner.slots.len() != size(state).iter().sum::<usize>() {
+            return Err(StackBuilderError::UnbalancedBlock);
+        }
+
+        self.next_id = inner.next_id;
+        self.program.repeat(count, &mut inner.program);
+        self.slots.truncate(self.slots.len() - inner.slots.len());
+        Ok(state
+            .iter()
+            .map(|value| self.push_value(value.size))
+            .collect())
+    }
+
+    /// Pushes a felt.
+    pub fn push(&mut self, value: u64) -> StackValue {
+        self.program.push(value);
+        self.push_value(1)
+    }
+
+    /// Pushes a word, in memory order, so the first element ends up deepest.
+    pub fn push_word(&mut self, word: [u64; 4]) -> StackValue {
+        for value in word {
+            self.program.push(value);
+        }
+        self.push_value(4)
+    }
+
+    /// Pushes a copy of a value with `dup` or `dupw`, leaving the value in place.
+    pub fn dup(&mut self, value: StackValue) -> Result<StackValue, StackBuilderError> {
+        let depth = self.check_reach(value)?;
+        match (value.is_word(), depth) {
+            (false, depth) => self.program.dup_n(depth),
+            (true, depth @ (4 | 8 | 12)) => {
+                self.program.add_instruction(Instruction::DupW(depth / 4))
+            }
+            (true, depth) => {
+                for _ in 0..4 {
+                    self.program.dup_n(depth + 3);
+                }
+            }
+        }
+        Ok(self.push_value(value.size))
+    }
+
+    /// Drops a value with `drop` or `dropw`.
+    pub fn drop(&mut self, value: StackValue) -> Result<(), StackBuilderError> {
+        let instruction = match value.is_word() {
+            true => Instruction::DropW,
+            false => Instruction::Drop,
+        };
+        self.apply(instruction, &[value], 0)?;
+        Ok(())
+    }
+
+    /// `a + b`
+    pub fn add(&mut self, a: StackValue, b: StackValue) -> Result<StackValue, StackBuilderError> {
+        self.binary(Instruction::Add, a, b)
+    }
+
+    /// `a - b`
+    pub fn sub(&mut self, a: StackValue, b: StackValue) -> Result<StackValue, StackBuilderError> {
+        self.binary(Instruction::Sub, a, b)
+    }
+
+    /// `a * b`
+    pub fn mul(&mut self, a: StackValue, b: StackValue) -> Result<StackValue, StackBuilderError> {
+        self.binary(Instruction::Mul, a, b)
+    }
+
+    /// `a / b`
+    pub fn div(&mut self, a: StackValue, b: StackValue) -> Result<StackValue, StackBuilderError> {
+        self.binary(Instruction::Div, a, b)
+    }
+
+    /// `a == b`
+    pub fn eq(&mut self, a: StackValue, b: StackValue) -> Result<StackValue, StackBuilderError> {
+        self.binary(Instruction::Eq, a, b)
+    }
+
+    /// `a != b`
+    pub fn neq(&mut self, a: StackValue, b: StackValue) -> Result<StackValue, StackBuilderError> {
+        self.binary(Instruction::Neq, a, b)
+    }
+
+    /// `a < b`
+    pub fn lt(&mut self, a: StackValue, b: StackValue) -> Result<StackValue, StackBuilderError> {
+        self.binary(Instruction::Lt, a, b)
+    }
+
+    /// `a <= b`
+    pub fn lte(&mut self, a: StackValue, b: StackValue) -> Result<StackValue, StackBuilderError> {
+        self.binary(Instruction::Lte, a, b)
+    }
+
+    /// `a > b`
+    pub fn gt(&mut self, a: StackValue, b: StackValue) -> Result<StackValue, StackBuilderError> {
+        self.binary(Instruction::Gt, a, b)
+    }
+
+    /// `a >= b`
+    pub fn gte(&mut self, a: StackValue, b: StackValue) -> Result<StackValue, StackBuilderError> {
+        self.binary(Instruction::Gte, a, b)
+    }
+
+    /// `a && b`, for binary values.
+    pub fn and(&mut self, a: StackValue, b: StackValue) -> Result<StackValue, StackBuilderError> {
+        self.binary(Instruction::And, a, b)
+    }
+
+    /// `a || b`, for binary values.
+    pub fn or(&mut self, a: StackValue, b: StackValue) -> Result<StackValue, StackBuilderError> {
+        self.binary(Instruction::Or, a, b)
+    }
+
+    /// `a ^ b`, for binary values.
+    pub fn xor(&mut self, a: StackValue, b: StackValue) -> Result<StackValue, StackBuilderError> {
+        self.binary(Instruction::Xor, a, b)
+    }
+
+    /// `-a`
+    pub fn neg(&mut self, a: StackValue) -> Result<StackValue, StackBuilderError> {
+        self.unary(Instruction::Neg, a)
+    }
+
+    /// `1 / a`
+    pub fn inv(&mut self, a: StackValue) -> Result<StackValue, StackBuilderError> {
+        self.unary(Instruction::Inv, a)
+    }
+
+    /// `!a`, for a binary value.
+    pub fn not(&mut self, a: StackValue) -> Result<StackValue, StackBuilderError> {
+        self.unary(Instruction::Not, a)
+    }
+
+    /// Compares two words with `eqw`, and drops them.
+    pub fn eqw(&mut self, a: StackValue, b: StackValue) -> Result<StackValue, StackBuilderError> {
+        Self::check_word(a)?;
+        Self::check_word(b)?;
+        self.move_to_top(&[a, b])?;
+        for instruction in eqw_and_drop() {
+            self.program.add_instruction(instruction);
+        }
+        self.slots.truncate(self.slots.len() - 8);
+        Ok(self.push_value(1))
+    }
+
+    /// Loads a felt from memory.
+    pub fn mem_load(&mut self, address: u32) -> StackValue {
+        self.program.mem_load_n(address);
+        self.push_value(1)
+    }
+
+    /// Stores a felt to memory.
+    pub fn mem_store(&mut self, address: u32, value: StackValue) -> Result<(), StackBuilderError> {
+        Self::check_felt(value)?;
+        self.apply(Instruction::MemStoreImm(address), &[value], 0)?;
+        Ok(())
+    }
+
+    /// Loads a word from memory.
+    pub fn mem_load_w(&mut self, address: u32) -> StackValue {
+        self.program.padw();
+        self.program.mem_load_w_n(address);
+        self.push_value(4)
+    }
+
+    /// Stores a word to memory.
+    pub fn mem_store_w(
+        &mut self,
+        address: u32,
+        value: StackValue,
+    ) -> Result<(), StackBuilderError> {
+        Self::check_word(value)?;
+        self.move_to_top(&[value])?;
+        // mem_storew keeps the word on the stack
+        self.program.mem_store_w_n(address);
+        self.program.add_instruction(Instruction::DropW);
+        self.slots.truncate(self.slots.len() - 4);
+        Ok(())
+    }
+}
+
+impl Program for StackBuilder {
+    fn get_instructions(&self) -> VecDeque<Instruction> {
+        self.program.get_instructions()
+    }
+}
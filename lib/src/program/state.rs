@@ -0,0 +1,299 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use miden::{
+    crypto::{MerkleStore, RpoDigest},
+    math::{Felt, StarkField},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{parse, tokenize};
+
+use super::{error::VmError, execute::key_to_hex, instruction::Instruction, MidenProgram};
+
+/// The version written to new state files. Files with another version are rejected.
+pub const STATE_FILE_VERSION: u32 = 1;
+
+#[derive(Deserialize)]
+struct Version {
+    version: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StateFileJson {
+    version: u32,
+    stack: Vec<u64>,
+    #[serde(default)]
+    ram_memory: BTreeMap<u32, [u64; 4]>,
+    #[serde(default)]
+    advice_stack: Vec<u64>,
+    #[serde(default)]
+    advice_map: BTreeMap<String, Vec<u64>>,
+    #[serde(default)]
+    merkle_store: Vec<MerkleNode>,
+}
+
+/// An inner node of the Merkle store, with its hash and the hashes of its children.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleNode {
+    pub value: String,
+    pub left: String,
+    pub right: String,
+}
+
+/// A snapshot of the simulator state of a program.
+///
+/// State files are JSON documents with a `version` field, the operand stack with its top first,
+/// the written words of RAM by address, the advice stack with its top first, the advice map and
+/// the inner nodes of the Merkle store. Hashes are written as hex strings. Locals are not part
+/// of the state: a snapshot is taken between instructions of the `begin` block, when no procedure
+/// is executing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProgramState {
+    pub stack: Vec<u64>,
+    pub ram_memory: BTreeMap<u32, [u64; 4]>,
+    pub advice_stack: Vec<u64>,
+    pub advice_map: BTreeMap<String, Vec<u64>>,
+    pub merkle_store: Vec<MerkleNode>,
+}
+
+impl ProgramState {
+    /// Serializes the state to JSON.
+    pub fn to_json(&self) -> String {
+        let json = StateFileJson {
+            version: STATE_FILE_VERSION,
+            stack: self.stack.clone(),
+            ram_memory: self.ram_memory.clone(),
+            advice_stack: self.advice_stack.clone(),
+            advice_map: self.advice_map.clone(),
+            merkle_store: self.merkle_store.clone(),
+        };
+        serde_json::to_string_pretty(&json).unwrap()
+    }
+
+    /// Deserializes a state from JSON.
+    ///
+    /// # Arguments
+    ///
+    /// * `json` - The JSON written by [`ProgramState::to_json`].
+    ///
+    /// # Returns
+    ///
+    /// The state, or an error if the JSON is malformed or has an unsupported version.
+    pub fn from_json(json: &str) -> Result<Self, VmError> {
+        let invalid = |e: &dyn std::fmt::Display| VmError::InvalidStateFile(e.to_string());
+
+        let version: Version = serde_json::from_str(json).map_err(|e| invalid(&e))?;
+        if version.version != STATE_FILE_VERSION {
+            return Err(VmError::InvalidStateFile(format!(
+                "unsupported version {}, expected {}",
+                version.version, STATE_FILE_VERSION
+            )));
+        }
+
+        let json: StateFileJson = serde_json::from_str(json).map_err(|e| invalid(&e))?;
+        Ok(ProgramState {
+            stack: json.stack,
+            ram_memory: json.ram_memory,
+            advice_stack: json.advice_stack,
+            advice_map: json.advice_map,
+            merkle_store: json.merkle_store,
+        })
+    }
+
+    /// Writes the state file to disk.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The path of the file to write.
+    pub fn write(&self, file: &str) -> Result<(), VmError> {
+        std::fs::write(file, self.to_json())?;
+        Ok(())
+    }
+
+    /// Reads a state file from disk.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The path of the file to read.
+    ///
+    /// # Returns
+    ///
+    /// The state, or an error if the file cannot be read or is not a valid state file.
+    pub fn read(file: &str) -> Result<Self, VmError> {
+        Self::from_json(&std::fs::read_to_string(file)?)
+    }
+}
+
+fn to_felt(value: u64) -> Result<Felt, VmError> {
+    if value >= Felt::MODULUS {
+        return Err(VmError::InvalidStateFile(format!(
+            "{value} is not a valid field element"
+        )));
+    }
+    Ok(Felt::from(value))
+}
+
+fn to_digest(hash: &str) -> Result<RpoDigest, VmError> {
+    RpoDigest::try_from(hash)
+        .map_err(|e| VmError::InvalidStateFile(format!("invalid hash {hash}: {e:?}")))
+}
+
+impl MidenProgram {
+    /// Returns a snapshot of the simulator state: the operand stack, RAM, the advice stack, the
+    /// advice map and the Merkle store.
+    pub fn get_state(&self) -> ProgramState {
+        ProgramState {
+            stack: self.stack.iter().map(|n| n.as_int()).collect(),
+            ram_memory: self
+                .ram_memory
+                .iter()
+                .map(|(address, word)| (*address, word.map(|n| n.as_int())))
+                .collect(),
+            advice_stack: self.advice_stack.iter().copied().collect(),
+            advice_map: self
+                .advice_map
+                .iter()
+                .map(|(key, values)| (key_to_hex(key), values.clone()))
+                .collect(),
+            merkle_store: self
+                .merkle_store
+                .iter()
+                .flat_map(|store| store.inner_nodes())
+                .map(|node| MerkleNode {
+                    value: node.value.to_string(),
+                    left: node.left.to_string(),
+                    right: node.right.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Restores a snapshot of the simulator state taken with [`MidenProgram::get_state`].
+    ///
+    /// The instructions of the program and the inputs it runs with on the Miden VM are left
+    /// untouched.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The state to restore.
+    ///
+    /// # Returns
+    ///
+    /// An error if the state holds a value that is not a field element or an invalid hash, in
+    /// which case the program is left unchanged.
+    pub fn set_state(&mut self, state: &ProgramState) -> Result<(), VmError> {
+        let stack = state
+            .stack
+            .iter()
+            .map(|n| to_felt(*n))
+            .collect::<Result<VecDeque<Felt>, _>>()?;
+        let ram_memory = state
+            .ram_memory
+            .iter()
+            .map(|(address, word)| {
+                let word = [
+                    to_felt(word[0])?,
+                    to_felt(word[1])?,
+                    to_felt(word[2])?,
+                    to_felt(word[3])?,
+                ];
+                Ok((*address, word))
+            })
+            .collect::<Result<_, VmError>>()?;
+        for value in state
+            .advice_stack
+            .iter()
+            .chain(state.advice_map.values().flatten())
+        {
+            to_felt(*value)?;
+        }
+        let advice_map = state
+            .advice_map
+            .iter()
+            .map(|(key, values)| {
+                let key = key.strip_prefix("0x").unwrap_or(key);
+                Ok((to_digest(&format!("0x{key}"))?, values.clone()))
+            })
+            .collect::<Result<_, VmError>>()?;
+        let mut merkle_store = MerkleStore::new();
+        for node in state.merkle_store.iter() {
+            let value = merkle_store
+                .merge_roots(to_digest(&node.left)?, to_digest(&node.right)?)
+                .map_err(|e| VmError::InvalidStateFile(format!("{e:?}")))?;
+            if value != to_digest(&node.value)? {
+                return Err(VmError::InvalidStateFile(format!(
+                    "node {} is not the hash of its children",
+                    node.value
+                )));
+            }
+        }
+
+        self.stack = stack;
+        self.pad_stack();
+        self.ram_memory = ram_memory;
+        self.advice_stack = state.advice_stack.iter().copied().collect();
+        self.advice_map = advice_map;
+        self.merkle_store = if state.merkle_store.is_empty() {
+            None
+        } else {
+            Some(merkle_store)
+        };
+        Ok(())
+    }
+
+    /// Replaces the instructions of the program and simulates them again, starting from a
+    /// snapshot taken with [`MidenProgram::get_state`]. The procedures and imports of the
+    /// program are kept.
+    ///
+    /// Errors and commented out instructions left by an earlier simulation are checked again,
+    /// so an instruction that was rejected runs if it is valid in the new sequence.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The state to simulate the instructions from.
+    /// * `instructions` - The new instructions of the program.
+    ///
+    /// # Returns
+    ///
+    /// An error if the state is invalid, in which case the program is left unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_masm::MidenProgram;
+    ///
+    /// let mut program = MidenProgram::new();
+    /// let initial = program.get_state();
+    /// program.add_masm("push.1 push.2 push.3 add").unwrap();
+    ///
+    /// let mut instructions = program.instructions.clone();
+    /// instructions.remove(1);
+    /// program.replace_instructions(&initial, instructions).unwrap();
+    /// assert_eq!(program.get_stack()[0], 4u64.into());
+    /// ```
+    pub fn replace_instructions(
+        &mut self,
+        state: &ProgramState,
+        instructions: VecDeque<Instruction>,
+    ) -> Result<(), VmError> {
+        self.set_state(state)?;
+        self.loc_memory.clear();
+        self.loc_frames.clear();
+        self.last_loc_frame = None;
+        self.instructions.clear();
+
+        let mut instructions = instructions
+            .into_iter()
+            .filter(|instruction| !matches!(instruction, Instruction::Error(_)))
+            .flat_map(|instruction| match &instruction {
+                Instruction::CommentedOut(masm) => match parse(tokenize(masm)) {
+                    Ok(parsed) if parsed.procedures.is_empty() => parsed.instructions,
+                    _ => VecDeque::from([instruction]),
+                },
+                _ => VecDeque::from([instruction]),
+            })
+            .collect();
+        self.add_instructions(&mut instructions);
+        Ok(())
+    }
+}
@@ -0,0 +1,234 @@
+use super::limbs;
+use crate::{Instruction, MidenProgram};
+
+/// The number of `u32` limbs of a `u256`.
+const LIMBS: usize = 8;
+
+/// Operations on `u256` values, stored as eight `u32` limbs with the most significant limb on
+/// top of the stack.
+///
+/// Binary operations take `b` on top of `a` and leave the result of `a op b` in their place.
+/// Multiplication and division need more than the 16 reachable stack elements, so they are
+/// added to the program as the procedures `u256_wrapping_mul` and `u256_divmod` on first use,
+/// and called with `exec`. The other operations are emitted inline.
+///
+/// # Example
+///
+/// ```
+/// use rust_masm::MidenProgram;
+///
+/// // (2^32 - 1) + 1 carries into the second limb
+/// let mut program = MidenProgram::new();
+/// program.push_u256([u32::MAX, 0, 0, 0, 0, 0, 0, 0]);
+/// program.push_u256([1, 0, 0, 0, 0, 0, 0, 0]);
+/// program.u256_wrapping_add();
+/// assert_eq!(program.get_stack()[6], 1u64.into());
+/// assert_eq!(program.get_stack()[7], 0u64.into());
+/// ```
+impl MidenProgram {
+    /// Pushes a `u256` given as its limbs, the least significant first, so the most significant
+    /// limb ends up on top.
+    pub fn push_u256(&mut self, limbs: [u32; LIMBS]) {
+        limbs::push(self, &limbs);
+    }
+
+    /// `a + b`, with the carry on top of the result.
+    pub fn u256_overflowing_add(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            let (sum, carry) = limbs::overflowing_add(b, x, y)?;
+            Ok([sum, vec![carry]].concat())
+        });
+    }
+
+    /// `a + b`, modulo `2^256`.
+    pub fn u256_wrapping_add(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            let (sum, carry) = limbs::overflowing_add(b, x, y)?;
+            b.drop(carry)?;
+            Ok(sum)
+        });
+    }
+
+    /// `a + b`, which fails if the sum does not fit in a `u256`.
+    pub fn u256_checked_add(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            let (sum, carry) = limbs::overflowing_add(b, x, y)?;
+            limbs::assert_zero(b, carry)?;
+            Ok(sum)
+        });
+    }
+
+    /// `a - b`, with the borrow on top of the result.
+    pub fn u256_overflowing_sub(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            let (difference, borrow) = limbs::overflowing_sub(b, x, y)?;
+            Ok([difference, vec![borrow]].concat())
+        });
+    }
+
+    /// `a - b`, modulo `2^256`.
+    pub fn u256_wrapping_sub(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            let (difference, borrow) = limbs::overflowing_sub(b, x, y)?;
+            b.drop(borrow)?;
+            Ok(difference)
+        });
+    }
+
+    /// `a - b`, which fails if `b > a`.
+    pub fn u256_checked_sub(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            let (difference, borrow) = limbs::overflowing_sub(b, x, y)?;
+            limbs::assert_zero(b, borrow)?;
+            Ok(difference)
+        });
+    }
+
+    /// `a * b`, modulo `2^256`.
+    pub fn u256_wrapping_mul(&mut self) {
+        limbs::exec_binary(
+            self,
+            "u256_wrapping_mul",
+            2 * LIMBS as u16,
+            LIMBS,
+            |b, x, y| {
+                let y = limbs::store(b, y, LIMBS as u16)?;
+                let x = limbs::store(b, x, 0)?;
+                limbs::mul(b, x, y, LIMBS, LIMBS)
+            },
+        );
+    }
+
+    /// `a / b`, rounded down. Fails if `b` is `0`.
+    pub fn u256_div(&mut self) {
+        self.u256_divmod();
+        limbs::emit(self, |b| {
+            let remainder = limbs::input(b, LIMBS);
+            limbs::drop(b, remainder)
+        });
+    }
+
+    /// `a % b`. Fails if `b` is `0`.
+    pub fn u256_mod(&mut self) {
+        self.u256_divmod();
+        limbs::emit(self, |b| {
+            let remainder = limbs::input(b, LIMBS);
+            let quotient = limbs::input(b, LIMBS);
+            limbs::drop(b, quotient)?;
+            b.move_to_top(&remainder)
+        });
+    }
+
+    /// `a / b` and `a % b`, with the remainder on top of the quotient. Fails if `b` is `0`.
+    pub fn u256_divmod(&mut self) {
+        limbs::exec_binary(self, "u256_divmod", 3 * LIMBS as u16, LIMBS, |b, x, y| {
+            let y = limbs::store(b, y, LIMBS as u16)?;
+            let x = limbs::store(b, x, 0)?;
+            let (quotient, remainder) = limbs::divmod(b, x, y, Some(2 * LIMBS as u16), LIMBS)?;
+            Ok([quotient, remainder].concat())
+        });
+    }
+
+    /// `a < b`, as `1` or `0`.
+    pub fn u256_lt(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| Ok(vec![limbs::lt(b, x, y)?]));
+    }
+
+    /// `a <= b`, as `1` or `0`.
+    pub fn u256_lte(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            let greater = limbs::lt(b, y, x)?;
+            Ok(vec![b.not(greater)?])
+        });
+    }
+
+    /// `a > b`, as `1` or `0`.
+    pub fn u256_gt(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| Ok(vec![limbs::lt(b, y, x)?]));
+    }
+
+    /// `a >= b`, as `1` or `0`.
+    pub fn u256_gte(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            let less = limbs::lt(b, x, y)?;
+            Ok(vec![b.not(less)?])
+        });
+    }
+
+    /// `a == b`, as `1` or `0`.
+    pub fn u256_eq(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| Ok(vec![limbs::eq(b, x, y)?]));
+    }
+
+    /// `a != b`, as `1` or `0`.
+    pub fn u256_neq(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            let equal = limbs::eq(b, x, y)?;
+            Ok(vec![b.not(equal)?])
+        });
+    }
+
+    /// Replaces the `u256` on top of the stack with `1` if it is `0`, and with `0` otherwise.
+    pub fn u256_eqz(&mut self) {
+        limbs::emit(self, |b| {
+            let x = limbs::input(b, LIMBS);
+            limbs::eqz(b, x)?;
+            Ok(())
+        });
+    }
+
+    /// `a & b`.
+    pub fn u256_and(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            limbs::bitwise(b, Instruction::U32CheckedAnd, x, y)
+        });
+    }
+
+    /// `a | b`.
+    pub fn u256_or(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            limbs::bitwise(b, Instruction::U32CheckedOr, x, y)
+        });
+    }
+
+    /// `a ^ b`.
+    pub fn u256_xor(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            limbs::bitwise(b, Instruction::U32CheckedXor, x, y)
+        });
+    }
+
+    /// Shifts the `u256` on top of the stack left by `n` bits, discarding the bits shifted out.
+    ///
+    /// # Panics
+    ///
+    /// If `n` is 256 or more.
+    pub fn u256_shl_n(&mut self, n: u32) {
+        assert!(
+            n < 256,
+            "a u256 can be shifted by at most 255 bits, got {n}"
+        );
+        limbs::emit(self, |b| {
+            let x = limbs::input(b, LIMBS);
+            let shifted = limbs::shl(b, x, n)?;
+            b.move_to_top(&shifted)
+        });
+    }
+
+    /// Shifts the `u256` on top of the stack right by `n` bits.
+    ///
+    /// # Panics
+    ///
+    /// If `n` is 256 or more.
+    pub fn u256_shr_n(&mut self, n: u32) {
+        assert!(
+            n < 256,
+            "a u256 can be shifted by at most 255 bits, got {n}"
+        );
+        limbs::emit(self, |b| {
+            let x = limbs::input(b, LIMBS);
+            let shifted = limbs::shr(b, x, n)?;
+            b.move_to_top(&shifted)
+        });
+    }
+}
@@ -0,0 +1,240 @@
+use super::limbs::{self, Register};
+use crate::{Instruction, MidenProgram};
+
+/// The number of `u32` limbs of a `u64`.
+const LIMBS: usize = 2;
+
+/// Operations on `u64` values, stored as two `u32` limbs with the high limb on top of the stack,
+/// with the semantics of the procedures of `std::math::u64`.
+///
+/// Binary operations take `b` on top of `a` and leave the result of `a op b` in their place.
+/// The sequences are emitted inline, so programs using them need no imports.
+///
+/// # Example
+///
+/// ```
+/// use rust_masm::MidenProgram;
+///
+/// let mut program = MidenProgram::new();
+/// program.push_u64(u64::MAX - 1);
+/// program.push_u64(3);
+/// program.u64_wrapping_add();
+/// assert_eq!(program.get_stack()[0], 0u64.into());
+/// assert_eq!(program.get_stack()[1], 1u64.into());
+/// ```
+impl MidenProgram {
+    /// Pushes a `u64` as its low limb, then its high limb.
+    pub fn push_u64(&mut self, value: u64) {
+        limbs::push(self, &[value as u32, (value >> 32) as u32]);
+    }
+
+    /// `a + b`, with the carry on top of the result.
+    pub fn u64_overflowing_add(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            let (sum, carry) = limbs::overflowing_add(b, x, y)?;
+            Ok([sum, vec![carry]].concat())
+        });
+    }
+
+    /// `a + b`, modulo `2^64`.
+    pub fn u64_wrapping_add(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            let (sum, carry) = limbs::overflowing_add(b, x, y)?;
+            b.drop(carry)?;
+            Ok(sum)
+        });
+    }
+
+    /// `a + b`, which fails if the sum does not fit in a `u64`.
+    pub fn u64_checked_add(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            let (sum, carry) = limbs::overflowing_add(b, x, y)?;
+            limbs::assert_zero(b, carry)?;
+            Ok(sum)
+        });
+    }
+
+    /// `a - b`, with the borrow on top of the result.
+    pub fn u64_overflowing_sub(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            let (difference, borrow) = limbs::overflowing_sub(b, x, y)?;
+            Ok([difference, vec![borrow]].concat())
+        });
+    }
+
+    /// `a - b`, modulo `2^64`.
+    pub fn u64_wrapping_sub(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            let (difference, borrow) = limbs::overflowing_sub(b, x, y)?;
+            b.drop(borrow)?;
+            Ok(difference)
+        });
+    }
+
+    /// `a - b`, which fails if `b > a`.
+    pub fn u64_checked_sub(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            let (difference, borrow) = limbs::overflowing_sub(b, x, y)?;
+            limbs::assert_zero(b, borrow)?;
+            Ok(difference)
+        });
+    }
+
+    /// `a * b`, with a flag on top of the result that is `1` if the product does not fit in a
+    /// `u64`.
+    pub fn u64_overflowing_mul(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            let mut product =
+                limbs::mul(b, Register::Stack(x), Register::Stack(y), LIMBS, 2 * LIMBS)?;
+            let high = product.split_off(LIMBS);
+            let zero = limbs::eqz(b, high)?;
+            let overflow = b.not(zero)?;
+            Ok([product, vec![overflow]].concat())
+        });
+    }
+
+    /// `a * b`, modulo `2^64`.
+    pub fn u64_wrapping_mul(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            limbs::mul(b, Register::Stack(x), Register::Stack(y), LIMBS, LIMBS)
+        });
+    }
+
+    /// `a * b`, which fails if the product does not fit in a `u64`.
+    pub fn u64_checked_mul(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            let mut product =
+                limbs::mul(b, Register::Stack(x), Register::Stack(y), LIMBS, 2 * LIMBS)?;
+            let high = product.split_off(LIMBS);
+            let zero = limbs::eqz(b, high)?;
+            b.apply(Instruction::Assert, &[zero], 0)?;
+            Ok(product)
+        });
+    }
+
+    /// `a / b`, rounded down. Fails if `b` is `0`.
+    pub fn u64_div(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            let (quotient, remainder) =
+                limbs::divmod(b, Register::Stack(x), Register::Stack(y), None, LIMBS)?;
+            limbs::drop(b, remainder)?;
+            Ok(quotient)
+        });
+    }
+
+    /// `a % b`. Fails if `b` is `0`.
+    pub fn u64_mod(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            let (quotient, remainder) =
+                limbs::divmod(b, Register::Stack(x), Register::Stack(y), None, LIMBS)?;
+            limbs::drop(b, quotient)?;
+            Ok(remainder)
+        });
+    }
+
+    /// `a / b` and `a % b`, with the remainder on top of the quotient. Fails if `b` is `0`.
+    pub fn u64_divmod(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            let (quotient, remainder) =
+                limbs::divmod(b, Register::Stack(x), Register::Stack(y), None, LIMBS)?;
+            Ok([quotient, remainder].concat())
+        });
+    }
+
+    /// `a < b`, as `1` or `0`.
+    pub fn u64_lt(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| Ok(vec![limbs::lt(b, x, y)?]));
+    }
+
+    /// `a <= b`, as `1` or `0`.
+    pub fn u64_lte(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            let greater = limbs::lt(b, y, x)?;
+            Ok(vec![b.not(greater)?])
+        });
+    }
+
+    /// `a > b`, as `1` or `0`.
+    pub fn u64_gt(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| Ok(vec![limbs::lt(b, y, x)?]));
+    }
+
+    /// `a >= b`, as `1` or `0`.
+    pub fn u64_gte(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            let less = limbs::lt(b, x, y)?;
+            Ok(vec![b.not(less)?])
+        });
+    }
+
+    /// `a == b`, as `1` or `0`.
+    pub fn u64_eq(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| Ok(vec![limbs::eq(b, x, y)?]));
+    }
+
+    /// `a != b`, as `1` or `0`.
+    pub fn u64_neq(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            let equal = limbs::eq(b, x, y)?;
+            Ok(vec![b.not(equal)?])
+        });
+    }
+
+    /// Replaces the `u64` on top of the stack with `1` if it is `0`, and with `0` otherwise.
+    pub fn u64_eqz(&mut self) {
+        limbs::emit(self, |b| {
+            let x = limbs::input(b, LIMBS);
+            limbs::eqz(b, x)?;
+            Ok(())
+        });
+    }
+
+    /// `a & b`.
+    pub fn u64_and(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            limbs::bitwise(b, Instruction::U32CheckedAnd, x, y)
+        });
+    }
+
+    /// `a | b`.
+    pub fn u64_or(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            limbs::bitwise(b, Instruction::U32CheckedOr, x, y)
+        });
+    }
+
+    /// `a ^ b`.
+    pub fn u64_xor(&mut self) {
+        limbs::emit_binary(self, LIMBS, |b, x, y| {
+            limbs::bitwise(b, Instruction::U32CheckedXor, x, y)
+        });
+    }
+
+    /// Shifts the `u64` on top of the stack left by `n` bits, discarding the bits shifted out.
+    ///
+    /// # Panics
+    ///
+    /// If `n` is 64 or more.
+    pub fn u64_shl_n(&mut self, n: u32) {
+        assert!(n < 64, "a u64 can be shifted by at most 63 bits, got {n}");
+        limbs::emit(self, |b| {
+            let x = limbs::input(b, LIMBS);
+            let shifted = limbs::shl(b, x, n)?;
+            b.move_to_top(&shifted)
+        });
+    }
+
+    /// Shifts the `u64` on top of the stack right by `n` bits.
+    ///
+    /// # Panics
+    ///
+    /// If `n` is 64 or more.
+    pub fn u64_shr_n(&mut self, n: u32) {
+        assert!(n < 64, "a u64 can be shifted by at most 63 bits, got {n}");
+        limbs::emit(self, |b| {
+            let x = limbs::input(b, LIMBS);
+            let shifted = limbs::shr(b, x, n)?;
+            b.move_to_top(&shifted)
+        });
+    }
+}
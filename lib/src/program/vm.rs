@@ -0,0 +1,96 @@
+use miden::{execute, AdviceInputs, DefaultHost, MemAdviceProvider, StackInputs};
+
+use crate::Inputs;
+
+use super::{error::VmError, Digest, MidenProgram, StackOutputs};
+
+/// The result of running a program on the Miden VM with [`MidenProgram::execute_vm`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VmOutput {
+    /// The stack outputs of the execution.
+    pub stack_outputs: StackOutputs,
+    /// The number of rows of the longest execution trace segment, before padding.
+    pub trace_len: usize,
+    /// The number of rows of the execution trace, padded to a power of two.
+    pub padded_trace_len: usize,
+    /// The hash of the executed program.
+    pub program_hash: Digest,
+}
+
+impl VmOutput {
+    /// Returns the elements of the operand stack, starting from the top.
+    pub fn stack(&self) -> &[u64] {
+        self.stack_outputs.stack()
+    }
+
+    /// Returns the overflow table addresses of the elements below the top 16 elements.
+    pub fn overflow_addrs(&self) -> &[u64] {
+        self.stack_outputs.overflow_addrs()
+    }
+}
+
+impl MidenProgram {
+    /// Runs the program on the Miden VM with the inputs of the program, without proving it.
+    ///
+    /// # Returns
+    ///
+    /// The stack outputs and trace length of the execution, or the error returned by the
+    /// assembler or the VM.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rust_masm::MidenProgram;
+    ///
+    /// let mut program = MidenProgram::new();
+    /// program.push(2);
+    /// program.push(3);
+    /// program.add();
+    ///
+    /// let output = program.execute_vm().unwrap();
+    /// assert_eq!(output.stack()[0], 5);
+    /// ```
+    pub fn execute_vm(&mut self) -> Result<VmOutput, VmError> {
+        let stack_inputs = self.stack_inputs.clone();
+        let advice_inputs = self.advice_inputs.clone();
+        self.run_vm(stack_inputs, advice_inputs)
+    }
+
+    /// Runs the program on the Miden VM with the given inputs, without proving it.
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - The operand stack and advice inputs to run the program with.
+    ///
+    /// # Returns
+    ///
+    /// The stack outputs and trace length of the execution, or an error if the inputs are
+    /// invalid or the assembler or the VM fail.
+    pub fn execute_vm_with_inputs(&mut self, inputs: &Inputs) -> Result<VmOutput, VmError> {
+        let stack_inputs = inputs
+            .parse_stack_inputs()
+            .map_err(VmError::InvalidInputs)?;
+        let advice_inputs = inputs
+            .parse_advice_inputs()
+            .map_err(VmError::InvalidInputs)?;
+        self.run_vm(stack_inputs, advice_inputs)
+    }
+
+    fn run_vm(
+        &mut self,
+        stack_inputs: StackInputs,
+        advice_inputs: AdviceInputs,
+    ) -> Result<VmOutput, VmError> {
+        let compiled = self.compile()?;
+
+        let host = DefaultHost::new(MemAdviceProvider::from(advice_inputs));
+        let trace = execute(compiled.program(), stack_inputs, host, Default::default())?;
+
+        Ok(VmOutput {
+            stack_outputs: trace.stack_outputs().clone(),
+            trace_len: trace.trace_len_summary().trace_len(),
+            padded_trace_len: trace.get_trace_len(),
+            program_hash: compiled.hash(),
+        })
+    }
+}
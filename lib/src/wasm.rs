@@ -0,0 +1,159 @@
+use std::collections::VecDeque;
+
+use miden::math::StarkField;
+use serde::Serialize;
+use serde_wasm_bindgen::Serializer;
+use wasm_bindgen::prelude::*;
+
+use crate::{parse, sanitize, tokenize, Inputs, Instruction, MidenProgram};
+
+/// Field elements do not fit in JavaScript numbers, so every value is converted to a `BigInt`
+/// and maps to plain objects.
+const SERIALIZER: Serializer = Serializer::new()
+    .serialize_large_number_types_as_bigints(true)
+    .serialize_maps_as_objects(true);
+
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsError> {
+    value
+        .serialize(&SERIALIZER)
+        .map_err(|e| JsError::new(&e.to_string()))
+}
+
+fn inputs_from_js(inputs: JsValue) -> Result<Inputs, JsError> {
+    if inputs.is_undefined() || inputs.is_null() {
+        return Ok(Inputs::default());
+    }
+    serde_wasm_bindgen::from_value(inputs).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// A program simulated in the browser, exported to JavaScript as `MidenProgram`.
+///
+/// A program is either simulated at once with `parse` or `simulate`, or loaded with `load` and
+/// then simulated one instruction at a time with `step`.
+#[wasm_bindgen(js_name = MidenProgram)]
+pub struct WasmProgram {
+    program: MidenProgram,
+    /// The instructions of a loaded program that have not been simulated yet.
+    pending: VecDeque<Instruction>,
+}
+
+#[wasm_bindgen(js_class = MidenProgram)]
+impl WasmProgram {
+    /// Creates an empty program.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmProgram {
+        WasmProgram {
+            program: MidenProgram::new(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Parses and simulates a program with empty inputs.
+    pub fn parse(masm: &str) -> Result<WasmProgram, JsError> {
+        Self::simulate(masm, JsValue::UNDEFINED)
+    }
+
+    /// Parses and simulates a program with inputs in the format of an inputs file, such as
+    /// `{ operand_stack: [1, 2], advice_stack: [3] }`.
+    pub fn simulate(masm: &str, inputs: JsValue) -> Result<WasmProgram, JsError> {
+        let mut program = Self::load(masm, inputs)?;
+        program.run();
+        Ok(program)
+    }
+
+    /// Parses a program and sets its inputs without simulating any instruction.
+    pub fn load(masm: &str, inputs: JsValue) -> Result<WasmProgram, JsError> {
+        let inputs = inputs_from_js(inputs)?;
+        let parsed = parse(tokenize(&sanitize(masm))).map_err(|e| JsError::new(&e))?;
+
+        let mut program = MidenProgram::new()
+            .with_inputs(inputs)
+            .map_err(|e| JsError::new(&e))?;
+        for path in parsed.imports.iter() {
+            program.add_import(path);
+        }
+        program.add_procs(parsed.procedures);
+        program.comments = parsed.comments;
+        program.trailing_comments = parsed.trailing_comments;
+
+        Ok(WasmProgram {
+            program,
+            pending: parsed.instructions,
+        })
+    }
+
+    /// Simulates the next instruction of a loaded program, or the whole block it opens, along
+    /// with the comments before it. Returns whether instructions are left to simulate.
+    pub fn step(&mut self) -> bool {
+        let mut block = VecDeque::new();
+        let mut depth = 0;
+        while let Some(instruction) = self.pending.pop_front() {
+            match instruction {
+                Instruction::IF | Instruction::WHILE | Instruction::REPEAT(_) => depth += 1,
+                Instruction::END => depth -= 1,
+                _ => {}
+            }
+            let comment = matches!(
+                instruction,
+                Instruction::Comment(_)
+                    | Instruction::DocComment(_)
+                    | Instruction::InlineComment(_)
+            );
+            block.push_back(instruction);
+            if depth <= 0 && !comment {
+                break;
+            }
+        }
+
+        self.program.add_instructions(&mut block);
+        !self.pending.is_empty()
+    }
+
+    /// Simulates every instruction left in a loaded program.
+    pub fn run(&mut self) {
+        while self.step() {}
+    }
+
+    /// Parses Miden assembly and simulates it on the current state, after the instructions
+    /// left in a loaded program.
+    #[wasm_bindgen(js_name = addMasm)]
+    pub fn add_masm(&mut self, masm: &str) -> Result<(), JsError> {
+        self.run();
+        self.program.add_masm(masm).map_err(|e| JsError::new(&e))
+    }
+
+    /// Returns the Miden assembly of the simulated instructions.
+    #[wasm_bindgen(js_name = getMasm)]
+    pub fn get_masm(&self) -> String {
+        self.program.get_masm()
+    }
+
+    /// Returns the operand stack, with its top first.
+    pub fn stack(&self) -> Result<JsValue, JsError> {
+        let stack: Vec<u64> = self.program.stack.iter().map(|n| n.as_int()).collect();
+        to_js(&stack)
+    }
+
+    /// Returns the depth of the operand stack.
+    #[wasm_bindgen(js_name = stackDepth)]
+    pub fn stack_depth(&self) -> usize {
+        self.program.get_stack_depth()
+    }
+
+    /// Returns the written words of memory, keyed by address.
+    pub fn memory(&self) -> Result<JsValue, JsError> {
+        to_js(&self.program.get_state().ram_memory)
+    }
+
+    /// Returns the advice stack, with its top first.
+    #[wasm_bindgen(js_name = adviceStack)]
+    pub fn advice_stack(&self) -> Result<JsValue, JsError> {
+        to_js(&self.program.advice_stack)
+    }
+}
+
+impl Default for WasmProgram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
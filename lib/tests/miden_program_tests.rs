@@ -1,4 +1,22 @@
-use rust_masm::{EmptyProgram, Inputs, MidenProgram, Proc};
+use std::{cell::RefCell, rc::Rc};
+
+use miden::{
+    crypto::{Rpo256, RpoDigest},
+    math::Felt,
+};
+
+use rust_masm::{
+    format_masm, parse, tokenize, CompileError, DebugOptions, EmptyProgram, EventSink, Expr,
+    FormatOptions, Function, ImmediateStyle, Indent, Inputs, Instruction, Layout, MerkleData,
+    MidenProgram, MidenProgramError, Module, Outputs, Proc, ProgramState, StackBuilder,
+    StackBuilderError, Stmt, Type, VmError, MIN_STACK_DEPTH, PROGRAM_FORMAT_VERSION,
+    STATE_FILE_VERSION,
+};
+#[cfg(feature = "prover")]
+use rust_masm::{
+    verify, LibraryNamespace, LibraryVersion, MaslLibrary, ProofFile, ProofHash, ProveOptions,
+    SecurityLevel, StackOutputs, PROOF_FILE_VERSION,
+};
 
 #[test]
 fn test_parse() {
@@ -402,6 +420,12 @@ fn test_error_handling() {
     program.print_masm();
 
     program.save("programs/test.masm");
+    assert_eq!(
+        program.get_errors(),
+        vec![MidenProgramError::NotBinaryValue(2)]
+    );
+    let in_proc = MidenProgram::parse("proc.bad push.1 push.0 div end begin exec.bad end").unwrap();
+    assert_eq!(in_proc.get_errors(), vec![MidenProgramError::DivideByZero]);
 
     assert_eq!(
         program
@@ -522,3 +546,1679 @@ fn test_test_test() {
         vec![10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
     );
 }
+
+#[test]
+fn test_comments() {
+    let masm = "
+        #! Doubles the top of the stack
+        # [a, ...] -> [2a, ...]
+        proc.double # no locals
+            # duplicate a
+            dup
+            add
+        end
+
+        # the entry point
+        begin
+            # push the input
+            push.2
+            exec.double # double it
+            push.0
+            if.true # never taken
+                push.1
+            end
+        end
+        # the end
+        ";
+
+    let program = MidenProgram::parse(masm).unwrap();
+
+    assert_eq!(
+        program.get_masm(),
+        "#! Doubles the top of the stack
+# [a, ...] -> [2a, ...]
+proc.double # no locals
+\t# duplicate a
+\tdup.0
+\tadd
+end
+
+
+# the entry point
+begin
+\t# push the input
+\tpush.2
+\texec.double # double it
+\tpush.0
+\tif.true # never taken
+\t\tpush.1
+\tend
+
+end
+# the end
+"
+    );
+
+    let reparsed = MidenProgram::parse(&program.get_masm()).unwrap();
+    assert_eq!(reparsed.get_masm(), program.get_masm());
+
+    // any comment is kept, whatever its text
+    let program = MidenProgram::parse("begin push.1 end\n#stack output : [1]").unwrap();
+    assert!(program.get_masm().ends_with("end\n#stack output : [1]\n"));
+
+    // fragments keep their comments in place
+    let parsed = parse(tokenize("push.1 # one\n# after")).unwrap();
+    assert_eq!(
+        parsed.instructions,
+        [
+            Instruction::Push(Felt::new(1)),
+            Instruction::InlineComment(" one".to_string()),
+            Instruction::Comment(" after".to_string()),
+        ]
+    );
+}
+
+#[derive(Default)]
+struct CollectingSink {
+    prints: Vec<(String, u64)>,
+    debugs: Vec<DebugOptions>,
+    errors: Vec<MidenProgramError>,
+}
+
+impl EventSink for CollectingSink {
+    fn print(&mut self, message: &str, program: &MidenProgram) {
+        let top = program.get_stack()[0].into();
+        self.prints.push((message.to_string(), top));
+    }
+
+    fn debug(&mut self, options: &DebugOptions, _program: &MidenProgram) {
+        self.debugs.push(*options);
+    }
+
+    fn error(&mut self, error: &MidenProgramError) {
+        self.errors.push(error.clone());
+    }
+}
+
+#[test]
+fn test_event_sink() {
+    let sink = Rc::new(RefCell::new(CollectingSink::default()));
+    let mut program = MidenProgram::new().with_event_sink(sink.clone());
+
+    program.push(5);
+    program.print("push 5");
+    program.push(2);
+    program.and();
+
+    assert_eq!(sink.borrow().prints, vec![("push 5".to_string(), 5)]);
+    assert_eq!(
+        sink.borrow().errors,
+        vec![MidenProgramError::NotBinaryValue(5)]
+    );
+}
+
+#[test]
+fn test_debug() {
+    let sink = Rc::new(RefCell::new(CollectingSink::default()));
+    let mut program = MidenProgram::new().with_event_sink(sink.clone());
+    program.add_program(
+        &mut MidenProgram::parse(
+            "
+            begin
+            push.1
+            debug.stack.2
+            mem_store.0
+            debug.mem.0.3
+            debug.local
+            end
+            ",
+        )
+        .unwrap(),
+    );
+    program.print("after store");
+    program.debug(DebugOptions::MemInterval(4, 4));
+
+    assert_eq!(
+        sink.borrow().debugs,
+        vec![
+            DebugOptions::StackTop(2),
+            DebugOptions::MemInterval(0, 3),
+            DebugOptions::LocalAll,
+            DebugOptions::MemInterval(4, 4),
+        ]
+    );
+    assert_eq!(sink.borrow().prints, vec![("after store".to_string(), 0)]);
+
+    let masm = program.get_masm();
+    assert!(masm.contains("\tdebug.stack.2\n"));
+    assert!(masm.contains("\tdebug.mem.0.3\n"));
+    assert!(masm.contains("\tdebug.local\n"));
+    assert!(masm.contains("\t# after store\n\tdebug.stack\n"));
+    assert!(masm.contains("\tdebug.mem.4\n"));
+
+    // PRINT survives the round trip as a comment followed by `debug.stack`
+    let round_trip = MidenProgram::parse(&masm).unwrap();
+    assert_eq!(round_trip.get_masm(), masm);
+
+    // the decorators the VM does not know about are skipped when proving
+    #[cfg(feature = "prover")]
+    assert!(program.prove().is_ok());
+
+    assert!(MidenProgram::parse("begin debug.stack.0 end").is_err());
+    assert!(MidenProgram::parse("begin debug.mem.3.1 end").is_err());
+    assert!(MidenProgram::parse("begin debug.heap end").is_err());
+}
+
+#[test]
+#[cfg(feature = "prover")]
+fn test_prove_and_verify() {
+    let mut program = MidenProgram::new();
+    program.push(3);
+    program.push(4);
+    program.mul();
+
+    let result = program.prove().unwrap();
+    assert_eq!(result.stack_outputs.stack()[0], 12);
+    assert!(result.verify(program.get_stack_inputs().clone()).is_ok());
+
+    // a proof does not verify against outputs it did not produce
+    let tampered = StackOutputs::new(vec![13], vec![]).unwrap();
+    assert!(matches!(
+        verify(
+            result.program_hash,
+            program.get_stack_inputs().clone(),
+            tampered,
+            result.proof
+        ),
+        Err(VmError::Verification(_))
+    ));
+
+    let mut invalid = MidenProgram::parse("begin push.1 assertz end").unwrap();
+    assert!(matches!(invalid.prove(), Err(VmError::Execution(_))));
+}
+
+#[test]
+#[cfg(feature = "prover")]
+fn test_prove_options() {
+    let mut program = MidenProgram::new();
+    program.push(3);
+    program.push(4);
+    program.mul();
+
+    let stack_inputs = program.get_stack_inputs().clone();
+
+    let secure = program
+        .prove_with_options(&ProveOptions {
+            security: SecurityLevel::Bits128,
+            ..ProveOptions::default()
+        })
+        .unwrap();
+    assert_eq!(secure.verify(stack_inputs.clone()).unwrap(), 128);
+
+    // fewer queries give a faster proof with a lower security level
+    let fast = program
+        .prove_with_options(&ProveOptions {
+            num_queries: Some(8),
+            ..ProveOptions::default()
+        })
+        .unwrap();
+    assert!(fast.verify(stack_inputs).unwrap() < 96);
+    assert_eq!(fast.program_hash, secure.program_hash);
+
+    // the prover needs a power of 2 of at least 8
+    for blowup_factor in [2, 3, 4, 256] {
+        let invalid = ProveOptions {
+            blowup_factor: Some(blowup_factor),
+            ..ProveOptions::default()
+        };
+        assert!(matches!(
+            program.prove_with_options(&invalid),
+            Err(VmError::InvalidOptions(_))
+        ));
+    }
+    assert!(ProveOptions {
+        hash: ProofHash::Rpo,
+        num_queries: Some(0),
+        ..ProveOptions::default()
+    }
+    .to_proving_options()
+    .is_err());
+}
+
+#[test]
+#[cfg(feature = "prover")]
+fn test_proof_file() {
+    let mut program =
+        MidenProgram::parse_with_inputs("begin mul end", Inputs::new(vec![6, 7], None, None, None))
+            .unwrap();
+
+    let result = program
+        .prove_with_options(&ProveOptions {
+            num_queries: Some(8),
+            ..ProveOptions::default()
+        })
+        .unwrap();
+    let proof_file = ProofFile::new(&result, program.get_stack_inputs().clone());
+
+    let path = std::env::temp_dir().join("rust_masm_test_proof_file.proof");
+    let path = path.to_str().unwrap();
+    proof_file.write(path).unwrap();
+
+    let read = ProofFile::read(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+
+    assert_eq!(read.program_hash, result.program_hash);
+    assert_eq!(read.stack_outputs.stack()[0], 42);
+    assert_eq!(read.proof, result.proof);
+    assert_eq!(read.to_json(), proof_file.to_json());
+    assert!(read.verify().is_ok());
+
+    let newer = proof_file.to_json().replacen(
+        &format!("\"version\": {}", PROOF_FILE_VERSION),
+        "\"version\": 99",
+        1,
+    );
+    assert!(matches!(
+        ProofFile::from_json(&newer),
+        Err(VmError::InvalidProofFile(_))
+    ));
+}
+
+#[test]
+fn test_compile() {
+    let mut program = MidenProgram::parse("begin push.1 debug.mem push.2 add end").unwrap();
+
+    let compiled = program.compile().unwrap();
+    assert_eq!(compiled.skipped_decorators(), &["debug.mem".to_string()]);
+    assert!(compiled.masm().contains("#debug.mem"));
+    assert_eq!(program.compile().unwrap().hash(), compiled.hash());
+
+    program.push(3);
+    assert_ne!(program.compile().unwrap().hash(), compiled.hash());
+
+    #[cfg(feature = "prover")]
+    {
+        let result = program.prove().unwrap();
+        assert_eq!(result.program_hash, program.compile().unwrap().hash());
+    }
+}
+
+#[test]
+#[cfg(feature = "prover")]
+fn test_compile_with_library_and_kernel() {
+    let dir = std::env::temp_dir().join("rust_masm_test_library");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("math.masm"), "export.double\n\tdup\n\tadd\nend\n").unwrap();
+    let library = MaslLibrary::read_from_dir(
+        &dir,
+        LibraryNamespace::new("mylib").unwrap(),
+        false,
+        LibraryVersion::MIN,
+    )
+    .unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let masm = "use.mylib::math\n\nbegin push.3 exec.math::double end";
+    let mut program = MidenProgram::parse(masm).unwrap().with_library(library);
+    assert_eq!(program.get_imports(), &["mylib::math".to_string()]);
+    assert!(program.get_masm().starts_with("use.mylib::math\n\nbegin\n"));
+
+    let result = program.prove().unwrap();
+    assert_eq!(result.stack_outputs.stack()[0], 6);
+
+    let mut program = MidenProgram::parse("begin push.1 push.2 syscall.sum end")
+        .unwrap()
+        .with_kernel("export.sum\n\tadd\nend\n");
+    let result = program.prove().unwrap();
+    assert_eq!(result.stack_outputs.stack()[0], 3);
+    assert!(!result.kernel.is_empty());
+    assert!(result.verify(program.get_stack_inputs().clone()).is_ok());
+
+    let proof_file = ProofFile::from_json(
+        &ProofFile::new(&result, program.get_stack_inputs().clone()).to_json(),
+    )
+    .unwrap();
+    assert!(proof_file.verify().is_ok());
+}
+
+#[test]
+fn test_execute_vm() {
+    let mut program = MidenProgram::parse("begin push.6 push.7 mul end").unwrap();
+
+    let output = program.execute_vm().unwrap();
+    assert_eq!(output.stack()[0], 42);
+    assert_eq!(output.stack()[0], u64::from(program.get_stack()[0]));
+    // push.6 and push.7 move two elements to the overflow table, mul brings one back
+    assert_eq!(output.stack().len(), 17);
+    assert_eq!(output.overflow_addrs().len(), 2);
+    assert!(output.trace_len > 0);
+    assert!(output.padded_trace_len >= output.trace_len);
+    assert!(output.padded_trace_len.is_power_of_two());
+
+    let mut program = MidenProgram::parse_with_inputs(
+        "begin adv_push.1 add end",
+        Inputs::new(vec![5], Some(vec![10]), None, None),
+    )
+    .unwrap();
+    assert_eq!(program.execute_vm().unwrap().stack()[0], 15);
+    let output = program
+        .execute_vm_with_inputs(&Inputs::new(vec![1], Some(vec![2]), None, None))
+        .unwrap();
+    assert_eq!(output.stack()[0], 3);
+
+    let mut program = MidenProgram::parse("begin push.1 assertz end").unwrap();
+    assert!(matches!(program.execute_vm(), Err(VmError::Execution(_))));
+    assert!(matches!(
+        program.execute_vm_with_inputs(&Inputs::new(vec![u64::MAX], None, None, None)),
+        Err(VmError::InvalidInputs(_))
+    ));
+}
+
+#[test]
+fn test_stack_depth() {
+    let ops = [
+        "drop", "dropw", "dup.15", "dupw", "dupw.3", "swap.15", "swapw.3", "swapdw", "padw",
+        "movup.15", "movdn.15", "movupw.3", "movdnw.3", "add", "sdepth",
+    ];
+
+    for depth in [0, 3, 16, 17, 20] {
+        let operand_stack: Vec<u64> = (1..=depth).collect();
+        for op in ops.iter() {
+            let mut program = MidenProgram::parse_with_inputs(
+                &format!("begin {op} {op} sdepth end"),
+                Inputs::new(operand_stack.clone(), None, None, None),
+            )
+            .unwrap();
+
+            let simulated: Vec<u64> = program.get_stack().iter().map(|n| u64::from(*n)).collect();
+            assert_eq!(
+                program.execute_vm().unwrap().stack(),
+                &simulated[..],
+                "`{op}` with {depth} inputs"
+            );
+        }
+    }
+
+    let mut program = MidenProgram::new();
+    assert_eq!(program.get_stack_depth(), MIN_STACK_DEPTH);
+    program.push(1);
+    program.push(2);
+    assert_eq!(program.get_stack_depth(), 18);
+    program.dropw();
+    assert_eq!(program.get_stack_depth(), MIN_STACK_DEPTH);
+    program.sdepth();
+    assert_eq!(u64::from(program.get_stack()[0]), 16);
+}
+
+#[test]
+fn test_u32_wrapping_arithmetic() {
+    let max = u32::MAX as u64;
+    let cases = [
+        ("u32checked_add", vec![max - 1, 1]),
+        ("u32overflowing_add", vec![max, 1]),
+        ("u32overflowing_add.1", vec![max]),
+        ("u32wrapping_add", vec![max, 2]),
+        ("u32wrapping_add.2", vec![max]),
+        ("u32overflowing_sub", vec![1, 2]),
+        ("u32overflowing_sub.2", vec![1]),
+        ("u32wrapping_sub", vec![1, 2]),
+        ("u32wrapping_sub.2", vec![1]),
+        ("u32overflowing_mul", vec![max, 2]),
+        ("u32wrapping_mul", vec![max, max]),
+        ("u32overflowing_madd", vec![max, max, max]),
+        ("u32wrapping_madd", vec![max, 3, 5]),
+        ("u32checked_shl", vec![max, 31]),
+        ("u32checked_shl.31", vec![max]),
+        ("u32unchecked_shl", vec![max, 4]),
+        ("u32checked_shr", vec![max, 31]),
+    ];
+
+    for (op, operand_stack) in cases {
+        let mut program = MidenProgram::parse_with_inputs(
+            &format!("begin {op} end"),
+            Inputs::new(operand_stack, None, None, None),
+        )
+        .unwrap();
+        assert!(
+            !program
+                .instructions
+                .iter()
+                .any(|instruction| matches!(instruction, Instruction::Error(_))),
+            "`{op}` was rejected"
+        );
+
+        let simulated: Vec<u64> = program.get_stack().iter().map(|n| u64::from(*n)).collect();
+        assert_eq!(
+            program.execute_vm().unwrap().stack(),
+            &simulated[..],
+            "`{op}`"
+        );
+    }
+}
+
+#[test]
+fn test_memory() {
+    let programs = [
+        // mem_store writes element 0 and keeps the rest of the word
+        "begin push.1.2.3.4 push.10 mem_storew dropw push.9 push.10 mem_store padw push.10 mem_loadw end",
+        // mem_load reads element 0, which is the deepest element of a stored word
+        "begin push.1.2.3.4 mem_storew.20 dropw mem_load.20 push.20 mem_load end",
+        "begin push.5 mem_store.30 padw mem_loadw.30 push.30 mem_load end",
+        "begin mem_load.40 padw mem_loadw.40 push.4294967295 mem_load end",
+        "begin push.7.8.9.10 push.4294967295 mem_storew padw push.4294967295 mem_loadw end",
+        "proc.locals.2
+            push.1.2.3.4 loc_storew.1 dropw
+            push.9 loc_store.1 padw loc_loadw.1 loc_load.1
+        end
+        begin exec.locals end",
+    ];
+
+    for masm in programs {
+        let mut program = MidenProgram::parse(masm).unwrap();
+        let simulated: Vec<u64> = program.get_stack().iter().map(|n| u64::from(*n)).collect();
+        assert_eq!(
+            program.execute_vm().unwrap().stack(),
+            &simulated[..],
+            "{masm}"
+        );
+    }
+
+    let program =
+        MidenProgram::parse("begin push.1.2.3.4 mem_storew.1 push.9 mem_store.1 end").unwrap();
+    assert_eq!(
+        program.get_ram_memory()[&1]
+            .iter()
+            .map(|n| u64::from(*n))
+            .collect::<Vec<u64>>(),
+        vec![9, 2, 3, 4]
+    );
+
+    let sink = Rc::new(RefCell::new(CollectingSink::default()));
+    let mut program = MidenProgram::new().with_event_sink(sink.clone());
+    program.push(u32::MAX as u64 + 1);
+    program.mem_load();
+    assert_eq!(
+        sink.borrow().errors,
+        vec![MidenProgramError::MemoryAddressOutOfBounds(
+            u32::MAX as u64 + 1
+        )]
+    );
+}
+
+#[test]
+fn test_local_frames() {
+    let masm = "
+        proc.inner.1
+            push.7 loc_store.0 loc_load.0
+        end
+
+        proc.outer.2
+            loc_load.1 push.5 loc_store.0 exec.inner loc_load.0
+        end
+
+        begin
+            exec.outer exec.outer
+        end
+    ";
+    let mut program = MidenProgram::parse(masm).unwrap();
+    let simulated: Vec<u64> = program.get_stack().iter().map(|n| u64::from(*n)).collect();
+    assert_eq!(&simulated[..6], &[5, 7, 0, 5, 7, 0]);
+    assert_eq!(program.execute_vm().unwrap().stack(), &simulated[..]);
+    assert!(program.get_loc_memory().is_empty());
+    assert!(program.get_masm().contains("proc.outer.2\n"));
+    // the frame of the second `outer`, which returned last
+    let (name, locals) = program.get_last_loc_frame().unwrap();
+    assert_eq!(name, "outer");
+    assert_eq!(locals.len(), 2);
+    assert_eq!(u64::from(locals[0][0]), 5);
+
+    // every invocation starts with zeroed locals, even where an earlier procedure stored a value
+    let program = MidenProgram::parse(
+        "proc.fresh.1 loc_load.0 push.3 loc_store.0 end begin exec.fresh exec.fresh end",
+    )
+    .unwrap();
+    assert_eq!(u64::from(program.get_stack()[0]), 0);
+    assert_eq!(u64::from(program.get_stack()[1]), 0);
+    let program = MidenProgram::parse(
+        "proc.setter.1 push.42 loc_store.0 end proc.getter.1 loc_load.0 end
+        begin exec.setter exec.getter end",
+    )
+    .unwrap();
+    assert_eq!(u64::from(program.get_stack()[0]), 0);
+
+    let sink = Rc::new(RefCell::new(CollectingSink::default()));
+    let mut program = MidenProgram::new().with_event_sink(sink.clone());
+    let mut procedure = Proc::new("one").with_locals(1);
+    procedure.push(1);
+    procedure.loc_store(1);
+    program.add_proc(procedure);
+    program.exec("one");
+    assert_eq!(
+        sink.borrow().errors,
+        vec![MidenProgramError::LocalIndexOutOfBounds(1, 1)]
+    );
+
+    let mut procedure = Proc::new("inferred");
+    procedure.loc_store(3);
+    assert_eq!(procedure.get_num_locals(), 4);
+    assert_eq!(Proc::new("declared").with_locals(8).get_num_locals(), 8);
+}
+
+#[test]
+fn test_program_state() {
+    let leaf = |n: u8| format!("0x{:02x}{}", n, "0".repeat(62));
+    let key = "ab".repeat(32);
+    let inputs = Inputs::new(
+        vec![1, 2],
+        Some(vec![3, 4, 5]),
+        Some([(key.clone(), vec![6, 7])].into_iter().collect()),
+        Some(vec![MerkleData::MerkleTree(vec![
+            leaf(1),
+            leaf(2),
+            leaf(3),
+            leaf(4),
+        ])]),
+    );
+    let program =
+        MidenProgram::parse_with_inputs("begin push.8.9.10.11 mem_storew.3 adv_push.1 end", inputs)
+            .unwrap();
+
+    let state = program.get_state();
+    assert_eq!(&state.stack[..6], &[3, 11, 10, 9, 8, 2]);
+    assert_eq!(state.ram_memory[&3], [8, 9, 10, 11]);
+    assert_eq!(state.advice_stack, vec![4, 5]);
+    assert_eq!(state.advice_map[&format!("0x{key}")], vec![6, 7]);
+    assert!(!state.merkle_store.is_empty());
+
+    let json = state.to_json();
+    assert!(json.contains(&format!("\"version\": {STATE_FILE_VERSION}")));
+    let restored = ProgramState::from_json(&json).unwrap();
+    assert_eq!(restored, state);
+
+    let mut program = MidenProgram::new();
+    program.set_state(&restored).unwrap();
+    assert_eq!(program.get_state(), state);
+    assert_eq!(program.get_stack_depth(), 21);
+
+    let invalid = ProgramState {
+        stack: vec![u64::MAX],
+        ..Default::default()
+    };
+    assert!(matches!(
+        program.set_state(&invalid),
+        Err(VmError::InvalidStateFile(_))
+    ));
+    assert_eq!(program.get_state(), state);
+
+    assert!(matches!(
+        ProgramState::from_json(&json.replace(
+            &format!("\"version\": {STATE_FILE_VERSION}"),
+            "\"version\": 0"
+        )),
+        Err(VmError::InvalidStateFile(_))
+    ));
+}
+
+#[test]
+fn test_add_masm() {
+    let mut program = MidenProgram::new();
+    program.add_masm("proc.double.1\ndup add\nend").unwrap();
+    program.add_masm("push.1 push.2 add push.1").unwrap();
+    program
+        .add_masm("if.true\npush.5\nelse\npush.6\nend")
+        .unwrap();
+    program.add_masm("repeat.2\nexec.double\nend").unwrap();
+    assert_eq!(program.get_stack()[0], 20u64.into());
+    assert_eq!(program.get_stack()[1], 3u64.into());
+
+    assert!(program.add_masm("proc.double\nend").is_err());
+    assert!(program.add_masm("push.1 foo").is_err());
+    assert_eq!(program.get_stack()[0], 20u64.into());
+
+    let output = program.execute_vm().unwrap();
+    assert_eq!(&output.stack()[..2], &[20, 3]);
+}
+
+#[test]
+fn test_replace_instructions() {
+    let mut program = MidenProgram::new();
+    let initial = program.get_state();
+    program.add_masm("push.1 push.0 div").unwrap();
+    program.add_masm("push.1 add").unwrap();
+    assert!(matches!(
+        program.instructions[2],
+        Instruction::Error(MidenProgramError::DivideByZero)
+    ));
+    assert_eq!(program.instructions[4], Instruction::Push(1u64.into()));
+
+    let snapshot = program.clone();
+
+    let mut instructions = program.instructions.clone();
+    instructions[1] = Instruction::Push(1u64.into());
+    program
+        .replace_instructions(&initial, instructions)
+        .unwrap();
+    assert!(!program
+        .instructions
+        .iter()
+        .any(|instruction| matches!(instruction, Instruction::Error(_))));
+    assert_eq!(program.get_stack()[0], 2u64.into());
+    assert_eq!(snapshot.get_stack()[0], 1u64.into());
+    assert_eq!(program.execute_vm().unwrap().stack()[0], 2);
+}
+
+#[test]
+fn test_serialize_program() {
+    let program = MidenProgram::parse(
+        "
+        use.std::math::u64
+        proc.double
+            dup add
+        end
+        begin
+            push.3
+            exec.double
+            push.18446744069414584320
+            if.true
+                push.1
+            else
+                push.2
+            end
+        end",
+    )
+    .unwrap();
+
+    let json = program.to_json();
+    let from_json = MidenProgram::from_json(&json).unwrap();
+    assert_eq!(from_json.get_masm(), program.get_masm());
+    assert_eq!(from_json.get_stack(), program.get_stack());
+
+    let from_bytes = MidenProgram::from_bytes(&program.to_bytes()).unwrap();
+    assert_eq!(from_bytes.get_masm(), program.get_masm());
+    assert_eq!(from_bytes.instructions, program.instructions);
+
+    let procs = parse(tokenize("proc.double dup add end"))
+        .unwrap()
+        .procedures;
+    let proc = procs[0].clone();
+    assert_eq!(Proc::from_json(&proc.to_json()).unwrap(), proc);
+    assert_eq!(Proc::from_bytes(&proc.to_bytes()).unwrap(), proc);
+
+    let other_version = json.replacen(
+        &format!("\"version\": {PROGRAM_FORMAT_VERSION}"),
+        "\"version\": 0",
+        1,
+    );
+    assert!(matches!(
+        MidenProgram::from_json(&other_version),
+        Err(VmError::InvalidProgramFile(_))
+    ));
+
+    let out_of_range = json.replacen("18446744069414584320", "18446744069414584321", 1);
+    assert!(matches!(
+        MidenProgram::from_json(&out_of_range),
+        Err(VmError::InvalidProgramFile(_))
+    ));
+}
+
+#[test]
+fn test_outputs() {
+    let mut program = MidenProgram::parse(
+        "
+        begin
+            push.1 push.2 push.3 push.4
+            push.5 push.6 push.7 push.8
+            push.9 push.10 push.11 push.12
+            push.13 push.14 push.15 push.16
+            push.17 push.18
+            push.100 mem_store.7
+        end",
+    )
+    .unwrap();
+
+    let simulated = program.get_outputs();
+    assert_eq!(simulated.stack.len(), 34);
+    assert_eq!(simulated.stack[0], 18);
+    assert_eq!(simulated.memory.as_ref().unwrap()[&7], [100, 0, 0, 0]);
+
+    let output = program.execute_vm().unwrap();
+    let executed = output.outputs();
+    assert_eq!(executed.overflow_addrs, output.overflow_addrs());
+    assert_eq!(
+        executed.program_hash,
+        Some(program.compile().unwrap().hash().to_string())
+    );
+    simulated.compare(&executed).unwrap();
+
+    let json = executed.to_json();
+    assert!(json.contains("\"stack\": [\n    \"18\""));
+    assert!(!json.contains("memory"));
+    assert_eq!(Outputs::from_json(&json).unwrap(), executed);
+
+    let miden_cli = r#"{ "stack": ["18", "17", "16"], "overflow_addrs": ["0", "1"] }"#;
+    let expected = Outputs::from_json(miden_cli).unwrap();
+    assert_eq!(expected.memory, None);
+    assert!(simulated.compare(&expected).is_err());
+
+    let mut expected = simulated.clone();
+    expected.memory.as_mut().unwrap().insert(8, [1, 0, 0, 0]);
+    assert!(simulated.compare(&expected).is_err());
+
+    assert!(matches!(
+        Outputs::from_json(r#"{ "stack": ["one"] }"#),
+        Err(VmError::InvalidOutputsFile(_))
+    ));
+}
+
+#[test]
+fn test_inputs_builder() {
+    let word = |n: u64| [Felt::new(n), Felt::new(0), Felt::new(0), Felt::new(0)];
+    let key = RpoDigest::from([Felt::new(1), Felt::new(2), Felt::new(3), Felt::new(4)]);
+
+    let inputs = Inputs::builder()
+        .stack([1u64, 2])
+        .advice_stack([Felt::new(3)])
+        .advice_stack([word(4)])
+        .advice_map_entry(key, [key])
+        .merkle_tree((1..=4).map(word))
+        .build()
+        .unwrap();
+
+    let leaf = |n: u8| format!("0x{:02x}{}", n, "0".repeat(62));
+    let expected = Inputs::new(
+        vec![1, 2],
+        Some(vec![3, 4, 0, 0, 0]),
+        Some([(key.to_string(), vec![1, 2, 3, 4])].into_iter().collect()),
+        Some(vec![MerkleData::MerkleTree(vec![
+            leaf(1),
+            leaf(2),
+            leaf(3),
+            leaf(4),
+        ])]),
+    );
+    assert_eq!(inputs, expected);
+
+    let json = inputs.to_json();
+    assert_eq!(serde_json::from_str::<Inputs>(&json).unwrap(), inputs);
+    let stack_only = Inputs::builder().stack([5u64]).build().unwrap().to_json();
+    assert!(!stack_only.contains("advice_stack"));
+
+    let program = MidenProgram::parse_with_inputs("begin adv_push.1 end", inputs).unwrap();
+    assert_eq!(program.get_stack()[0], Felt::new(3));
+
+    let error = Inputs::builder().stack([u64::MAX]).build().unwrap_err();
+    assert!(error.contains("not a valid field element"));
+    let error = Inputs::builder()
+        .merkle_tree((1..=3).map(word))
+        .build()
+        .unwrap_err();
+    assert!(error.contains("Merkle tree"));
+
+    assert!(Inputs::parse_word("0x")
+        .unwrap_err()
+        .contains("expected 64 hex digits"));
+    assert!(Inputs::parse_word("").is_err());
+    assert_eq!(Inputs::parse_word(&leaf(7)[2..]).unwrap(), word(7));
+    let short_key = Inputs::new(
+        vec![],
+        None,
+        Some([("ab".to_string(), vec![1])].into_iter().collect()),
+        None,
+    );
+    assert!(short_key.validate().unwrap_err().contains("advice map key"));
+    let error = MidenProgram::parse_with_inputs("begin end", short_key).err();
+    assert!(error.unwrap().contains("advice map key"));
+    assert!(Inputs::try_from_file("inputs/missing.json")
+        .unwrap_err()
+        .contains("inputs/missing.json"));
+}
+
+#[test]
+fn test_advice_map() {
+    let values = vec![Felt::new(5), Felt::new(6), Felt::new(7)];
+    let key = Rpo256::hash_elements(&values);
+
+    let mut program = MidenProgram::new();
+    assert_eq!(program.add_hashed_advice_values(values.clone()), key);
+    assert_eq!(program.advice_map[&key], vec![5, 6, 7]);
+
+    program.push_digest(key);
+    program.adv_push_mapvaln(0);
+    assert_eq!(
+        program.advice_stack.iter().copied().collect::<Vec<u64>>(),
+        vec![3, 5, 6, 7]
+    );
+    program.push(1);
+    program.adv_push_mapval(1);
+    assert_eq!(program.advice_stack.len(), 7);
+    program.adv_push(4);
+    assert_eq!(program.get_stack()[0], Felt::new(3));
+    assert_eq!(program.get_stack()[3], Felt::new(5));
+    assert_eq!(
+        program.get_masm().matches("adv.push_mapval").count(),
+        2,
+        "{}",
+        program.get_masm()
+    );
+    assert_eq!(program.execute_vm().unwrap().stack()[0], 3);
+
+    let parsed = MidenProgram::parse_with_inputs(
+        &format!(
+            "begin push.{}.{}.{}.{} adv.push_mapval.4 adv.push_mapval adv_push.1 end",
+            Felt::new(1),
+            Felt::new(2),
+            Felt::new(3),
+            Felt::new(4)
+        ),
+        Inputs::builder()
+            .hashed_advice_map_entry(values)
+            .build()
+            .unwrap(),
+    );
+    let program = parsed.unwrap();
+    assert_eq!(
+        program.instructions[4],
+        Instruction::Error(MidenProgramError::AdviceMapKeyNotFound(format!(
+            "0x{}",
+            "0".repeat(64)
+        )))
+    );
+    assert_eq!(
+        program.instructions[6],
+        Instruction::Error(MidenProgramError::AdviceMapKeyNotFound(
+            "0x0100000000000000020000000000000003000000000000000400000000000000".to_string()
+        ))
+    );
+    assert!(MidenProgram::parse("begin adv.push_u64div end").is_err());
+    assert!(MidenProgram::parse("begin adv.push_mapval.13 end").is_err());
+
+    let inputs = Inputs::builder()
+        .hashed_advice_map_entry([Felt::new(9)])
+        .build()
+        .unwrap();
+    let key = Rpo256::hash_elements(&[Felt::new(9)]);
+    let mut program = MidenProgram::new().with_inputs(inputs).unwrap();
+    program.push_digest(key);
+    program.adv_push_mapval(0);
+    program.adv_push(1);
+    assert_eq!(program.get_stack()[0], Felt::new(9));
+    assert_eq!(program.execute_vm().unwrap().stack()[0], 9);
+
+    let state = program.get_state();
+    let mut restored = MidenProgram::new();
+    restored.set_state(&state).unwrap();
+    assert_eq!(restored.advice_map, program.advice_map);
+}
+
+/// Calls a compiled function in the simulator and in the VM, and checks that both leave only
+/// `expected` in place of the arguments.
+fn run_compiled(module: &Module, name: &str, args: &[u64], expected: &[u64]) {
+    let mut program = MidenProgram::new();
+    program.add_procs(module.compile().unwrap());
+    for arg in args {
+        program.push(*arg);
+    }
+    program.exec(name);
+
+    let stack: Vec<u64> = program.get_outputs().stack;
+    assert_eq!(&stack[..expected.len()], expected, "{}", program.get_masm());
+    assert_eq!(program.get_stack_depth(), MIN_STACK_DEPTH + expected.len());
+    assert!(stack[expected.len()..].iter().all(|v| *v == 0));
+
+    let executed = program.execute_vm().unwrap();
+    assert_eq!(&executed.stack()[..expected.len()], expected);
+}
+
+#[test]
+fn test_compile_expressions() {
+    // fn poly(a: felt, b: felt, c: felt) -> felt {
+    //     let x = a * b + c;
+    //     let y = x - a;
+    //     return x * y + b / 2;
+    // }
+    let poly = Function::new("poly")
+        .with_param("a", Type::Felt)
+        .with_param("b", Type::Felt)
+        .with_param("c", Type::Felt)
+        .with_returns(Type::Felt)
+        .with_body(vec![
+            Stmt::local(
+                "x",
+                Type::Felt,
+                Expr::var("a") * Expr::var("b") + Expr::var("c"),
+            ),
+            Stmt::local("y", Type::Felt, Expr::var("x") - Expr::var("a")),
+            Stmt::ret(Expr::var("x") * Expr::var("y") + Expr::var("b") / Expr::felt(2)),
+        ]);
+    let module = Module::new().with_function(poly);
+    for (a, b, c) in [(1, 2, 3), (7, 10, 0), (100, 4, 99)] {
+        let x = a * b + c;
+        let y = x - a;
+        run_compiled(&module, "poly", &[a, b, c], &[x * y + b / 2]);
+    }
+
+    // the last uses of the parameters move them instead of copying them
+    let masm = Proc::get_masm(&module.compile().unwrap()[0]);
+    assert!(masm.contains("movup"), "{masm}");
+
+    // comparisons and boolean operators produce felts
+    let compare = Function::new("compare")
+        .with_param("a", Type::U32)
+        .with_param("b", Type::U32)
+        .with_returns(Type::Felt)
+        .with_body(vec![Stmt::ret(
+            Expr::var("a").lt(Expr::var("b")) & !Expr::var("a").eq(Expr::u32(0))
+                | Expr::var("a").gte(Expr::u32(100)),
+        )]);
+    let module = Module::new().with_function(compare);
+    for (a, b) in [(1u64, 2u64), (0, 2), (3, 2), (150, 2)] {
+        let expected = ((a < b) && a != 0) || a >= 100;
+        run_compiled(&module, "compare", &[a, b], &[expected as u64]);
+    }
+
+    // u32 arithmetic uses the checked instructions
+    let mix = Function::new("mix")
+        .with_param("a", Type::U32)
+        .with_param("b", Type::U32)
+        .with_returns(Type::U32)
+        .with_body(vec![Stmt::ret(
+            ((Expr::var("a") * Expr::var("b") + Expr::u32(7)) % Expr::u32(1000))
+                ^ (Expr::var("a") / Expr::var("b")),
+        )]);
+    let module = Module::new().with_function(mix);
+    for (a, b) in [(12u64, 5u64), (999, 3), (65535, 65535)] {
+        run_compiled(&module, "mix", &[a, b], &[((a * b + 7) % 1000) ^ (a / b)]);
+    }
+}
+
+#[test]
+fn test_compile_control_flow() {
+    // fn fib(n: u32) -> u32 {
+    //     let a: u32 = 0;
+    //     let b: u32 = 1;
+    //     while n > 0 {
+    //         let next: u32 = a + b;
+    //         a = b;
+    //         b = next;
+    //         n = n - 1;
+    //     }
+    //     if a > 100 { a = a - 100; } else { a = a + 1; }
+    //     return a;
+    // }
+    let fib = Function::new("fib")
+        .with_param("n", Type::U32)
+        .with_returns(Type::U32)
+        .with_body(vec![
+            Stmt::local("a", Type::U32, Expr::u32(0)),
+            Stmt::local("b", Type::U32, Expr::u32(1)),
+            Stmt::while_loop(
+                Expr::var("n").gt(Expr::u32(0)),
+                vec![
+                    Stmt::local("next", Type::U32, Expr::var("a") + Expr::var("b")),
+                    Stmt::assign("a", Expr::var("b")),
+                    Stmt::assign("b", Expr::var("next")),
+                    Stmt::assign("n", Expr::var("n") - Expr::u32(1)),
+                ],
+            ),
+            Stmt::if_else(
+                Expr::var("a").gt(Expr::u32(100)),
+                vec![Stmt::assign("a", Expr::var("a") - Expr::u32(100))],
+                vec![Stmt::assign("a", Expr::var("a") + Expr::u32(1))],
+            ),
+            Stmt::ret(Expr::var("a")),
+        ]);
+    let module = Module::new().with_function(fib);
+
+    for n in [0u64, 1, 5, 12, 20] {
+        let (mut a, mut b) = (0u64, 1u64);
+        for _ in 0..n {
+            (a, b) = (b, a + b);
+        }
+        let expected = if a > 100 { a - 100 } else { a + 1 };
+        run_compiled(&module, "fib", &[n], &[expected]);
+    }
+}
+
+#[test]
+fn test_compile_spills_and_calls() {
+    // more variables than fit on the stack are spilled to locals
+    let mut body: Vec<Stmt> = (0..12)
+        .map(|i| {
+            Stmt::local(
+                &format!("v{i}"),
+                Type::Felt,
+                Expr::var("x") * Expr::felt(i + 1),
+            )
+        })
+        .collect();
+    let sum = (1..12).fold(Expr::var("v0"), |sum, i| sum + Expr::var(&format!("v{i}")));
+    body.push(Stmt::ret(sum - Expr::var("x")));
+    let spill = Function::new("spill")
+        .with_param("x", Type::Felt)
+        .with_returns(Type::Felt)
+        .with_body(body);
+
+    // fn square(x: felt) -> felt { return x * x; }
+    let square = Function::new("square")
+        .with_param("x", Type::Felt)
+        .with_returns(Type::Felt)
+        .with_body(vec![Stmt::ret(Expr::var("x") * Expr::var("x"))]);
+
+    // fn sum_of_squares(a: felt, b: felt) -> felt { return square(a) + square(b); }
+    let sum_of_squares = Function::new("sum_of_squares")
+        .with_param("a", Type::Felt)
+        .with_param("b", Type::Felt)
+        .with_returns(Type::Felt)
+        .with_body(vec![Stmt::ret(
+            Expr::call("square", vec![Expr::var("a")]) + Expr::call("square", vec![Expr::var("b")]),
+        )]);
+
+    // fn pick(first: felt, a: word, b: word) -> word {
+    //     let result: word = b;
+    //     if first { result = a; }
+    //     return result;
+    // }
+    let pick = Function::new("pick")
+        .with_param("first", Type::Felt)
+        .with_param("a", Type::Word)
+        .with_param("b", Type::Word)
+        .with_returns(Type::Word)
+        .with_body(vec![
+            Stmt::local("result", Type::Word, Expr::var("b")),
+            Stmt::if_then(
+                Expr::var("first"),
+                vec![Stmt::assign("result", Expr::var("a"))],
+            ),
+            Stmt::ret(Expr::var("result")),
+        ]);
+
+    // fn same(a: word) -> felt { return pick(1, a, [1, 2, 3, 4]) == a; }
+    let same = Function::new("same")
+        .with_param("a", Type::Word)
+        .with_returns(Type::Felt)
+        .with_body(vec![Stmt::ret(
+            Expr::call(
+                "pick",
+                vec![Expr::felt(1), Expr::var("a"), Expr::word([1, 2, 3, 4])],
+            )
+            .eq(Expr::var("a")),
+        )]);
+
+    let module = Module::new()
+        .with_function(sum_of_squares)
+        .with_function(spill)
+        .with_function(same)
+        .with_function(square)
+        .with_function(pick);
+
+    let procs = module.compile().unwrap();
+    let names: Vec<&str> = procs.iter().map(|proc| proc.name.as_str()).collect();
+    assert_eq!(names, ["square", "sum_of_squares", "spill", "pick", "same"]);
+    assert!(procs[2].get_num_locals() > 0);
+
+    for x in [0u64, 1, 9] {
+        run_compiled(
+            &module,
+            "spill",
+            &[x],
+            &[(1..=12).map(|i| x * i).sum::<u64>() - x],
+        );
+    }
+    run_compiled(&module, "sum_of_squares", &[3, 4], &[25]);
+    run_compiled(&module, "pick", &[1, 1, 2, 3, 4, 5, 6, 7, 8], &[4, 3, 2, 1]);
+    run_compiled(&module, "pick", &[0, 1, 2, 3, 4, 5, 6, 7, 8], &[8, 7, 6, 5]);
+    run_compiled(&module, "same", &[5, 6, 7, 8], &[1]);
+}
+
+#[test]
+fn test_compile_errors() {
+    let compile = |function: Function| Module::new().with_function(function).compile();
+
+    assert_eq!(
+        compile(Function::new("f").with_body(vec![Stmt::assign("x", Expr::felt(1))])),
+        Err(CompileError::UnknownVariable("x".to_string()))
+    );
+    assert_eq!(
+        compile(Function::new("f").with_body(vec![Stmt::local("x", Type::U32, Expr::felt(1))])),
+        Err(CompileError::TypeMismatch(
+            "x".to_string(),
+            Type::U32,
+            Type::Felt
+        ))
+    );
+    assert_eq!(
+        compile(
+            Function::new("f")
+                .with_returns(Type::Felt)
+                .with_body(vec![Stmt::Expr(Expr::felt(1))])
+        ),
+        Err(CompileError::MissingReturn("f".to_string()))
+    );
+    assert_eq!(
+        compile(Function::new("f").with_body(vec![Stmt::Expr(
+            Expr::word([1, 2, 3, 4]) + Expr::word([1, 2, 3, 4])
+        )])),
+        Err(CompileError::UnsupportedOperation(
+            "+".to_string(),
+            Type::Word
+        ))
+    );
+    assert_eq!(
+        compile(
+            Function::new("f")
+                .with_returns(Type::Felt)
+                .with_body(vec![Stmt::ret(Expr::call("f", vec![]))])
+        ),
+        Err(CompileError::RecursiveCall("f".to_string()))
+    );
+    assert_eq!(
+        compile(Function::new("f").with_body(vec![Stmt::Expr(Expr::call("g", vec![]))])),
+        Err(CompileError::UnknownFunction("g".to_string()))
+    );
+}
+
+#[test]
+fn test_stack_builder() {
+    // (a - b) * (a + c), with a, b and c already on the stack, a on top
+    let mut b = StackBuilder::new();
+    let x = b.input();
+    let y = b.input();
+    let z = b.input();
+    let x2 = b.dup(x).unwrap();
+    let difference = b.sub(x, y).unwrap();
+    let sum = b.add(x2, z).unwrap();
+    let product = b.mul(difference, sum).unwrap();
+    assert_eq!(b.depth_of(product), Ok(0));
+
+    // consumed values cannot be used again
+    assert_eq!(b.add(x, product), Err(StackBuilderError::Consumed(x)));
+    assert_eq!(
+        b.mul(product, product),
+        Err(StackBuilderError::Consumed(product))
+    );
+
+    let mut program = MidenProgram::new();
+    program.push(4);
+    program.push(3);
+    program.push(10);
+    program.add_program(&mut b);
+    assert_eq!(program.get_stack()[0], Felt::new((10 - 3) * (10 + 4)));
+    assert_eq!(program.get_stack_depth(), MIN_STACK_DEPTH + 1);
+    let masm = program.get_masm();
+    assert!(masm.contains("movup") && masm.contains("dup.0"), "{masm}");
+
+    // words are moved with swapw when they are a word apart, and element by element otherwise
+    let mut b = StackBuilder::new();
+    let first = b.push_word([1, 2, 3, 4]);
+    let second = b.push_word([5, 6, 7, 8]);
+    let copy = b.dup(first).unwrap();
+    let equal = b.eqw(first, copy).unwrap();
+    let flag = b.push(9);
+    b.mem_store(0, flag).unwrap();
+    b.mem_store_w(1, second).unwrap();
+    assert_eq!(b.drop(second), Err(StackBuilderError::Consumed(second)));
+    assert_eq!(
+        b.mem_store_w(2, equal),
+        Err(StackBuilderError::ExpectedWord(equal))
+    );
+
+    let mut program = MidenProgram::new();
+    program.add_program(&mut b);
+    assert_eq!(program.get_stack()[0], Felt::new(1));
+    assert_eq!(program.get_stack_depth(), MIN_STACK_DEPTH + 1);
+    let outputs = program.get_outputs();
+    assert_eq!(outputs.memory.as_ref().unwrap()[&0][0], 9);
+    assert_eq!(outputs.memory.as_ref().unwrap()[&1], [5, 6, 7, 8]);
+    assert!(
+        program.get_masm().contains("dupw.1"),
+        "{}",
+        program.get_masm()
+    );
+
+    let executed = program.execute_vm().unwrap();
+    assert_eq!(executed.stack()[0], 1);
+}
+
+/// Checks the stack of the simulated and the executed program against `expected`, top first.
+fn check_limb_result(program: &mut MidenProgram, expected: &[u64]) {
+    check_simulated_limb_result(program, expected);
+
+    let executed = program.execute_vm().unwrap();
+    assert_eq!(&executed.stack()[..expected.len()], expected);
+}
+
+fn check_simulated_limb_result(program: &MidenProgram, expected: &[u64]) {
+    let stack: Vec<u64> = program.get_outputs().stack;
+    assert_eq!(&stack[..expected.len()], expected, "{}", program.get_masm());
+    assert_eq!(program.get_stack_depth(), MIN_STACK_DEPTH + expected.len());
+}
+
+/// The limbs of a `u64`, top first.
+fn u64_limbs(value: u64) -> Vec<u64> {
+    vec![value >> 32, value & 0xFFFF_FFFF]
+}
+
+/// An operation of `MidenProgram` on the values on top of the stack.
+type StackOp = fn(&mut MidenProgram);
+
+fn run_u64(a: u64, b: u64, op: StackOp, expected: &[u64]) {
+    let mut program = MidenProgram::new();
+    program.push_u64(a);
+    program.push_u64(b);
+    op(&mut program);
+    check_limb_result(&mut program, expected);
+}
+
+#[test]
+fn test_u64_arithmetic() {
+    let values = [
+        0,
+        1,
+        0xFFFF_FFFF,
+        0x1_0000_0000,
+        0x1234_5678_9ABC_DEF0,
+        u64::MAX,
+    ];
+    for a in values {
+        for b in values {
+            let (sum, carry) = a.overflowing_add(b);
+            run_u64(
+                a,
+                b,
+                MidenProgram::u64_overflowing_add,
+                &[&[carry as u64][..], &u64_limbs(sum)].concat(),
+            );
+            run_u64(a, b, MidenProgram::u64_wrapping_add, &u64_limbs(sum));
+            let (difference, borrow) = a.overflowing_sub(b);
+            run_u64(
+                a,
+                b,
+                MidenProgram::u64_overflowing_sub,
+                &[&[borrow as u64][..], &u64_limbs(difference)].concat(),
+            );
+            run_u64(a, b, MidenProgram::u64_wrapping_sub, &u64_limbs(difference));
+            let (product, overflow) = a.overflowing_mul(b);
+            run_u64(
+                a,
+                b,
+                MidenProgram::u64_overflowing_mul,
+                &[&[overflow as u64][..], &u64_limbs(product)].concat(),
+            );
+            run_u64(a, b, MidenProgram::u64_wrapping_mul, &u64_limbs(product));
+            if !carry {
+                run_u64(a, b, MidenProgram::u64_checked_add, &u64_limbs(sum));
+            }
+            if !borrow {
+                run_u64(a, b, MidenProgram::u64_checked_sub, &u64_limbs(difference));
+            }
+            if !overflow {
+                run_u64(a, b, MidenProgram::u64_checked_mul, &u64_limbs(product));
+            }
+            if b != 0 {
+                run_u64(a, b, MidenProgram::u64_div, &u64_limbs(a / b));
+                run_u64(a, b, MidenProgram::u64_mod, &u64_limbs(a % b));
+                run_u64(
+                    a,
+                    b,
+                    MidenProgram::u64_divmod,
+                    &[u64_limbs(a % b), u64_limbs(a / b)].concat(),
+                );
+            }
+
+            run_u64(a, b, MidenProgram::u64_lt, &[(a < b) as u64]);
+            run_u64(a, b, MidenProgram::u64_lte, &[(a <= b) as u64]);
+            run_u64(a, b, MidenProgram::u64_gt, &[(a > b) as u64]);
+            run_u64(a, b, MidenProgram::u64_gte, &[(a >= b) as u64]);
+            run_u64(a, b, MidenProgram::u64_eq, &[(a == b) as u64]);
+            run_u64(a, b, MidenProgram::u64_neq, &[(a != b) as u64]);
+            run_u64(a, b, MidenProgram::u64_and, &u64_limbs(a & b));
+            run_u64(a, b, MidenProgram::u64_or, &u64_limbs(a | b));
+            run_u64(a, b, MidenProgram::u64_xor, &u64_limbs(a ^ b));
+        }
+
+        let mut program = MidenProgram::new();
+        program.push_u64(a);
+        program.u64_eqz();
+        check_limb_result(&mut program, &[(a == 0) as u64]);
+
+        for shift in [0, 1, 31, 32, 33, 63] {
+            let mut program = MidenProgram::new();
+            program.push_u64(a);
+            program.u64_shl_n(shift);
+            check_limb_result(&mut program, &u64_limbs(a << shift));
+
+            let mut program = MidenProgram::new();
+            program.push_u64(a);
+            program.u64_shr_n(shift);
+            check_limb_result(&mut program, &u64_limbs(a >> shift));
+        }
+    }
+
+    // the checked operations fail on the VM
+    let failing: [(u64, u64, StackOp); 4] = [
+        (u64::MAX, 2, MidenProgram::u64_checked_add),
+        (0, 1, MidenProgram::u64_checked_sub),
+        (u64::MAX, 2, MidenProgram::u64_checked_mul),
+        (1, 0, MidenProgram::u64_div),
+    ];
+    for (a, b, op) in failing {
+        let mut program = MidenProgram::new();
+        program.push_u64(a);
+        program.push_u64(b);
+        op(&mut program);
+        assert!(program.execute_vm().is_err());
+    }
+}
+
+/// A `u256` as its low and high halves.
+type U256 = (u128, u128);
+
+/// The limbs of a `u256`, the least significant first.
+fn u256_limbs((low, high): U256) -> [u32; 8] {
+    let mut limbs = [0; 8];
+    for i in 0..4 {
+        limbs[i] = (low >> (32 * i)) as u32;
+        limbs[i + 4] = (high >> (32 * i)) as u32;
+    }
+    limbs
+}
+
+/// The limbs of a `u256`, top first.
+fn u256_stack(value: U256) -> Vec<u64> {
+    u256_limbs(value)
+        .iter()
+        .rev()
+        .map(|limb| *limb as u64)
+        .collect()
+}
+
+fn run_u256(a: U256, b: U256, op: StackOp, expected: &[u64]) {
+    let mut program = MidenProgram::new();
+    program.push_u256(u256_limbs(a));
+    program.push_u256(u256_limbs(b));
+    op(&mut program);
+    check_limb_result(&mut program, expected);
+}
+
+#[test]
+fn test_u256_arithmetic() {
+    let values: [U256; 5] = [
+        (0, 0),
+        (1, 0),
+        (u128::MAX, 0),
+        (0x0123_4567_89AB_CDEF_FEDC_BA98_7654_3210, 0xFFFF_0000_FFFF),
+        (u128::MAX, u128::MAX),
+    ];
+    for a in values {
+        for b in values {
+            let (low, carry_low) = a.0.overflowing_add(b.0);
+            let (high, carry_high) = a.1.overflowing_add(b.1);
+            let (high, carry) = high.overflowing_add(carry_low as u128);
+            let carry = carry || carry_high;
+            run_u256(
+                a,
+                b,
+                MidenProgram::u256_overflowing_add,
+                &[&[carry as u64][..], &u256_stack((low, high))].concat(),
+            );
+            run_u256(
+                a,
+                b,
+                MidenProgram::u256_wrapping_add,
+                &u256_stack((low, high)),
+            );
+            if !carry {
+                run_u256(
+                    a,
+                    b,
+                    MidenProgram::u256_checked_add,
+                    &u256_stack((low, high)),
+                );
+            }
+
+            let (low, borrow_low) = a.0.overflowing_sub(b.0);
+            let (high, borrow_high) = a.1.overflowing_sub(b.1);
+            let (high, borrow) = high.overflowing_sub(borrow_low as u128);
+            let borrow = borrow || borrow_high;
+            run_u256(
+                a,
+                b,
+                MidenProgram::u256_overflowing_sub,
+                &[&[borrow as u64][..], &u256_stack((low, high))].concat(),
+            );
+            run_u256(
+                a,
+                b,
+                MidenProgram::u256_wrapping_sub,
+                &u256_stack((low, high)),
+            );
+            if !borrow {
+                run_u256(
+                    a,
+                    b,
+                    MidenProgram::u256_checked_sub,
+                    &u256_stack((low, high)),
+                );
+            }
+
+            let (a_value, b_value) = ((a.1, a.0), (b.1, b.0));
+            run_u256(a, b, MidenProgram::u256_lt, &[(a_value < b_value) as u64]);
+            run_u256(a, b, MidenProgram::u256_lte, &[(a_value <= b_value) as u64]);
+            run_u256(a, b, MidenProgram::u256_gt, &[(a_value > b_value) as u64]);
+            run_u256(a, b, MidenProgram::u256_gte, &[(a_value >= b_value) as u64]);
+            run_u256(a, b, MidenProgram::u256_eq, &[(a == b) as u64]);
+            run_u256(a, b, MidenProgram::u256_neq, &[(a != b) as u64]);
+            run_u256(
+                a,
+                b,
+                MidenProgram::u256_and,
+                &u256_stack((a.0 & b.0, a.1 & b.1)),
+            );
+            run_u256(
+                a,
+                b,
+                MidenProgram::u256_or,
+                &u256_stack((a.0 | b.0, a.1 | b.1)),
+            );
+            run_u256(
+                a,
+                b,
+                MidenProgram::u256_xor,
+                &u256_stack((a.0 ^ b.0, a.1 ^ b.1)),
+            );
+        }
+
+        let mut program = MidenProgram::new();
+        program.push_u256(u256_limbs(a));
+        program.u256_eqz();
+        check_limb_result(&mut program, &[(a == (0, 0)) as u64]);
+
+        for shift in [0, 1, 32, 45, 127] {
+            let shifted_left = match shift {
+                0 => a,
+                _ => (a.0 << shift, (a.1 << shift) | (a.0 >> (128 - shift))),
+            };
+            let mut program = MidenProgram::new();
+            program.push_u256(u256_limbs(a));
+            program.u256_shl_n(shift);
+            check_limb_result(&mut program, &u256_stack(shifted_left));
+
+            let shifted_right = match shift {
+                0 => a,
+                _ => ((a.0 >> shift) | (a.1 << (128 - shift)), a.1 >> shift),
+            };
+            let mut program = MidenProgram::new();
+            program.push_u256(u256_limbs(a));
+            program.u256_shr_n(shift);
+            check_limb_result(&mut program, &u256_stack(shifted_right));
+        }
+    }
+
+    // products and quotients of values that Rust can compute natively, with the divisions
+    // only simulated, as each takes seconds on the VM in debug builds
+    let values: [u128; 4] = [1, 0xFFFF_FFFF, 0x1234_5678_9ABC_DEF0, u64::MAX as u128];
+    for a in values {
+        for b in values {
+            run_u256(
+                (a, 0),
+                (b, 0),
+                MidenProgram::u256_wrapping_mul,
+                &u256_stack((a * b, 0)),
+            );
+
+            let a = a * 0xDEAD_BEEF_0000_0001;
+            let divisions: [(StackOp, Vec<u64>); 3] = [
+                (MidenProgram::u256_div, u256_stack((a / b, 0))),
+                (MidenProgram::u256_mod, u256_stack((a % b, 0))),
+                (
+                    MidenProgram::u256_divmod,
+                    [u256_stack((a % b, 0)), u256_stack((a / b, 0))].concat(),
+                ),
+            ];
+            for (op, expected) in divisions {
+                let mut program = MidenProgram::new();
+                program.push_u256(u256_limbs((a, 0)));
+                program.push_u256(u256_limbs((b, 0)));
+                op(&mut program);
+                check_simulated_limb_result(&program, &expected);
+            }
+        }
+    }
+    let (a, b) = (u128::MAX - 12345, 0xFFFF_FFFF_0000_0003_u128);
+    run_u256(
+        (a, 0),
+        (b, 0),
+        MidenProgram::u256_divmod,
+        &[u256_stack((a % b, 0)), u256_stack((a / b, 0))].concat(),
+    );
+
+    // 2^255 * 2 wraps to 0, and the procedures are added once
+    let mut program = MidenProgram::new();
+    program.push_u256(u256_limbs((0, 1 << 127)));
+    program.push_u256(u256_limbs((2, 0)));
+    program.u256_wrapping_mul();
+    program.push_u256(u256_limbs((3, 0)));
+    program.u256_wrapping_mul();
+    check_limb_result(&mut program, &u256_stack((0, 0)));
+    assert_eq!(program.get_proc_names(), ["u256_wrapping_mul"]);
+
+    let mut program = MidenProgram::new();
+    program.push_u256(u256_limbs((1, 0)));
+    program.push_u256(u256_limbs((0, 0)));
+    program.u256_div();
+    assert!(program.execute_vm().is_err());
+}
+
+#[test]
+fn test_format_indentation() {
+    let formatted = format_masm(
+        "
+        proc.double
+        dup add
+        end
+        begin
+        push.1
+        if.true
+        exec.double
+        else
+        push.2
+        end
+        end
+        ",
+        &FormatOptions {
+            indent: Indent::Spaces(4),
+            ..FormatOptions::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        formatted,
+        "proc.double
+    dup
+    add
+end
+
+begin
+    push.1
+    if.true
+        exec.double
+    else
+        push.2
+    end
+end
+"
+    );
+}
+
+#[test]
+fn test_format_comments_and_imports() {
+    let formatted = format_masm(
+        "use.std::sys
+use.std::math::u64
+
+#! Adds one to the top of the stack
+proc.incr  # trailing
+    # a comment
+    add.1
+end",
+        &FormatOptions::default(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        formatted,
+        "use.std::math::u64
+use.std::sys
+
+#! Adds one to the top of the stack
+proc.incr # trailing
+\t# a comment
+\tadd.1
+end
+"
+    );
+}
+
+#[test]
+fn test_format_grouped_immediates() {
+    let options = FormatOptions {
+        layout: Layout::Grouped,
+        immediates: ImmediateStyle::Hex,
+        ..FormatOptions::default()
+    };
+
+    let formatted =
+        format_masm("begin push.10.255 add if.true push.0x01 end end", &options).unwrap();
+    assert_eq!(
+        formatted,
+        "begin\n\tpush.0x0a.0xff add\n\tif.true\n\t\tpush.0x01\n\tend\nend\n"
+    );
+
+    // formatting is idempotent
+    assert_eq!(format_masm(&formatted, &options).unwrap(), formatted);
+
+    let decimal = FormatOptions {
+        immediates: ImmediateStyle::Decimal,
+        ..options
+    };
+    assert_eq!(
+        format_masm(&formatted, &decimal).unwrap(),
+        "begin\n\tpush.10.255 add\n\tif.true\n\t\tpush.1\n\tend\nend\n"
+    );
+}
+
+#[test]
+fn test_format_unbalanced() {
+    assert!(format_masm("begin push.1 end end", &FormatOptions::default()).is_err());
+    assert!(format_masm("begin push.1", &FormatOptions::default()).is_err());
+}
+
+#[test]
+fn test_format_invalid() {
+    let source = "begin push.1 pushh.2 frobnicate end";
+    assert_eq!(
+        format_masm(source, &FormatOptions::default()),
+        Err(parse(tokenize(source)).unwrap_err())
+    );
+}
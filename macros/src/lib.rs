@@ -0,0 +1,184 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Delimiter, Span, TokenStream as TokenStream2, TokenTree};
+use quote::{quote, quote_spanned};
+use rust_masm::{parse, tokenize};
+
+/// A part of a word of Miden assembly: either text, or a Rust expression written in braces whose
+/// value is formatted into the word at runtime.
+enum Part {
+    Text(String),
+    Value(TokenStream2),
+}
+
+/// Writes Miden assembly inline in Rust and expands to the `VecDeque<Instruction>` it parses to,
+/// ready for `MidenProgram::add_program` or `MidenProgram::add_instructions`.
+///
+/// The assembly is checked by the parser at compile time. A Rust expression in braces can be
+/// used wherever the assembly takes a value after a `.`, as in `push.{n}` or `mem_store.{addr}`,
+/// and is formatted into the assembly with `Display` when the macro runs. Rust comments can be
+/// used in place of `#` comments.
+///
+/// # Panics
+///
+/// If an interpolated value makes an instruction invalid, such as `repeat.{n}` with a value that
+/// is not a number.
+///
+/// # Example
+///
+/// ```
+/// use rust_masm::MidenProgram;
+/// use rust_masm_macros::masm;
+///
+/// let n = 5u64;
+/// let mut instructions = masm! {
+///     push.{n} push.1 add
+///     // 5 + 1 is 6, so the first branch runs
+///     push.6 eq
+///     if.true
+///         push.10
+///     else
+///         push.20
+///     end
+/// };
+///
+/// let mut program = MidenProgram::new();
+/// program.add_program(&mut instructions);
+/// assert_eq!(program.get_stack()[0], 10u64.into());
+/// ```
+///
+/// Unknown instructions are rejected when the code is compiled:
+///
+/// ```compile_fail
+/// let instructions = rust_masm_macros::masm! { push.1 not_an_instruction };
+/// ```
+///
+/// Procedures are not supported, as they cannot be added to a program with `add_program`:
+///
+/// ```compile_fail
+/// let instructions = rust_masm_macros::masm! { proc.double dup add end };
+/// ```
+#[proc_macro]
+pub fn masm(input: TokenStream) -> TokenStream {
+    match expand(input.into()) {
+        Ok(tokens) => tokens.into(),
+        Err((span, message)) => quote_spanned!(span=> compile_error!(#message)).into(),
+    }
+}
+
+fn expand(input: TokenStream2) -> Result<TokenStream2, (Span, String)> {
+    let words = words(input)?;
+
+    // every interpolated value is checked as `1`, which is valid for all immediates
+    let checked: Vec<String> = words
+        .iter()
+        .map(|word| {
+            word.iter()
+                .map(|part| match part {
+                    Part::Text(text) => text.as_str(),
+                    Part::Value(_) => "1",
+                })
+                .collect()
+        })
+        .collect();
+    let checked = checked.join(" ");
+
+    let parsed = parse(tokenize(&checked)).map_err(|e| (Span::call_site(), e))?;
+    if !parsed.procedures.is_empty() || !parsed.imports.is_empty() {
+        return Err((
+            Span::call_site(),
+            "masm! only supports instructions, use MidenProgram::parse for procedures and imports"
+                .to_string(),
+        ));
+    }
+
+    let mut format = String::new();
+    let mut values = Vec::new();
+    for (i, word) in words.into_iter().enumerate() {
+        if i > 0 {
+            format.push(' ');
+        }
+        for part in word {
+            match part {
+                Part::Text(text) => format.push_str(&text.replace('{', "{{").replace('}', "}}")),
+                Part::Value(value) => {
+                    format.push_str("{}");
+                    values.push(value);
+                }
+            }
+        }
+    }
+
+    Ok(quote! {
+        {
+            let masm = ::std::format!(#format #(, (#values))*);
+            match ::rust_masm::parse(::rust_masm::tokenize(&masm)) {
+                Ok(parsed) => parsed.instructions,
+                Err(e) => ::std::panic!("masm!: invalid Miden assembly `{}`: {}", masm, e),
+            }
+        }
+    })
+}
+
+/// Splits the tokens into the whitespace separated words of the assembly. Rust splits a word like
+/// `push.1` or `use.std::math::u64` into several tokens, which are joined again after every `.`
+/// and `:`.
+fn words(input: TokenStream2) -> Result<Vec<Vec<Part>>, (Span, String)> {
+    let mut words: Vec<Vec<Part>> = Vec::new();
+    let mut joined = false;
+
+    for token in input {
+        match token {
+            TokenTree::Punct(punct) if matches!(punct.as_char(), '.' | ':') => {
+                match words.last_mut() {
+                    Some(word) if !word.is_empty() => {
+                        word.push(Part::Text(punct.as_char().to_string()));
+                        joined = true;
+                    }
+                    _ => {
+                        return Err((
+                            punct.span(),
+                            format!(
+                                "unexpected `{}` at the start of an instruction",
+                                punct.as_char()
+                            ),
+                        ))
+                    }
+                }
+            }
+            TokenTree::Group(group) if group.delimiter() == Delimiter::Brace => {
+                if !joined {
+                    return Err((
+                        group.span(),
+                        "an interpolated value must follow a `.`, as in `push.{n}`".to_string(),
+                    ));
+                }
+                words.last_mut().unwrap().push(Part::Value(group.stream()));
+                joined = false;
+            }
+            TokenTree::Ident(ident) => {
+                push_text(&mut words, ident.to_string(), joined);
+                joined = false;
+            }
+            TokenTree::Literal(literal) => {
+                let text = literal.to_string();
+                if text.starts_with(|c: char| !c.is_ascii_digit()) {
+                    return Err((literal.span(), format!("unexpected literal `{text}`")));
+                }
+                push_text(&mut words, text, joined);
+                joined = false;
+            }
+            token => {
+                return Err((token.span(), format!("unexpected token `{token}`")));
+            }
+        }
+    }
+
+    Ok(words)
+}
+
+fn push_text(words: &mut Vec<Vec<Part>>, text: String, joined: bool) {
+    match words.last_mut() {
+        Some(word) if joined => word.push(Part::Text(text)),
+        _ => words.push(vec![Part::Text(text)]),
+    }
+}
@@ -0,0 +1,69 @@
+use rust_masm::{Instruction, MidenProgram};
+use rust_masm_macros::masm;
+
+#[test]
+fn test_masm() {
+    let instructions = masm! {
+        push.1 push.2 add
+        u32checked_add
+        push.0x10 mem_store.3
+    };
+    assert_eq!(instructions[0], Instruction::Push(1u64.into()));
+    assert_eq!(instructions.len(), 6);
+
+    let mut program = MidenProgram::new();
+    program.add_program(&mut masm! { push.1.2.3 });
+    assert_eq!(program.get_stack()[0], 3u64.into());
+    assert_eq!(program.get_stack()[2], 1u64.into());
+}
+
+#[test]
+fn test_masm_blocks() {
+    let mut program = MidenProgram::new();
+    program.add_program(&mut masm! {
+        push.0
+        repeat.3
+            push.2 add
+        end
+        dup push.6 eq
+        if.true
+            push.1
+        else
+            push.0
+        end
+        push.3 push.1
+        while.true
+            push.1 sub dup neq.0
+        end
+    });
+    assert_eq!(program.get_stack()[0], 0u64.into());
+    assert_eq!(program.get_stack()[1], 1u64.into());
+    assert_eq!(program.get_stack()[2], 6u64.into());
+}
+
+#[test]
+fn test_masm_interpolation() {
+    let (a, b, times, address) = (7u64, 35u32, 2, 100);
+
+    let mut program = MidenProgram::new();
+    program.add_program(&mut masm! {
+        push.{a}.{b}
+        repeat.{times}
+            dup
+        end
+        mem_store.{address}
+        push.{a * 2}
+    });
+    assert_eq!(program.get_stack()[0], 14u64.into());
+    assert_eq!(program.get_stack()[1], 35u64.into());
+    assert_eq!(program.get_ram_memory()[&100][0], 35u64.into());
+    assert!(program.get_masm().contains("repeat.2"));
+    assert!(program.get_masm().contains("mem_store.100"));
+}
+
+#[test]
+#[should_panic(expected = "invalid Miden assembly")]
+fn test_masm_invalid_value() {
+    let times = "x";
+    masm! { repeat.{times} add end };
+}
@@ -0,0 +1,6 @@
+//! The JavaScript bindings of rust-masm, built with `wasm-pack build wasm`.
+//!
+//! The bindings live in the `wasm` module of rust-masm, this crate only links them into a
+//! WebAssembly module.
+
+pub use rust_masm::*;